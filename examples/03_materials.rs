@@ -33,6 +33,7 @@ material_select!(MyMaterial {
     D(TD=Colored<Diffuse>),
     R(TR=Glossy),
     G(TG=Glowing),
+    T(TT=Refractive),
     L(TL=Colored<Luminous>),
 });
 
@@ -84,6 +85,13 @@ fn main() -> clay::Result<()> {
             Diffuse {}.color_with(Vector3::new(0.1, 0.01, 0.9)),
         )),
     );
+    scene.add(
+        MyShape::from(Ellipsoid::new(
+            0.35 * Matrix3::identity(),
+            Vector3::new(1.0, -1.2, 0.35),
+        ))
+        .cover(MyMaterial::from(Refractive::new(1.5))),
+    );
     scene.add(
         MyShape::from(Ellipsoid::new(
             0.25 * Matrix3::identity(),
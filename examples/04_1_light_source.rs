@@ -1,6 +1,6 @@
 use clay::{
     material::*,
-    material_select,
+    material_pdf_select, material_select,
     object::*,
     prelude::*,
     process::{create_default_postproc, create_renderer},
@@ -22,6 +22,7 @@ material_select!(MyMaterial {
     D(TD=Colored<Diffuse>),
     L(TL=Colored<Luminous>),
 });
+material_pdf_select!(MyMaterial { D, L });
 
 // Here we declare our object - a combination of
 // spherical shape and colored diffuse material
@@ -1,7 +1,7 @@
 use clay::{
     filter::IdentityFilter,
     material::*,
-    material_combine, material_select,
+    material_combine, material_pdf_combine, material_pdf_select, material_select,
     object::*,
     prelude::*,
     process::{create_postproc, create_renderer},
@@ -28,11 +28,17 @@ material_combine!(Glossy {
     reflect: Reflective,
     diffuse: Colored<Diffuse>,
 });
+material_pdf_combine!(Glossy { reflect, diffuse });
 material_select!(MyMaterial {
     Matte(TM=Colored<Diffuse>),
     Glossy(TG=Glossy),
     Luminous(TC=Colored<Luminous>),
 });
+material_pdf_select!(MyMaterial {
+    Matte,
+    Glossy,
+    Luminous,
+});
 type MyObject = Covered<MyShape, MyMaterial>;
 type MyScene = TargetListScene<MyObject, Sphere, GradBg>;
 type MyView = ProjectionView;
@@ -1,7 +1,7 @@
 use clay::{
     filter::*,
     material::*,
-    material_combine, material_select,
+    material_combine, material_pdf_combine, material_pdf_select, material_select,
     object::*,
     prelude::*,
     process::{create_postproc, create_renderer},
@@ -18,17 +18,20 @@ use std::{env, time::Duration};
 shape_select!(MyShape {
     P(TP=Parallelepiped),
     S(TS=Ellipsoid),
+    W(TW=Difference<Parallelepiped, Parallelepiped>),
 });
 material_combine!(Glossy {
     reflect: Reflective,
     diffuse: Colored<Diffuse>,
 });
+material_pdf_combine!(Glossy { reflect, diffuse });
 material_select!(MyMaterial {
     D(TD=Colored<Diffuse>),
     R(TR=Reflective),
     G(TG=Glossy),
     L(TL=Colored<Luminous>),
 });
+material_pdf_select!(MyMaterial { D, R, G, L });
 
 // Here we declare our object - a combination of
 // spherical shape and colored diffuse material
@@ -90,25 +93,24 @@ fn main() -> clay::Result<()> {
         ),
         Vector3::new(1.0, 1.0, 0.4),
     ));
-    // last wall with window
-    let mut wparts = Vec::new();
-    wparts.push(Parallelepiped::new(
-        Matrix3::from_diagonal(&Vector3::new(thc, size.1, 0.5 * wpos)),
-        Vector3::new(size.0 + thc, 0.0, 0.5 * wpos),
-    ));
-    wparts.push(Parallelepiped::new(
-        Matrix3::from_diagonal(&Vector3::new(thc, size.1, size.2 - wsize.1 - 0.5 * wpos)),
-        Vector3::new(size.0 + thc, 0.0, size.2 + wsize.1 + 0.5 * wpos),
-    ));
-    wparts.push(Parallelepiped::new(
-        Matrix3::from_diagonal(&Vector3::new(thc, 0.5 * (size.1 - wsize.0), wsize.1)),
-        Vector3::new(size.0 + thc, 0.5 * (size.1 + wsize.0), wpos + wsize.1),
-    ));
-    wparts.push(Parallelepiped::new(
-        Matrix3::from_diagonal(&Vector3::new(thc, 0.5 * (size.1 - wsize.0), wsize.1)),
-        Vector3::new(size.0 + thc, -0.5 * (size.1 + wsize.0), wpos + wsize.1),
-    ));
+    // last wall with window cut out of it
+    scene.add(
+        MyShape::from(Difference::new(
+            Parallelepiped::new(
+                Matrix3::from_diagonal(&Vector3::new(thc, size.1, size.2)),
+                Vector3::new(size.0 + thc, 0.0, size.2),
+            ),
+            Parallelepiped::new(
+                Matrix3::from_diagonal(&Vector3::new(2.0 * thc, wsize.0, wsize.1)),
+                Vector3::new(size.0 + thc, 0.0, wpos + wsize.1),
+            ),
+        ))
+        .cover(MyMaterial::from(
+            Diffuse {}.color_with(Vector3::new(0.9, 0.9, 0.9)),
+        )),
+    );
     // window cross
+    let mut wparts = Vec::new();
     wparts.push(Parallelepiped::new(
         Matrix3::from_diagonal(&Vector3::new(thc, thc, wsize.1)),
         Vector3::new(size.0 + thc, 0.0, wpos + wsize.1),
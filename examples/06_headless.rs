@@ -0,0 +1,78 @@
+use clay::{
+    material::*,
+    object::*,
+    process::{
+        create_default_postproc, create_renderer, render_to_file, save_image, Budget, ImageFormat,
+    },
+    scene::{GradientBackground as GradBg, ListScene},
+    shape::*,
+    view::ProjectionView,
+};
+use clay_utils::args;
+use nalgebra::{Rotation3, Vector3};
+use std::env;
+
+type MyObject = Covered<Sphere, Colored<Diffuse>>;
+type MyScene = ListScene<MyObject, GradBg>;
+type MyView = ProjectionView;
+
+fn main() -> clay::Result<()> {
+    // Parse args to select OpenCL platform
+    let context = args::parse(env::args())?;
+
+    // Dimensions of the image
+    let dims = (640, 400);
+
+    // Initialize the scene
+    let mut scene = ListScene::new(GradBg::new(
+        Vector3::new(1.0, 1.0, 1.0),
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 1.0),
+    ));
+    scene.add(
+        Sphere::new(0.75, Vector3::new(-0.75, 0.0, 0.0))
+            .cover(Diffuse {}.color_with(Vector3::new(0.4, 1.0, 0.4))),
+    );
+    scene.add(
+        Sphere::new(1.0, Vector3::new(1.0, 0.0, 0.0))
+            .cover(Diffuse {}.color_with(Vector3::new(0.4, 0.4, 1.0))),
+    );
+
+    // Create view
+    let view = ProjectionView::new(
+        Vector3::new(0.25, -3.0, 0.0),
+        Rotation3::face_towards(&-Vector3::y_axis(), &Vector3::z_axis()),
+    );
+
+    // Create renderer, worker and postprocessor
+    let renderer = create_renderer::<MyScene, MyView>().build(dims, scene, view)?;
+    let (mut worker, _) = renderer.create_worker(&context)?;
+    let (mut postproc, _) = create_default_postproc()
+        .collect()?
+        .build_default(&context, dims)?;
+
+    // Render fixed number of samples and save both filtered and HDR images
+    render_to_file(
+        "spheres.png",
+        Budget::Passes(64),
+        &mut worker,
+        &mut postproc,
+        dims,
+    )?;
+    save_image(
+        "spheres.pfm",
+        ImageFormat::Pfm,
+        &worker,
+        &mut postproc,
+        dims,
+    )?;
+    save_image(
+        "spheres.exr",
+        ImageFormat::Exr,
+        &worker,
+        &mut postproc,
+        dims,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use clay::{
+    desc::{DescTargetListScene, SceneDesc},
+    process::{create_default_postproc, create_renderer},
+    view::ProjectionView,
+};
+use clay_utils::{args, FrameCounter};
+use clay_viewer::{Motion, Window};
+use std::{env, time::Duration};
+
+type MyScene = DescTargetListScene;
+type MyView = ProjectionView;
+
+fn main() -> clay::Result<()> {
+    // Parse args to select OpenCL platform
+    let context = args::parse(env::args())?;
+
+    // Dimensions of the window
+    let dims = (1280, 800);
+
+    // Load the scene and the view from description file
+    let desc = SceneDesc::load("examples/scenes/materials.ron")?;
+    let scene = desc.build_target_list_scene()?;
+    let view = desc.build_view();
+
+    // Create renderer and worker
+    let mut renderer = create_renderer::<MyScene, MyView>().build(dims, scene, view)?;
+    let (mut worker, _) = renderer.create_worker(&context)?;
+
+    // Create dummy postprocessor
+    let (mut postproc, _) = create_default_postproc()
+        .collect()?
+        .build_default(&context, dims)?;
+
+    // Create viewer window
+    let mut window = Window::new(dims)?;
+    // Capture mouse
+    window.set_capture_mode(true);
+
+    // Create motion controller
+    let mut motion = Motion::new(renderer.view.pos, renderer.view.ori.clone());
+
+    // Structure for frame rate measurement (optional)
+    let mut frame_counter = FrameCounter::new_with_log(Duration::from_secs(2));
+
+    // Main loop - repeatedly update view and render
+    while !window.poll_with_handler(&mut motion)? {
+        // Render
+        let n = worker.run_for(Duration::from_millis(20))?;
+
+        // Postprocess
+        postproc.process_one(&worker.data().buffer())?;
+        postproc.make_image()?;
+
+        // Draw image to Window
+        window.draw(&postproc.image())?;
+
+        // Measure frame duration
+        let dt = window.step_frame();
+
+        // Check motion occurred
+        if motion.was_updated() {
+            // Clear cumulative buffer
+            worker.data_mut().buffer_mut().clear()?;
+
+            // Move to a new location
+            motion.step(dt);
+
+            // Update view location
+            renderer.view.update(motion.pos(), motion.ori());
+            renderer.view.fov = motion.fov;
+            renderer.update_data(&context, worker.data_mut())?;
+        }
+
+        // Count and print frame rate
+        frame_counter.step_frame(dt, n);
+    }
+
+    Ok(())
+}
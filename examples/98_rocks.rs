@@ -1,7 +1,7 @@
 use clay::{
     filter::*,
     material::*,
-    material_combine, material_select,
+    material_combine, material_pdf_combine, material_pdf_select, material_select,
     object::*,
     prelude::*,
     process::{create_postproc, create_renderer},
@@ -23,16 +23,19 @@ material_combine!(Glossy {
     reflect: Reflective,
     diffuse: Colored<Diffuse>,
 });
+material_pdf_combine!(Glossy { reflect, diffuse });
 material_combine!(Glowing {
     reflect: Reflective,
     diffuse: Colored<Luminous>,
 });
+material_pdf_combine!(Glowing { reflect, diffuse });
 material_select!(MyMaterial {
     D(TD=Colored<Diffuse>),
     G(TG=Glossy),
     F(TF=Glowing),
     L(TL=Colored<Luminous>),
 });
+material_pdf_select!(MyMaterial { D, G, F, L });
 
 // Here we declare our object - a combination of
 // spherical shape and colored diffuse material
@@ -0,0 +1,29 @@
+#pragma once
+
+#include <clay/view/stereo.h>
+
+#define ANAGLYPH_FILTER_ARGS_DEF \
+    int anaglyph_layout
+
+#define ANAGLYPH_FILTER_ARGS \
+    anaglyph_layout
+
+// Composes red/cyan anaglyph from the stereo image. The result is written
+// to both halves of the image, so any of them could be cropped.
+float3 anaglyph_filter_apply(
+    int2 pos, int2 size,
+    __global const float *buffer,
+    ANAGLYPH_FILTER_ARGS_DEF
+) {
+    int2 eye_size = stereo_eye_size(size, anaglyph_layout);
+    int2 left = stereo_eye_pos(pos, size, anaglyph_layout);
+    int2 right = left;
+    if (anaglyph_layout == STEREO_TOP_BOTTOM) {
+        right.y += eye_size.y;
+    } else {
+        right.x += eye_size.x;
+    }
+    float3 lc = vload3(left.x + left.y*size.x, buffer);
+    float3 rc = vload3(right.x + right.y*size.x, buffer);
+    return (float3)(lc.x, rc.y, rc.z);
+}
@@ -3,6 +3,9 @@
 #include <clay_core/matrix.h>
 #include <clay_core/map/map.h>
 
+// `matrix3_load` reads the nine floats as the rows `x`, `y` and `z`
+// and `matrix3_dot` multiplies each row by the vector, so the matrix
+// and its inverse are packed by rows (*see `Linear::pack_to`*).
 
 MAP_RET linear_rel(MAP_ARGS_DEF) {
     matrix3 linear = matrix3_load(fbuf);
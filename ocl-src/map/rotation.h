@@ -0,0 +1,27 @@
+#pragma once
+
+#include <clay_core/matrix.h>
+#include <clay_core/map/map.h>
+
+
+MAP_RET rotation_rel(MAP_ARGS_DEF) {
+    matrix3 rotation = matrix3_load(fbuf);
+    return matrix3_dot(rotation, v);
+}
+
+MAP_RET rotation_abs(MAP_ARGS_DEF) {
+    return rotation_rel(MAP_ARGS);
+}
+
+MAP_RET rotation_rel_inv(MAP_ARGS_DEF) {
+    matrix3 rotation = matrix3_load(fbuf);
+    return matrix3_dot(matrix3_transpose(rotation), v);
+}
+
+MAP_RET rotation_abs_inv(MAP_ARGS_DEF) {
+    return rotation_rel_inv(MAP_ARGS);
+}
+
+MAP_RET rotation_norm(MAP_ARGS_DEF) {
+    return rotation_rel(MAP_ARGS);
+}
@@ -0,0 +1,24 @@
+#pragma once
+
+#include <clay_core/map/map.h>
+
+
+MAP_RET scale3_rel(MAP_ARGS_DEF) {
+    return v*vload3(0, fbuf);
+}
+
+MAP_RET scale3_abs(MAP_ARGS_DEF) {
+    return scale3_rel(MAP_ARGS);
+}
+
+MAP_RET scale3_rel_inv(MAP_ARGS_DEF) {
+    return v/vload3(0, fbuf);
+}
+
+MAP_RET scale3_abs_inv(MAP_ARGS_DEF) {
+    return scale3_rel_inv(MAP_ARGS);
+}
+
+MAP_RET scale3_norm(MAP_ARGS_DEF) {
+    return scale3_rel_inv(MAP_ARGS);
+}
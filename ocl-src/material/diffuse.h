@@ -8,6 +8,19 @@
 
 #define DIFFUSE_COSINE
 
+// Probability density of the direction sampled by `diffuse_bounce` per unit solid angle.
+float diffuse_pdf(float3 norm, float3 dir) {
+    float cos_theta = dot(dir, norm);
+    if (cos_theta <= 0.0f) {
+        return 0.0f;
+    }
+    #ifdef DIFFUSE_COSINE
+    return cos_theta/M_PI_F;
+    #else
+    return 0.5f/M_PI_F;
+    #endif // DIFFUSE_COSINE
+}
+
 MATERIAL_BOUNCE_RET diffuse_bounce(
     MATERIAL_BOUNCE_ARGS_DEF
 ) {
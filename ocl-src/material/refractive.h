@@ -0,0 +1,62 @@
+#pragma once
+
+#include <clay_core/random.h>
+#include <clay_core/material/material.h>
+
+
+#define FRESNEL_EXACT 0
+#define FRESNEL_SCHLICK 1
+
+// Reflectance of the surface for unpolarized light,
+// `eta` is the ratio of refractive indices of incident and transmitted media.
+float fresnel_reflectance(int mode, float eta, float cos_i, float cos_t) {
+    if (mode == FRESNEL_SCHLICK) {
+        float r0 = (eta - 1.0f)/(eta + 1.0f);
+        r0 *= r0;
+        float c = 1.0f - (eta > 1.0f ? cos_t : cos_i);
+        return r0 + (1.0f - r0)*c*c*c*c*c;
+    } else {
+        float rs = (eta*cos_i - cos_t)/(eta*cos_i + cos_t);
+        float rp = (cos_i - eta*cos_t)/(cos_i + eta*cos_t);
+        return 0.5f*(rs*rs + rp*rp);
+    }
+}
+
+MATERIAL_BOUNCE_RET refractive_bounce(
+    MATERIAL_BOUNCE_ARGS_DEF
+) {
+    if (directed) {
+        return false;
+    }
+
+    int mode = ibuf[0];
+    float ior = fbuf[0];
+
+    // The normal is directed outward, so the ray leaves the object
+    // when it is co-directed with the normal.
+    float3 n = norm;
+    float cos_i = -dot(ray.dir, norm);
+    float eta = 1.0f/ior;
+    if (cos_i < 0.0f) {
+        n = -norm;
+        cos_i = -cos_i;
+        eta = ior;
+    }
+
+    float sin2_t = eta*eta*(1.0f - cos_i*cos_i);
+    float cos_t = 0.0f;
+    float refl = 1.0f;
+    if (sin2_t < 1.0f) {
+        cos_t = sqrt(1.0f - sin2_t);
+        refl = fresnel_reflectance(mode, eta, cos_i, cos_t);
+    }
+
+    new_ray->start = pos;
+    if (random_uniform(seed) < refl) {
+        new_ray->dir = ray.dir + 2.0f*cos_i*n;
+    } else {
+        new_ray->dir = normalize(eta*ray.dir + (eta*cos_i - cos_t)*n);
+    }
+    new_ray->color = ray.color;
+    return true;
+}
@@ -0,0 +1,193 @@
+#pragma once
+
+#include <clay_core/random.h>
+#include <clay/shape/aabb.h>
+#include <clay/scene/inside.h>
+#include <clay/scene/roulette.h>
+
+
+#define SCENE_ARGS_DEF \
+    __global const int *object_buffer_int, \
+    __global const float *object_buffer_float, \
+    int objects_count, \
+    int unbounded_count, \
+    \
+    __global const int *node_buffer_int, \
+    __global const float *node_buffer_float, \
+    int nodes_count, \
+    \
+    int max_depth, \
+    int rr_depth, \
+    float rr_min_prob, \
+    \
+    BACKGROUND_ARGS_DEF
+
+#define SCENE_ARGS \
+    object_buffer_int, \
+    object_buffer_float, \
+    objects_count, \
+    unbounded_count, \
+    \
+    node_buffer_int, \
+    node_buffer_float, \
+    nodes_count, \
+    \
+    max_depth, \
+    rr_depth, \
+    rr_min_prob, \
+    \
+    BACKGROUND_ARGS
+
+#define NODE_SIZE_INT 2
+#define NODE_SIZE_FLOAT 6
+
+// The `BVH_STACK_SIZE` is defined by the host that checks
+// that the hierarchy is not too deep for the stack.
+
+
+bool bvh_node_hit(
+    Ray ray, float3 inv_dir,
+    __global const float *fbuf,
+    float max_dist
+) {
+    float enter, exit;
+    float3 norm;
+    return aabb_dist(
+        ray.start, inv_dir,
+        vload3(0, fbuf), vload3(1, fbuf),
+        &enter, &exit, &norm
+    ) && enter < max_dist;
+}
+
+void bvh_object_hit(
+    uint *seed,
+    Ray ray,
+    int i,
+    __global const int *object_buffer_int,
+    __global const float *object_buffer_float,
+    int *hit_idx,
+    float *hit_enter,
+    float *hit_exit,
+    float3 *hit_norm
+) {
+    float enter, exit;
+    float3 norm;
+
+    __global const int *ibuf = object_buffer_int + OBJECT_SIZE_INT*i;
+    __global const float *fbuf = object_buffer_float + OBJECT_SIZE_FLOAT*i;
+
+    bool hit = false;
+    if (ray.origin != i) {
+        hit = __object_hit(seed, ray, ibuf, fbuf, &enter, &exit, &norm);
+    } else if (ray.history & RAY_INSIDE) {
+        hit = inside_object_hit(seed, ray, ibuf, fbuf, &enter, &exit, &norm);
+    }
+    if (hit) {
+        if (enter < *hit_enter) {
+            *hit_enter = enter;
+            *hit_exit = exit;
+            *hit_norm = norm;
+            *hit_idx = i;
+        }
+    }
+}
+
+bool scene_trace(
+    uint *seed,
+    Ray ray,
+    Ray *new_ray,
+    float3 *color,
+    SCENE_ARGS_DEF
+) {
+    int hit_idx = -1;
+    float hit_enter = INFINITY;
+    float hit_exit = 0.0f;
+    float3 hit_norm;
+
+    // Objects without bounds are checked one by one
+    int i = 0;
+    for (i = 0; i < unbounded_count; ++i) {
+        bvh_object_hit(
+            seed, ray, i,
+            object_buffer_int, object_buffer_float,
+            &hit_idx, &hit_enter, &hit_exit, &hit_norm
+        );
+    }
+
+    // Traverse the hierarchy
+    float3 inv_dir = 1.0f/ray.dir;
+    int stack[BVH_STACK_SIZE];
+    int stack_len = 0;
+    if (nodes_count > 0) {
+        stack[stack_len++] = 0;
+    }
+    while (stack_len > 0) {
+        int node = stack[--stack_len];
+        __global const int *nibuf = node_buffer_int + NODE_SIZE_INT*node;
+        __global const float *nfbuf = node_buffer_float + NODE_SIZE_FLOAT*node;
+        if (!bvh_node_hit(ray, inv_dir, nfbuf, hit_enter)) {
+            continue;
+        }
+
+        int count = nibuf[0];
+        if (count > 0) {
+            // Leaf node - check its objects
+            int first = nibuf[1];
+            for (i = first; i < first + count; ++i) {
+                bvh_object_hit(
+                    seed, ray, i,
+                    object_buffer_int, object_buffer_float,
+                    &hit_idx, &hit_enter, &hit_exit, &hit_norm
+                );
+            }
+        } else {
+            // Inner node - left child is stored right after its parent
+            stack[stack_len++] = nibuf[1];
+            stack[stack_len++] = node + 1;
+        }
+    }
+
+    if (hit_idx >= 0) {
+        float3 hit_pos = ray.start + ray.dir*hit_enter;
+
+        __global const int *ibuf = object_buffer_int + OBJECT_SIZE_INT*hit_idx;
+        __global const float *fbuf = object_buffer_float + OBJECT_SIZE_FLOAT*hit_idx;
+        if(__object_bounce(
+            seed, ray, hit_pos, hit_norm,
+            false, (float3)(0.0f), 0.0f,
+            ibuf, fbuf, new_ray, color
+        )) {
+            new_ray->origin = hit_idx;
+            inside_update(new_ray, hit_norm);
+            return true;
+        }
+        return false;
+    }
+
+    // Background
+    *color += __background(ray, BACKGROUND_ARGS);
+    return false;
+}
+
+float3 __scene_trace(
+    uint *seed,
+    Ray ray,
+    SCENE_ARGS_DEF
+) {
+    float3 color = (float3)(0.0f);
+    // Rays that carry no light (e.g. outside of the fisheye circle) are not traced
+    if (all(ray.color == (float3)(0.0f))) {
+        return color;
+    }
+    int i = 0;
+    Ray current_ray = ray;
+    for (i = 0; i < max_depth; ++i) {
+        Ray next_ray = ray_new();
+        bool bounce = scene_trace(seed, current_ray, &next_ray, &color, SCENE_ARGS);
+        if (!bounce || !roulette_survive(seed, &next_ray, i + 1, rr_depth, rr_min_prob)) {
+            break;
+        }
+        current_ray = next_ray;
+    }
+    return color;
+}
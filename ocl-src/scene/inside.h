@@ -0,0 +1,54 @@
+#pragma once
+
+#include <clay_core/ray.h>
+
+
+// The ray propagates inside of its origin object (e.g. after refraction)
+#define RAY_INSIDE (1 << 8)
+
+#define INSIDE_EPS 1e-4f
+
+
+// Finds the point where the ray propagating inside of the object leaves it.
+// The exit distance of the hit from just outside the surface gives the point,
+// and the backward hit from outside of that point gives the outward normal.
+bool inside_object_hit(
+    uint *seed, Ray ray,
+    __global const int *ibuf,
+    __global const float *fbuf,
+    float *enter, float *exit,
+    float3 *norm
+) {
+    float e, x;
+    float3 n;
+
+    Ray r = ray;
+    r.start = ray.start - INSIDE_EPS*ray.dir;
+    if (!__object_hit(seed, r, ibuf, fbuf, &e, &x, &n)) {
+        return false;
+    }
+    float dist = x - INSIDE_EPS;
+    if (dist < INSIDE_EPS) {
+        return false;
+    }
+
+    r.start = ray.start + (dist + INSIDE_EPS)*ray.dir;
+    r.dir = -ray.dir;
+    if (!__object_hit(seed, r, ibuf, fbuf, &e, &x, &n)) {
+        return false;
+    }
+
+    *enter = dist;
+    *exit = dist;
+    *norm = n;
+    return true;
+}
+
+// Marks the secondary ray if it goes inside of the object.
+void inside_update(Ray *new_ray, float3 norm) {
+    if (dot(new_ray->dir, norm) < 0.0f) {
+        new_ray->history |= RAY_INSIDE;
+    } else {
+        new_ray->history &= ~RAY_INSIDE;
+    }
+}
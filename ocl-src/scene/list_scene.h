@@ -1,6 +1,8 @@
 #pragma once
 
 #include <clay_core/random.h>
+#include <clay/scene/inside.h>
+#include <clay/scene/roulette.h>
 
 
 #define SCENE_ARGS_DEF \
@@ -8,6 +10,8 @@
     __global const float *object_buffer_float, \
     int objects_count, \
     int max_depth, \
+    int rr_depth, \
+    float rr_min_prob, \
     \
     BACKGROUND_ARGS_DEF
 
@@ -16,6 +20,8 @@
     object_buffer_float, \
     objects_count, \
     max_depth, \
+    rr_depth, \
+    rr_min_prob, \
     \
     BACKGROUND_ARGS
 
@@ -37,13 +43,16 @@ bool scene_trace(
         float enter, exit;
         float3 norm;
 
-        if (ray.origin == i) {
-            continue;
-        }
-        
         __global const int *ibuf = object_buffer_int + OBJECT_SIZE_INT*i;
         __global const float *fbuf = object_buffer_float + OBJECT_SIZE_FLOAT*i;
-        if (__object_hit(seed, ray, ibuf, fbuf, &enter, &exit, &norm)) {
+
+        bool hit = false;
+        if (ray.origin != i) {
+            hit = __object_hit(seed, ray, ibuf, fbuf, &enter, &exit, &norm);
+        } else if (ray.history & RAY_INSIDE) {
+            hit = inside_object_hit(seed, ray, ibuf, fbuf, &enter, &exit, &norm);
+        }
+        if (hit) {
             if (enter < hit_enter) {
                 hit_enter = enter;
                 hit_exit = exit;
@@ -64,6 +73,7 @@ bool scene_trace(
             ibuf, fbuf, new_ray, color
         )) {
             new_ray->origin = hit_idx;
+            inside_update(new_ray, hit_norm);
             return true;
         }
         return false;
@@ -80,12 +90,16 @@ float3 __scene_trace(
     SCENE_ARGS_DEF
 ) {
     float3 color = (float3)(0.0f);
+    // Rays that carry no light (e.g. outside of the fisheye circle) are not traced
+    if (all(ray.color == (float3)(0.0f))) {
+        return color;
+    }
     int i = 0;
     Ray current_ray = ray;
     for (i = 0; i < max_depth; ++i) {
         Ray next_ray = ray_new();
         bool bounce = scene_trace(seed, current_ray, &next_ray, &color, SCENE_ARGS);
-        if (!bounce) {
+        if (!bounce || !roulette_survive(seed, &next_ray, i + 1, rr_depth, rr_min_prob)) {
             break;
         }
         current_ray = next_ray;
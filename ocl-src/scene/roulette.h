@@ -0,0 +1,24 @@
+#pragma once
+
+#include <clay_core/random.h>
+#include <clay_core/ray.h>
+
+
+// Russian roulette path termination.
+// Returns false if the path is terminated, otherwise compensates the ray color.
+// Negative `rr_depth` disables the roulette.
+bool roulette_survive(
+    uint *seed, Ray *ray, int depth,
+    int rr_depth, float rr_min_prob
+) {
+    if (rr_depth < 0 || depth < rr_depth) {
+        return true;
+    }
+    float3 c = ray->color;
+    float prob = clamp(fmax(c.x, fmax(c.y, c.z)), rr_min_prob, 1.0f);
+    if (random_uniform(seed) >= prob) {
+        return false;
+    }
+    ray->color /= prob;
+    return true;
+}
@@ -1,6 +1,9 @@
 #pragma once
 
 #include <clay_core/random.h>
+#include <clay/scene/inside.h>
+#include <clay/scene/roulette.h>
+#include <clay/material/diffuse.h>
 
 
 #define SCENE_ARGS_DEF \
@@ -14,6 +17,9 @@
     \
     int max_depth, \
     float target_prob, \
+    int mis, \
+    int rr_depth, \
+    float rr_min_prob, \
     \
     BACKGROUND_ARGS_DEF
 
@@ -28,22 +34,91 @@
     \
     max_depth, \
     target_prob, \
+    mis, \
+    rr_depth, \
+    rr_min_prob, \
     \
     BACKGROUND_ARGS
 
 #define TARGET_THRESHOLD 0.1f
 
 #define OBJ_DI 1
-#define OBJ_DF 0
+#define OBJ_DF 1
 #define TAR_DI 1
-#define TAR_DF 1
+#define TAR_DF 3
 
+// Offset of object probability to bounce diffusely
+#define OBJ_DIFFUSE_PROB 0
 
+// Offsets of target selection probability and cumulative probability
+#define TAR_PROB 1
+#define TAR_CDF 2
+
+
+// Chooses the target with probability proportional to its brightness
+// by the binary search over the cumulative distribution.
+int target_choose(
+    uint *seed,
+    __global const float *target_buffer_float,
+    int targets_count
+) {
+    float r = random_uniform(seed);
+    int lo = 0, hi = targets_count - 1;
+    while (lo < hi) {
+        int mid = (lo + hi)/2;
+        if (r < target_buffer_float[TARGET_SIZE_FLOAT*mid + TAR_CDF]) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    return lo;
+}
+
+#define MIS_DISABLED 0
+#define MIS_BALANCE 1
+#define MIS_POWER 2
+
+// Weight of the sample obtained by the strategy with density `a`
+// in presence of the other strategy with density `b` (both include strategy choice probability).
+float mis_weight(int mis, float a, float b) {
+    if (b <= 0.0f) {
+        return 1.0f;
+    }
+    if (mis == MIS_POWER) {
+        a *= a;
+        b *= b;
+    }
+    return a/(a + b);
+}
+
+// Density of the direction sampled by the material of the object per unit solid angle.
+// The diffuse bounces are sampled as by `diffuse_bounce` with the probability
+// reported by the object, and the other bounces are specular.
+float material_pdf(bool diffuse, float3 norm, float3 dir, float diffuse_prob) {
+    return diffuse ? diffuse_prob*diffuse_pdf(norm, dir) : 0.0f;
+}
+
+// Density of the direction to the target per unit solid angle,
+// the `size` is the solid angle of the target divided by `2*pi`
+// (or the inverse density multiplied by `2*pi` if it is not uniform).
+float target_pdf(float target_prob, float choose_prob, float size) {
+    if (size <= 0.0f) {
+        return 0.0f;
+    }
+    return target_prob*choose_prob/(2.0f*M_PI_F*size);
+}
+
+
+// The `pdf` is the density of the ray direction sampled by the material,
+// it is zero if the direction could not be obtained by the target sampling.
+// On return it contains the density for the new ray.
 bool scene_trace(
     uint *seed,
     Ray ray,
     Ray *new_ray,
     float3 *color,
+    float *pdf,
     SCENE_ARGS_DEF
 ) {
     int hit_idx = -1;
@@ -59,17 +134,24 @@ bool scene_trace(
         float enter, exit;
         float3 norm;
 
-        if (ray.origin == i) {
-            continue;
-        }
-        
         __global const int *ibuf = object_buffer_int + OBJECT_SIZE_INT*i;
         __global const float *fbuf = object_buffer_float + OBJECT_SIZE_FLOAT*i;
-        if (__object_hit(
-            seed, ray,
-            ibuf + OBJ_DI, fbuf + OBJ_DF,
-            &enter, &exit, &norm
-        )) {
+
+        bool hit = false;
+        if (ray.origin != i) {
+            hit = __object_hit(
+                seed, ray,
+                ibuf + OBJ_DI, fbuf + OBJ_DF,
+                &enter, &exit, &norm
+            );
+        } else if (ray.history & RAY_INSIDE) {
+            hit = inside_object_hit(
+                seed, ray,
+                ibuf + OBJ_DI, fbuf + OBJ_DF,
+                &enter, &exit, &norm
+            );
+        }
+        if (hit) {
             if (enter < hit_enter) {
                 hit_enter = enter;
                 hit_exit = exit;
@@ -87,10 +169,32 @@ bool scene_trace(
             if (ray.target != hit_idx) {
                 return false;
             }
-        } else if (ray.history & RAY_DIFFUSE) {
-            if (ray.target != tar_idx) {
+        } else if (mis == MIS_DISABLED) {
+            if ((ray.history & RAY_DIFFUSE) && ray.target != tar_idx) {
                 return false;
             }
+        } else if (tar_idx >= 0 && *pdf > 0.0f) {
+            // The target could also be reached by sampling it from the previous point
+            __global const int *tibuf = target_buffer_int + TARGET_SIZE_INT*tar_idx;
+            __global const float *tfbuf = target_buffer_float + TARGET_SIZE_FLOAT*tar_idx;
+            #ifdef __target_pdf
+            float size = __target_pdf(
+                ray.start, ray.dir,
+                tibuf + TAR_DI, tfbuf + TAR_DF
+            );
+            #else
+            // The density is uniform over the directions the target is sampled in
+            float3 dir;
+            float size = __target_sample(
+                seed, ray.start,
+                tibuf + TAR_DI, tfbuf + TAR_DF,
+                &dir
+            );
+            #endif // __target_pdf
+            ray.color *= mis_weight(
+                mis, (1.0f - target_prob)*(*pdf),
+                target_pdf(target_prob, tfbuf[TAR_PROB], size)
+            );
         }
 
         float3 hit_pos = ray.start + ray.dir*hit_enter;
@@ -99,13 +203,16 @@ bool scene_trace(
         int target = -1;
         bool directed = false;
         float target_size = 0.0f;
+        float target_weight = 0.0f;
+        float target_choose_prob = 0.0f;
         float3 target_dir = (float3)(0.0f);
         if (random_uniform(seed) < target_prob) {
-            int target_idx = floor(random_uniform(seed)*targets_count);
+            int target_idx = target_choose(seed, target_buffer_float, targets_count);
             __global const int *tibuf = target_buffer_int + TARGET_SIZE_INT*target_idx;
             __global const float *tfbuf = target_buffer_float + TARGET_SIZE_FLOAT*target_idx;
 
-            //float brightness = tfbuf[0];
+            target_choose_prob = tfbuf[TAR_PROB];
+            target_weight = 1.0f/target_choose_prob;
             target = tibuf[0];
             target_size = __target_sample(
                 seed, hit_pos,
@@ -115,28 +222,51 @@ bool scene_trace(
             directed = true;
         }
 
-        // Bounce from material
+        // Bounce from material, the diffuse flag is cleared
+        // to find out whether this particular bounce is diffuse
+        Ray bounce_ray = ray;
+        bounce_ray.history &= ~RAY_DIFFUSE;
+        new_ray->history = bounce_ray.history;
         bool bounce = __object_bounce(
-            seed, ray, hit_pos, hit_norm,
+            seed, bounce_ray, hit_pos, hit_norm,
             directed, target_dir, target_size,
             oibuf + OBJ_DI, ofbuf + OBJ_DF, new_ray, color
         );
+        bool diffuse = (new_ray->history & RAY_DIFFUSE) != 0;
+        new_ray->history |= ray.history & RAY_DIFFUSE;
+
         if (bounce && !(ray.history & RAY_TARGETED)) {
             new_ray->origin = hit_idx;
+            inside_update(new_ray, hit_norm);
+            float mat_pdf = material_pdf(
+                diffuse, hit_norm, new_ray->dir,
+                ofbuf[OBJ_DIFFUSE_PROB]
+            );
             if (directed) {
                 new_ray->target = target;
                 new_ray->history |= RAY_TARGETED;
-                new_ray->color *= targets_count/target_prob;
+                new_ray->color *= target_weight/target_prob;
+                if (mis != MIS_DISABLED) {
+                    new_ray->color *= mis_weight(
+                        mis, target_pdf(target_prob, target_choose_prob, target_size),
+                        (1.0f - target_prob)*mat_pdf
+                    );
+                }
+                *pdf = 0.0f;
             } else {
                 new_ray->color *= 1.0f/(1.0f - target_prob);
+                *pdf = mat_pdf;
             }
             return true;
         } else {
             return false;
         }
     } else {
-        // Background
-        *color += __background(ray, BACKGROUND_ARGS);
+        // Background, the targeted rays that miss their targets don't gather it,
+        // because the background in their direction is gathered by the material sampling
+        if (!(ray.history & RAY_TARGETED)) {
+            *color += __background(ray, BACKGROUND_ARGS);
+        }
         return false;
     }
 }
@@ -147,13 +277,18 @@ float3 __scene_trace(
     SCENE_ARGS_DEF
 ) {
     float3 color = (float3)(0.0f);
+    // Rays that carry no light (e.g. outside of the fisheye circle) are not traced
+    if (all(ray.color == (float3)(0.0f))) {
+        return color;
+    }
     Ray current_ray = ray;
+    float pdf = 0.0f;
     int i = 0;
     for (i = 0; i < max_depth; ++i) {
         Ray next_ray = ray_new();
         next_ray.history = current_ray.history;
-        bool bounce = scene_trace(seed, current_ray, &next_ray, &color, SCENE_ARGS);
-        if (!bounce) {
+        bool bounce = scene_trace(seed, current_ray, &next_ray, &color, &pdf, SCENE_ARGS);
+        if (!bounce || !roulette_survive(seed, &next_ray, i + 1, rr_depth, rr_min_prob)) {
             break;
         }
         current_ray = next_ray;
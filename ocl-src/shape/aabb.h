@@ -0,0 +1,49 @@
+#pragma once
+
+#include <clay_core/shape/shape.h>
+
+
+bool aabb_dist(
+    float3 start, float3 inv_dir,
+    float3 cmin, float3 cmax,
+    float *enter, float *exit,
+    float3 *norm
+) {
+    float3 vmin = (cmin - start)*inv_dir;
+    float3 vmax = (cmax - start)*inv_dir;
+
+    float3 near = fmin(vmin, vmax);
+    float3 far = fmax(vmin, vmax);
+
+    float dist_in = fmax(fmax(near.x, near.y), near.z);
+    float dist_out = fmin(fmin(far.x, far.y), far.z);
+
+    if (dist_out < 0.0f || dist_in > dist_out) {
+        return false;
+    }
+
+    *norm = (float3)(0.0f);
+    if (dist_in == near.x) {
+        norm->x = -sign(inv_dir.x);
+    } else if (dist_in == near.y) {
+        norm->y = -sign(inv_dir.y);
+    } else {
+        norm->z = -sign(inv_dir.z);
+    }
+
+    *enter = dist_in;
+    *exit = dist_out;
+    return true;
+}
+
+// Unlike solid shapes the box is also hit
+// if the ray starts inside it (the enter distance is negative then).
+SHAPE_HIT_RET aabb_hit(
+    SHAPE_HIT_ARGS_DEF
+) {
+    return aabb_dist(
+        ray.start, 1.0f/ray.dir,
+        vload3(0, fbuf), vload3(1, fbuf),
+        enter, exit, norm
+    );
+}
@@ -0,0 +1,67 @@
+#pragma once
+
+#include <clay_core/shape/shape.h>
+#include <clay/shape/cylinder.h>
+
+
+// Finds the interval where the ray is inside of the unit sphere centered at `pos`.
+bool _sphere_interval(Ray ray, float3 pos, float *t0, float *t1) {
+    float3 s = ray.start - pos;
+    float b = -dot(ray.dir, s);
+    float c = dot(s, s) - 1.0f;
+    float d = b*b - c;
+    if (d < 0.0f) {
+        return false;
+    }
+    d = sqrt(d);
+    *t0 = b - d;
+    *t1 = b + d;
+    return true;
+}
+
+// Unit capsule - cylinder of radius one along `z` axis with hemispherical ends
+// centered at `(0, 0, -h)` and `(0, 0, h)`, where `h` is the first float of the buffer.
+// The capsule is convex, so the ray interval inside of it is the union of
+// the intervals of the cylindrical part and the both spheres.
+SHAPE_HIT_RET unit_capsule_hit(
+    SHAPE_HIT_ARGS_DEF
+) {
+    float h = fbuf[0];
+    float dist_in = INFINITY, dist_out = -INFINITY;
+    float3 norm_in = (float3)(0.0f);
+    float t0, t1, s0, s1;
+
+    if (
+        _cylinder_interval(ray, &t0, &t1) &&
+        _slab_interval(ray, h, &s0, &s1)
+    ) {
+        t0 = max(t0, s0);
+        t1 = min(t1, s1);
+        if (t0 <= t1) {
+            dist_in = t0;
+            dist_out = t1;
+            float3 p = ray.start + ray.dir*t0;
+            norm_in = (float3)(p.x, p.y, 0.0f);
+        }
+    }
+
+    int i = 0;
+    for (i = 0; i < 2; ++i) {
+        float3 pos = (float3)(0.0f, 0.0f, i == 0 ? -h : h);
+        if (_sphere_interval(ray, pos, &t0, &t1)) {
+            if (t0 < dist_in) {
+                dist_in = t0;
+                norm_in = ray.start + ray.dir*t0 - pos;
+            }
+            dist_out = max(dist_out, t1);
+        }
+    }
+
+    if (dist_in < 0.0f || dist_in > dist_out) {
+        return false;
+    }
+    *enter = dist_in;
+    *exit = dist_out;
+    *norm = norm_in;
+    return true;
+}
@@ -0,0 +1,70 @@
+#pragma once
+
+#include <clay_core/shape/shape.h>
+#include <clay/shape/cylinder.h>
+
+
+// Unit cone with the apex at `(0, 0, 1)` and the unit disk base at `z = -1`.
+// The cone is solid, i.e. its base is capped.
+SHAPE_HIT_RET unit_cone_hit(
+    SHAPE_HIT_ARGS_DEF
+) {
+    float s0, s1;
+    if (!_slab_interval(ray, 1.0f, &s0, &s1)) {
+        return false;
+    }
+
+    // Radius of the cone section is `w = (1 - z)/2`,
+    // the double cone is `x^2 + y^2 - w^2 <= 0` or `a*t^2 + 2*b*t + c <= 0`.
+    float w0 = 0.5f*(1.0f - ray.start.z);
+    float a = ray.dir.x*ray.dir.x + ray.dir.y*ray.dir.y - 0.25f*ray.dir.z*ray.dir.z;
+    float b = ray.start.x*ray.dir.x + ray.start.y*ray.dir.y + 0.5f*w0*ray.dir.z;
+    float c = ray.start.x*ray.start.x + ray.start.y*ray.start.y - w0*w0;
+
+    // Within the slab only the lower nappe remains, so the result is a single interval.
+    float c0 = -INFINITY, c1 = INFINITY;
+    if (a == 0.0f) {
+        if (b > 0.0f) {
+            c1 = -0.5f*c/b;
+        } else if (b < 0.0f) {
+            c0 = -0.5f*c/b;
+        } else if (c > 0.0f) {
+            return false;
+        }
+    } else {
+        float d = b*b - a*c;
+        if (d < 0.0f) {
+            if (a > 0.0f) {
+                return false;
+            }
+        } else {
+            d = sqrt(d);
+            float r0 = (-b - d)/a;
+            float r1 = (-b + d)/a;
+            if (a > 0.0f) {
+                c0 = r0;
+                c1 = r1;
+            } else if (r1 > s0) {
+                // Roots are swapped for negative `a`, take the part before the first one
+                c1 = r1;
+            } else {
+                c0 = r0;
+            }
+        }
+    }
+
+    float dist_in = max(c0, s0);
+    float dist_out = min(c1, s1);
+    if (dist_in < 0.0f || dist_in > dist_out) {
+        return false;
+    }
+    *enter = dist_in;
+    *exit = dist_out;
+    if (c0 > s0) {
+        float3 p = ray.start + ray.dir*dist_in;
+        *norm = normalize((float3)(p.x, p.y, 0.25f*(1.0f - p.z)));
+    } else {
+        *norm = (float3)(0.0f, 0.0f, -sign(ray.dir.z));
+    }
+    return true;
+}
@@ -0,0 +1,143 @@
+#pragma once
+
+#include <clay_core/shape/shape.h>
+
+
+#define CSG_UNION 0
+#define CSG_INTERSECTION 1
+#define CSG_DIFFERENCE 2
+
+#define CSG_EPS 1e-4f
+
+// Interval of the ray inside of an operand, the `enter` distance
+// is negative if the ray starts inside of the operand.
+typedef struct {
+    int hit;
+    float enter, exit;
+    float3 norm;
+} CsgInterval;
+
+// Normal of the piece is taken from the entry point of the first or the second
+// operand, or from the exit point of the second operand (inverted).
+#define CSG_NORM_FIRST 0
+#define CSG_NORM_SECOND 1
+#define CSG_NORM_SECOND_EXIT 2
+
+// Combines the intervals of the operands and finds the first piece of the result
+// that the ray enters ahead. Returns the source of its normal or -1 if there is no such piece.
+int csg_combine(int op, CsgInterval a, CsgInterval b, float *enter, float *exit) {
+    float pieces_enter[2], pieces_exit[2];
+    int pieces_norm[2];
+    int count = 0;
+
+    if (op == CSG_UNION) {
+        if (a.hit && b.hit && a.enter <= b.exit && b.enter <= a.exit) {
+            pieces_enter[0] = min(a.enter, b.enter);
+            pieces_exit[0] = max(a.exit, b.exit);
+            pieces_norm[0] = a.enter <= b.enter ? CSG_NORM_FIRST : CSG_NORM_SECOND;
+            count = 1;
+        } else {
+            if (a.hit) {
+                pieces_enter[count] = a.enter;
+                pieces_exit[count] = a.exit;
+                pieces_norm[count] = CSG_NORM_FIRST;
+                ++count;
+            }
+            if (b.hit) {
+                pieces_enter[count] = b.enter;
+                pieces_exit[count] = b.exit;
+                pieces_norm[count] = CSG_NORM_SECOND;
+                ++count;
+            }
+            if (count == 2 && b.enter < a.enter) {
+                pieces_enter[0] = b.enter;
+                pieces_exit[0] = b.exit;
+                pieces_norm[0] = CSG_NORM_SECOND;
+                pieces_enter[1] = a.enter;
+                pieces_exit[1] = a.exit;
+                pieces_norm[1] = CSG_NORM_FIRST;
+            }
+        }
+    } else if (op == CSG_INTERSECTION) {
+        if (a.hit && b.hit) {
+            pieces_enter[0] = max(a.enter, b.enter);
+            pieces_exit[0] = min(a.exit, b.exit);
+            pieces_norm[0] = a.enter >= b.enter ? CSG_NORM_FIRST : CSG_NORM_SECOND;
+            count = pieces_enter[0] <= pieces_exit[0];
+        }
+    } else {
+        if (a.hit && !b.hit) {
+            pieces_enter[0] = a.enter;
+            pieces_exit[0] = a.exit;
+            pieces_norm[0] = CSG_NORM_FIRST;
+            count = 1;
+        } else if (a.hit) {
+            if (a.enter < b.enter) {
+                pieces_enter[count] = a.enter;
+                pieces_exit[count] = min(a.exit, b.enter);
+                pieces_norm[count] = CSG_NORM_FIRST;
+                ++count;
+            }
+            if (b.exit < a.exit) {
+                bool carved = b.exit > a.enter;
+                pieces_enter[count] = carved ? b.exit : a.enter;
+                pieces_exit[count] = a.exit;
+                pieces_norm[count] = carved ? CSG_NORM_SECOND_EXIT : CSG_NORM_FIRST;
+                ++count;
+            }
+        }
+    }
+
+    int i = 0;
+    for (i = 0; i < count; ++i) {
+        if (pieces_enter[i] >= 0.0f) {
+            *enter = pieces_enter[i];
+            *exit = pieces_exit[i];
+            return pieces_norm[i];
+        }
+    }
+    return -1;
+}
+
+// Finds the interval of the ray inside of the operand `shape`.
+// If the ray misses, it is moved back by `probe` distance (the size of
+// the operand bounds) to check whether it starts inside of the operand.
+#define CSG_OPERAND_HIT(shape, probe, ibuf, fbuf, res) { \
+    res.hit = shape##_hit(seed, ray, ibuf, fbuf, &res.enter, &res.exit, &res.norm); \
+    if (!res.hit && probe > 0.0f) { \
+        Ray pr = ray; \
+        pr.start -= probe*ray.dir; \
+        if (shape##_hit(seed, pr, ibuf, fbuf, &res.enter, &res.exit, &res.norm)) { \
+            res.enter -= probe; \
+            res.exit -= probe; \
+            res.hit = res.enter <= 0.0f && res.exit >= 0.0f; \
+        } \
+    } \
+}
+
+// Defines the hit function `self##_hit` of the combination of `first` and `second` shapes.
+// The float buffer starts with the probe distances of the operands, then the operands follow.
+#define CSG_FN_DEF(self, op, first, second, first_size_int, first_size_float) \
+SHAPE_HIT_RET self##_hit(SHAPE_HIT_ARGS_DEF) { \
+    __global const int *ibuf_a = ibuf; \
+    __global const float *fbuf_a = fbuf + 2; \
+    __global const int *ibuf_b = ibuf + (first_size_int); \
+    __global const float *fbuf_b = fbuf + 2 + (first_size_float); \
+    CsgInterval a, b; \
+    CSG_OPERAND_HIT(first, fbuf[0], ibuf_a, fbuf_a, a); \
+    CSG_OPERAND_HIT(second, fbuf[1], ibuf_b, fbuf_b, b); \
+    int src = csg_combine(op, a, b, enter, exit); \
+    if (src == CSG_NORM_FIRST) { \
+        *norm = a.norm; \
+    } else if (src == CSG_NORM_SECOND) { \
+        *norm = b.norm; \
+    } else if (src == CSG_NORM_SECOND_EXIT) { \
+        Ray br = ray; \
+        br.start = ray.start + ray.dir*(*enter + CSG_EPS); \
+        br.dir = -ray.dir; \
+        float e, x; \
+        float3 n; \
+        *norm = second##_hit(seed, br, ibuf_b, fbuf_b, &e, &x, &n) ? -n : -ray.dir; \
+    } \
+    return src >= 0; \
+}
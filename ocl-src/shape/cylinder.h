@@ -0,0 +1,86 @@
+#pragma once
+
+#include <clay_core/shape/shape.h>
+
+
+// Finds the interval where the ray is inside of the infinite
+// unit cylinder `x^2 + y^2 <= 1` along `z` axis.
+bool _cylinder_interval(Ray ray, float *t0, float *t1) {
+    // a*t^2 + 2*b*t + c = 0
+    float a = ray.dir.x*ray.dir.x + ray.dir.y*ray.dir.y;
+    float b = ray.start.x*ray.dir.x + ray.start.y*ray.dir.y;
+    float c = ray.start.x*ray.start.x + ray.start.y*ray.start.y - 1.0f;
+    if (a == 0.0f) {
+        *t0 = -INFINITY;
+        *t1 = INFINITY;
+        return c <= 0.0f;
+    }
+    float d = b*b - a*c;
+    if (d < 0.0f) {
+        return false;
+    }
+    d = sqrt(d);
+    *t0 = (-b - d)/a;
+    *t1 = (-b + d)/a;
+    return true;
+}
+
+// Finds the interval where the ray is inside of the slab `-h <= z <= h`.
+bool _slab_interval(Ray ray, float h, float *t0, float *t1) {
+    if (ray.dir.z == 0.0f) {
+        *t0 = -INFINITY;
+        *t1 = INFINITY;
+        return fabs(ray.start.z) <= h;
+    }
+    float a = (-h - ray.start.z)/ray.dir.z;
+    float b = (h - ray.start.z)/ray.dir.z;
+    *t0 = min(a, b);
+    *t1 = max(a, b);
+    return true;
+}
+
+// Unit cylinder of radius one and height two along `z` axis.
+// The first integer of the buffer tells whether it is capped (solid) or open.
+// The open cylinder is a two-sided surface, its normal faces the ray.
+SHAPE_HIT_RET unit_cylinder_hit(
+    SHAPE_HIT_ARGS_DEF
+) {
+    float c0, c1, s0, s1;
+    if (
+        !_cylinder_interval(ray, &c0, &c1) ||
+        !_slab_interval(ray, 1.0f, &s0, &s1)
+    ) {
+        return false;
+    }
+
+    if (ibuf[0] != 0) {
+        float dist_in = max(c0, s0);
+        float dist_out = min(c1, s1);
+        if (dist_in < 0.0f || dist_in > dist_out) {
+            return false;
+        }
+        *enter = dist_in;
+        *exit = dist_out;
+        if (c0 > s0) {
+            float3 p = ray.start + ray.dir*dist_in;
+            *norm = (float3)(p.x, p.y, 0.0f);
+        } else {
+            *norm = (float3)(0.0f, 0.0f, -sign(ray.dir.z));
+        }
+        return true;
+    }
+
+    float dist = c0;
+    if (dist < 0.0f || dist < s0 || dist > s1) {
+        dist = c1;
+        if (dist < 0.0f || dist < s0 || dist > s1) {
+            return false;
+        }
+    }
+    float3 p = ray.start + ray.dir*dist;
+    float3 n = (float3)(p.x, p.y, 0.0f);
+    *enter = dist;
+    *exit = dist;
+    *norm = dot(n, ray.dir) > 0.0f ? -n : n;
+    return true;
+}
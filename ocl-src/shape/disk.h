@@ -0,0 +1,94 @@
+#pragma once
+
+#include <clay_core/matrix.h>
+#include <clay_core/shape/shape.h>
+#include <clay_core/shape/target.h>
+#include <clay_core/random.h>
+#include <clay/shape/plane.h>
+
+
+// Two-sided disk of unit radius in the plane `z = 0`.
+SHAPE_HIT_RET unit_disk_hit(
+    SHAPE_HIT_ARGS_DEF
+) {
+    if (!unit_plane_hit(seed, ray, ibuf, fbuf, enter, exit, norm)) {
+        return false;
+    }
+    float3 p = ray.start + ray.dir*(*enter);
+    return p.x*p.x + p.y*p.y <= 1.0f;
+}
+
+// The disk is packed as the affine map of the unit disk,
+// `a` and `b` are its semi-axes and `a x b` is its normal scaled by `area/pi`.
+void disk_target_load(
+    __global const float *fbuf,
+    float3 *a, float3 *b, float3 *center
+) {
+    matrix3 m = matrix3_load(fbuf);
+    *a = (float3)(m.x.x, m.y.x, m.z.x);
+    *b = (float3)(m.x.y, m.y.y, m.z.y);
+    *center = vload3(0, fbuf + 18);
+}
+
+// Solid angle (divided by `2*pi`) that the disk would have if it had
+// the same density per unit solid angle as the direction `dir` to the point
+// at the distance `len2` has, that is `area*|cos(theta)|/(2*pi*len2)`.
+float disk_target_size(float3 a, float3 b, float3 dir, float len2) {
+    if (len2 <= 0.0f) {
+        return 0.0f;
+    }
+    return 0.5f*fabs(dot(cross(a, b), dir))/len2;
+}
+
+// Samples the point uniformly over the disk area, so the density per unit
+// solid angle of the direction to it is `len2/(area*|cos(theta)|)`.
+TARGET_SAMPLE_RET disk_target_sample(
+    TARGET_SAMPLE_ARGS_DEF
+) {
+    float3 a, b, center;
+    disk_target_load(fbuf, &a, &b, &center);
+
+    float r = sqrt(random_uniform(seed));
+    float phi = 2.0f*M_PI_F*random_uniform(seed);
+    float3 p = center + r*(cos(phi)*a + sin(phi)*b);
+
+    float3 d = p - pos;
+    float len2 = dot(d, d);
+    if (len2 <= 0.0f) {
+        *dir = random_sphere(seed);
+        return 0.0f;
+    }
+    *dir = d/sqrt(len2);
+    return disk_target_size(a, b, *dir, len2);
+}
+
+// Density of the direction `dir` from `pos` sampled by `disk_target_sample`
+// in the form of the size returned by it, zero if the direction misses the disk.
+#define disk_target_pdf disk_target_pdf
+TARGET_SAMPLE_RET disk_target_pdf(
+    float3 pos, float3 dir,
+    __global const int *ibuf,
+    __global const float *fbuf
+) {
+    float3 a, b, center;
+    disk_target_load(fbuf, &a, &b, &center);
+
+    float3 n = cross(a, b);
+    float dn = dot(dir, n);
+    if (dn == 0.0f) {
+        return 0.0f;
+    }
+    float t = dot(center - pos, n)/dn;
+    if (t <= 0.0f) {
+        return 0.0f;
+    }
+    // Coordinates of the point in the basis of the semi-axes
+    float3 q = pos + dir*t - center;
+    float n2 = dot(n, n);
+    float x = dot(cross(q, b), n)/n2;
+    float y = dot(cross(a, q), n)/n2;
+    if (x*x + y*y > 1.0f) {
+        return 0.0f;
+    }
+    return disk_target_size(a, b, dir, t*t);
+}
@@ -0,0 +1,185 @@
+#pragma once
+
+#include <clay_core/shape/shape.h>
+
+
+// Height at the grid node.
+float _hf_height(__global const float *hbuf, int w, int i, int j) {
+    return hbuf[j*w + i];
+}
+
+// Height gradient at the grid node by finite differences,
+// the central ones inside of the grid and the one-sided ones at the borders.
+float2 _hf_grad(
+    __global const float *hbuf, int w, int h,
+    float dx, float dy, int i, int j
+) {
+    int i0 = max(i - 1, 0), i1 = min(i + 1, w - 1);
+    int j0 = max(j - 1, 0), j1 = min(j + 1, h - 1);
+    return (float2)(
+        (_hf_height(hbuf, w, i1, j) - _hf_height(hbuf, w, i0, j))/((i1 - i0)*dx),
+        (_hf_height(hbuf, w, i, j1) - _hf_height(hbuf, w, i, j0))/((j1 - j0)*dy)
+    );
+}
+
+// Finds the roots of `a*t^2 + b*t + c` in `[0, l]`, returns their number.
+int _hf_roots(float a, float b, float c, float l, float *roots) {
+    int count = 0;
+    if (fabs(a) < 1e-8f) {
+        if (b != 0.0f) {
+            roots[count++] = -c/b;
+        }
+    } else {
+        float d = b*b - 4.0f*a*c;
+        if (d >= 0.0f) {
+            d = sqrt(d);
+            // Numerically stable form
+            float q = -0.5f*(b + (b < 0.0f ? -d : d));
+            float r0 = q/a;
+            float r1 = q != 0.0f ? c/q : r0;
+            roots[count++] = min(r0, r1);
+            roots[count++] = max(r0, r1);
+        }
+    }
+    int n = 0, i = 0;
+    for (i = 0; i < count; ++i) {
+        if (roots[i] >= 0.0f && roots[i] <= l) {
+            roots[n++] = roots[i];
+        }
+    }
+    return n;
+}
+
+// Heightfield over the square `[-1, 1] x [-1, 1]` in the plane `z = 0`.
+// It is a solid between `z = 0` and the bilinear interpolation of the grid heights.
+//
+// The integer buffer contains the grid dimensions, the float one contains
+// the maximal height and then the heights by rows along `x` axis starting from `y = -1`.
+// The cells are traversed along the ray by the grid DDA, and the ray is intersected
+// with the bilinear patch of each cell. The normal is interpolated from
+// the height gradient at the grid nodes.
+SHAPE_HIT_RET heightfield_hit(
+    SHAPE_HIT_ARGS_DEF
+) {
+    int w = ibuf[0], h = ibuf[1];
+    float zmax = fbuf[0];
+    __global const float *hbuf = fbuf + 1;
+    float dx = 2.0f/(w - 1), dy = 2.0f/(h - 1);
+
+    // Clip the ray by the bounding box
+    float3 inv_dir = 1.0f/ray.dir;
+    float3 vmin = ((float3)(-1.0f, -1.0f, 0.0f) - ray.start)*inv_dir;
+    float3 vmax = ((float3)(1.0f, 1.0f, zmax) - ray.start)*inv_dir;
+    float3 near = min(vmin, vmax);
+    float3 far = max(vmin, vmax);
+    float tb0 = max(near.x, max(near.y, near.z));
+    float tb1 = min(far.x, min(far.y, far.z));
+    if (tb0 > tb1 || tb1 < 0.0f) {
+        return false;
+    }
+
+    float t = max(tb0, 0.0f);
+    float3 p = ray.start + ray.dir*t;
+    float gx = clamp((p.x + 1.0f)/dx, 0.0f, (float)(w - 1));
+    float gy = clamp((p.y + 1.0f)/dy, 0.0f, (float)(h - 1));
+    int i = clamp((int)floor(gx), 0, w - 2);
+    int j = clamp((int)floor(gy), 0, h - 2);
+
+    // Check whether the ray enters the solid through the box side
+    bool entered = false;
+    float s = gx - i, r = gy - j;
+    float base = mix(
+        mix(_hf_height(hbuf, w, i, j), _hf_height(hbuf, w, i + 1, j), s),
+        mix(_hf_height(hbuf, w, i, j + 1), _hf_height(hbuf, w, i + 1, j + 1), s),
+        r
+    );
+    if (p.z <= base) {
+        if (tb0 < 0.0f) {
+            return false;
+        }
+        entered = true;
+        *enter = tb0;
+        if (near.x >= near.y && near.x >= near.z) {
+            *norm = (float3)(-sign(ray.dir.x), 0.0f, 0.0f);
+        } else if (near.y >= near.z) {
+            *norm = (float3)(0.0f, -sign(ray.dir.y), 0.0f);
+        } else {
+            *norm = (float3)(0.0f, 0.0f, -sign(ray.dir.z));
+        }
+    }
+
+    // Grid DDA
+    int step_x = ray.dir.x > 0.0f ? 1 : -1;
+    int step_y = ray.dir.y > 0.0f ? 1 : -1;
+    float tmax_x = INFINITY, tmax_y = INFINITY;
+    float tdelta_x = INFINITY, tdelta_y = INFINITY;
+    if (ray.dir.x != 0.0f) {
+        tmax_x = (-1.0f + (i + (step_x > 0))*dx - ray.start.x)*inv_dir.x;
+        tdelta_x = dx*fabs(inv_dir.x);
+    }
+    if (ray.dir.y != 0.0f) {
+        tmax_y = (-1.0f + (j + (step_y > 0))*dy - ray.start.y)*inv_dir.y;
+        tdelta_y = dy*fabs(inv_dir.y);
+    }
+
+    while (t < tb1) {
+        float tn = min(tb1, min(tmax_x, tmax_y));
+
+        // Intersection with the bilinear patch `f(t) = z(t) - H(t)` relative to `t`
+        float3 q = ray.start + ray.dir*t;
+        float s0 = (q.x + 1.0f)/dx - i, r0 = (q.y + 1.0f)/dy - j;
+        float ds = ray.dir.x/dx, dr = ray.dir.y/dy;
+        float h00 = _hf_height(hbuf, w, i, j);
+        float h10 = _hf_height(hbuf, w, i + 1, j);
+        float h01 = _hf_height(hbuf, w, i, j + 1);
+        float h11 = _hf_height(hbuf, w, i + 1, j + 1);
+        float bs = h10 - h00, br = h01 - h00, c = h00 - h10 - h01 + h11;
+        float f0 = q.z - (h00 + bs*s0 + br*r0 + c*s0*r0);
+        float f1 = ray.dir.z - (bs*ds + br*dr + c*(s0*dr + r0*ds));
+        float f2 = -c*ds*dr;
+
+        float roots[2];
+        int count = _hf_roots(f2, f1, f0, tn - t, roots);
+        int k = 0;
+        for (k = 0; k < count; ++k) {
+            float tau = roots[k];
+            // The surface is entered when `f` decreases and left when it increases
+            float df = f1 + 2.0f*f2*tau;
+            if (!entered && df < 0.0f) {
+                entered = true;
+                *enter = t + tau;
+                float su = clamp(s0 + ds*tau, 0.0f, 1.0f);
+                float rv = clamp(r0 + dr*tau, 0.0f, 1.0f);
+                float2 g = mix(
+                    mix(_hf_grad(hbuf, w, h, dx, dy, i, j), _hf_grad(hbuf, w, h, dx, dy, i + 1, j), su),
+                    mix(_hf_grad(hbuf, w, h, dx, dy, i, j + 1), _hf_grad(hbuf, w, h, dx, dy, i + 1, j + 1), su),
+                    rv
+                );
+                *norm = normalize((float3)(-g.x, -g.y, 1.0f));
+            } else if (entered && df > 0.0f && t + tau > *enter) {
+                *exit = t + tau;
+                return true;
+            }
+        }
+
+        if (tn >= tb1) {
+            break;
+        }
+        t = tn;
+        if (tmax_x < tmax_y) {
+            i += step_x;
+            tmax_x += tdelta_x;
+        } else {
+            j += step_y;
+            tmax_y += tdelta_y;
+        }
+        if (i < 0 || i > w - 2 || j < 0 || j > h - 2) {
+            break;
+        }
+    }
+
+    if (entered) {
+        *exit = tb1;
+    }
+    return entered;
+}
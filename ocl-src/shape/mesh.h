@@ -0,0 +1,66 @@
+#pragma once
+
+#include <clay_core/shape/shape.h>
+#include <clay/shape/triangle.h>
+
+
+#define MESH_FACE_SIZE 6
+
+// Triangle mesh stored in the pack buffers.
+//
+// The integer buffer contains the number of faces, the offset of normals
+// in the float buffer and then the indices of vertices and normals of each face
+// (the normal indices are negative for the flat faces).
+// The float buffer contains vertex positions followed by normals.
+//
+// The faces are two-sided and checked one by one. The enter distance is the nearest hit
+// and the exit one is the next hit, so the closed meshes could be left from inside.
+SHAPE_HIT_RET mesh_hit(
+    SHAPE_HIT_ARGS_DEF
+) {
+    int faces_count = ibuf[0];
+    __global const float *vbuf = fbuf;
+    __global const float *nbuf = fbuf + ibuf[1];
+    __global const int *faces = ibuf + 2;
+
+    float t0 = INFINITY, t1 = INFINITY;
+    float3 n0 = (float3)(0.0f);
+    bool hit = false;
+
+    int i = 0;
+    for (i = 0; i < faces_count; ++i) {
+        __global const int *face = faces + MESH_FACE_SIZE*i;
+        float3 v0 = vload3(face[0], vbuf);
+        float3 v1 = vload3(face[1], vbuf);
+        float3 v2 = vload3(face[2], vbuf);
+
+        float t, u, v;
+        if (!triangle_intersect(ray, v0, v1, v2, &t, &u, &v)) {
+            continue;
+        }
+        if (t < t0) {
+            bool smooth = face[3] >= 0;
+            t1 = t0;
+            t0 = t;
+            n0 = triangle_norm(
+                ray, v0, v1, v2,
+                smooth,
+                smooth ? vload3(face[3], nbuf) : n0,
+                smooth ? vload3(face[4], nbuf) : n0,
+                smooth ? vload3(face[5], nbuf) : n0,
+                u, v
+            );
+            hit = true;
+        } else if (t < t1) {
+            t1 = t;
+        }
+    }
+    if (!hit) {
+        return false;
+    }
+
+    *enter = t0;
+    *exit = isinf(t1) ? t0 : t1;
+    *norm = n0;
+    return true;
+}
@@ -0,0 +1,21 @@
+#pragma once
+
+#include <clay_core/shape/shape.h>
+
+
+// Two-sided plane `z = 0`, the normal always faces the ray.
+SHAPE_HIT_RET unit_plane_hit(
+    SHAPE_HIT_ARGS_DEF
+) {
+    if (ray.dir.z == 0.0f) {
+        return false;
+    }
+    float t = -ray.start.z/ray.dir.z;
+    if (t < 0.0f) {
+        return false;
+    }
+    *enter = t;
+    *exit = t;
+    *norm = (float3)(0.0f, 0.0f, ray.dir.z > 0.0f ? -1.0f : 1.0f);
+    return true;
+}
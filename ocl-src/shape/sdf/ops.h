@@ -0,0 +1,42 @@
+#pragma once
+
+#include <clay/shape/sdf/sdf.h>
+
+
+// Polynomial smooth minimum with the blending radius `k`.
+float sdf_smooth_min(float a, float b, float k) {
+    if (k <= 0.0f) {
+        return min(a, b);
+    }
+    float h = clamp(0.5f + 0.5f*(b - a)/k, 0.0f, 1.0f);
+    return mix(b, a, h) - k*h*(1.0f - h);
+}
+
+// The float buffer of an operator starts with its parameter, then the operands follow.
+#define _SDF_OPS_BUFFERS(first_size_int, first_size_float) \
+    __global const int *ibuf_a = ibuf; \
+    __global const float *fbuf_a = fbuf + 1; \
+    __global const int *ibuf_b = ibuf + (first_size_int); \
+    __global const float *fbuf_b = fbuf + 1 + (first_size_float);
+
+// Defines the distance function `self##_dist` of the smooth union of two distance functions.
+#define SDF_SMOOTH_UNION_FN_DEF(self, first, second, first_size_int, first_size_float) \
+SDF_DIST_RET self##_dist(SDF_DIST_ARGS_DEF) { \
+    _SDF_OPS_BUFFERS(first_size_int, first_size_float) \
+    return sdf_smooth_min( \
+        first##_dist(p, ibuf_a, fbuf_a), \
+        second##_dist(p, ibuf_b, fbuf_b), \
+        fbuf[0] \
+    ); \
+}
+
+// Defines the distance function `self##_dist` of the linear blend of two distance functions.
+#define SDF_BLEND_FN_DEF(self, first, second, first_size_int, first_size_float) \
+SDF_DIST_RET self##_dist(SDF_DIST_ARGS_DEF) { \
+    _SDF_OPS_BUFFERS(first_size_int, first_size_float) \
+    return mix( \
+        first##_dist(p, ibuf_a, fbuf_a), \
+        second##_dist(p, ibuf_b, fbuf_b), \
+        fbuf[0] \
+    ); \
+}
@@ -0,0 +1,55 @@
+#pragma once
+
+#include <clay/shape/sdf/sdf.h>
+
+
+#define MANDELBULB_BAILOUT 2.0f
+
+SDF_DIST_RET sdf_sphere_dist(SDF_DIST_ARGS_DEF) {
+    float3 pos = vload3(0, fbuf);
+    float rad = fbuf[3];
+    return length(p - pos) - rad;
+}
+
+SDF_DIST_RET sdf_box_dist(SDF_DIST_ARGS_DEF) {
+    float3 pos = vload3(0, fbuf);
+    float3 half_size = vload3(1, fbuf);
+    float3 q = fabs(p - pos) - half_size;
+    return length(max(q, 0.0f)) + min(max(q.x, max(q.y, q.z)), 0.0f);
+}
+
+// Torus around `z` axis.
+SDF_DIST_RET sdf_torus_dist(SDF_DIST_ARGS_DEF) {
+    float3 pos = vload3(0, fbuf);
+    float major = fbuf[3];
+    float minor = fbuf[4];
+    float3 r = p - pos;
+    float2 q = (float2)(length(r.xy) - major, r.z);
+    return length(q) - minor;
+}
+
+// Distance estimator of the Mandelbulb fractal centered at the origin.
+SDF_DIST_RET sdf_mandelbulb_dist(SDF_DIST_ARGS_DEF) {
+    int iters = ibuf[0];
+    float power = fbuf[0];
+
+    float3 z = p;
+    float dr = 1.0f;
+    float r = length(z);
+    int i = 0;
+    for (i = 0; i < iters && r > 0.0f && r < MANDELBULB_BAILOUT; ++i) {
+        float theta = acos(z.z/r)*power;
+        float phi = atan2(z.y, z.x)*power;
+        dr = pow(r, power - 1.0f)*power*dr + 1.0f;
+        z = pow(r, power)*(float3)(
+            sin(theta)*cos(phi),
+            sin(theta)*sin(phi),
+            cos(theta)
+        ) + p;
+        r = length(z);
+    }
+    if (r <= 0.0f) {
+        return 0.0f;
+    }
+    return 0.5f*log(r)*r/dr;
+}
@@ -0,0 +1,85 @@
+#pragma once
+
+#include <clay_core/shape/shape.h>
+
+
+// Distance function returns the signed distance from the point to the surface
+// (or its lower bound), it is negative inside.
+#define SDF_DIST_RET float
+#define SDF_DIST_ARGS_DEF float3 p, __global const int *ibuf, __global const float *fbuf
+#define SDF_DIST_ARGS p, ibuf, fbuf
+
+// Finds the interval where the ray is inside of the bounding sphere.
+bool sdf_bound_interval(Ray ray, float3 pos, float rad, float *t0, float *t1) {
+    float3 s = ray.start - pos;
+    float b = -dot(ray.dir, s);
+    float d = b*b - dot(s, s) + rad*rad;
+    if (d < 0.0f) {
+        return false;
+    }
+    d = sqrt(d);
+    *t0 = b - d;
+    *t1 = b + d;
+    return *t1 >= 0.0f;
+}
+
+// Defines the hit function `self##_hit` of the shape given by the distance function `sdf##_dist`.
+//
+// The ray is marched by the distance to the surface inside of the bounding sphere
+// until it gets closer than `eps`, and then it is marched inside of the shape to find the exit.
+// The integer buffer starts with the maximal number of steps, the float one starts with
+// `eps` and the bounding sphere, then the distance function parameters follow.
+#define SDF_SHAPE_FN_DEF(self, sdf) \
+SHAPE_HIT_RET self##_hit(SHAPE_HIT_ARGS_DEF) { \
+    int max_steps = ibuf[0]; \
+    float eps = fbuf[0]; \
+    float3 center = vload3(0, fbuf + 1); \
+    float rad = fbuf[4]; \
+    __global const int *sibuf = ibuf + 1; \
+    __global const float *sfbuf = fbuf + 5; \
+    \
+    float t0, t1; \
+    if (!sdf_bound_interval(ray, center, rad, &t0, &t1)) { \
+        return false; \
+    } \
+    float t = max(t0, 0.0f); \
+    if (sdf##_dist(ray.start + ray.dir*t, sibuf, sfbuf) < 0.0f) { \
+        return false; \
+    } \
+    \
+    int i = 0; \
+    bool hit = false; \
+    for (i = 0; i < max_steps && t <= t1; ++i) { \
+        float d = sdf##_dist(ray.start + ray.dir*t, sibuf, sfbuf); \
+        if (d < eps) { \
+            hit = true; \
+            break; \
+        } \
+        t += d; \
+    } \
+    if (!hit) { \
+        return false; \
+    } \
+    \
+    float3 p = ray.start + ray.dir*t; \
+    float3 ex = (float3)(eps, 0.0f, 0.0f); \
+    float3 ey = (float3)(0.0f, eps, 0.0f); \
+    float3 ez = (float3)(0.0f, 0.0f, eps); \
+    *norm = normalize((float3)( \
+        sdf##_dist(p + ex, sibuf, sfbuf) - sdf##_dist(p - ex, sibuf, sfbuf), \
+        sdf##_dist(p + ey, sibuf, sfbuf) - sdf##_dist(p - ey, sibuf, sfbuf), \
+        sdf##_dist(p + ez, sibuf, sfbuf) - sdf##_dist(p - ez, sibuf, sfbuf) \
+    )); \
+    *enter = t; \
+    \
+    t += 2.0f*eps; \
+    for (i = 0; i < max_steps && t < t1; ++i) { \
+        float d = sdf##_dist(ray.start + ray.dir*t, sibuf, sfbuf); \
+        if (d >= 0.0f) { \
+            break; \
+        } \
+        t += max(-d, eps); \
+    } \
+    *exit = min(t, t1); \
+    return true; \
+}
@@ -29,12 +29,13 @@ SHAPE_HIT_RET unit_sphere_hit(
     return true;
 }
 
-TARGET_SAMPLE_RET sphere_target_sample(
-    TARGET_SAMPLE_ARGS_DEF
+// Samples direction from `pos` uniformly in the cone of the sphere,
+// returns the solid angle of the cone divided by `2*pi`.
+float sphere_cone_sample(
+    uint *seed, float3 pos,
+    float3 spos, float rad,
+    float3 *dir
 ) {
-    float rad = fbuf[0];
-    float3 spos = vload3(0, fbuf + 1);
-
     float3 sdir = spos - pos;
     float len2 = dot(sdir, sdir);
 
@@ -53,3 +54,11 @@ TARGET_SAMPLE_RET sphere_target_sample(
 
     return 1.0f - cos_alpha;
 }
+
+TARGET_SAMPLE_RET sphere_target_sample(
+    TARGET_SAMPLE_ARGS_DEF
+) {
+    float rad = fbuf[0];
+    float3 spos = vload3(0, fbuf + 1);
+    return sphere_cone_sample(seed, pos, spos, rad, dir);
+}
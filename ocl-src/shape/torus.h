@@ -0,0 +1,129 @@
+#pragma once
+
+#include <clay_core/shape/shape.h>
+
+
+#define POLY_BISECT_ITERS 32
+
+// Evaluates the polynomial of degree `n` with coefficients `c[0] + c[1]*t + ... + c[n]*t^n`.
+float _poly_eval(const float *c, int n, float t) {
+    float v = 0.0f;
+    int i = 0;
+    for (i = n; i >= 0; --i) {
+        v = v*t + c[i];
+    }
+    return v;
+}
+
+// Finds the root on the interval where the polynomial changes its sign.
+float _poly_bisect(const float *c, int n, float a, float b, float fa) {
+    int i = 0;
+    for (i = 0; i < POLY_BISECT_ITERS; ++i) {
+        float m = 0.5f*(a + b);
+        float fm = _poly_eval(c, n, m);
+        if ((fm < 0.0f) == (fa < 0.0f)) {
+            a = m;
+            fa = fm;
+        } else {
+            b = m;
+        }
+    }
+    return 0.5f*(a + b);
+}
+
+// Finds the roots of the polynomial on `[a, b]` given the sorted critical points
+// of the polynomial inside of the interval. The polynomial is monotone between them,
+// so each segment contains at most one root. Returns the number of roots found.
+int _poly_roots(
+    const float *c, int n,
+    const float *crit, int crit_count,
+    float a, float b, float *roots
+) {
+    int count = 0;
+    float lo = a;
+    float flo = _poly_eval(c, n, lo);
+    int i = 0;
+    for (i = 0; i <= crit_count; ++i) {
+        float hi = i < crit_count ? crit[i] : b;
+        float fhi = _poly_eval(c, n, hi);
+        if ((flo < 0.0f) != (fhi < 0.0f)) {
+            roots[count++] = _poly_bisect(c, n, lo, hi, flo);
+        }
+        lo = hi;
+        flo = fhi;
+    }
+    return count;
+}
+
+// Unit torus - the major radius is one and the axis is `z`,
+// the minor radius is the first float of the buffer.
+//
+// The ray is moved to the point nearest to the origin to keep the quartic
+// well-conditioned, and the roots are isolated by the roots of its derivatives
+// inside of the bounding sphere and then refined by bisection.
+SHAPE_HIT_RET unit_torus_hit(
+    SHAPE_HIT_ARGS_DEF
+) {
+    float minor = fbuf[0];
+    float bound = 1.0f + minor;
+
+    float tc = -dot(ray.start, ray.dir);
+    float3 o = ray.start + ray.dir*tc;
+    float h2 = bound*bound - dot(o, o);
+    if (h2 <= 0.0f) {
+        return false;
+    }
+    float h = sqrt(h2);
+
+    // (|p|^2 + 1 - minor^2)^2 - 4*(p.x^2 + p.y^2) = 0, the cubic term vanishes
+    float3 d = ray.dir;
+    float k = dot(o, o) + 1.0f - minor*minor;
+    float quartic[5] = {
+        k*k - 4.0f*(o.x*o.x + o.y*o.y),
+        -8.0f*(o.x*d.x + o.y*d.y),
+        2.0f*k - 4.0f*(d.x*d.x + d.y*d.y),
+        0.0f,
+        1.0f,
+    };
+    float cubic[4] = {
+        quartic[1],
+        2.0f*quartic[2],
+        0.0f,
+        4.0f,
+    };
+
+    // Roots of the second derivative are symmetric
+    float inflect[2];
+    int inflect_count = 0;
+    if (quartic[2] < 0.0f) {
+        float s = sqrt(-quartic[2]/6.0f);
+        if (s < h) {
+            inflect[inflect_count++] = -s;
+            inflect[inflect_count++] = s;
+        }
+    }
+    float crit[3];
+    int crit_count = _poly_roots(cubic, 3, inflect, inflect_count, -h, h, crit);
+
+    float roots[4];
+    int count = _poly_roots(quartic, 4, crit, crit_count, -h, h, roots);
+
+    // The quartic is negative inside, so the roots go in enter-exit pairs
+    int i = 0;
+    for (i = 0; i + 1 < count; i += 2) {
+        float dist_in = roots[i] + tc;
+        if (dist_in >= 0.0f) {
+            float3 p = ray.start + ray.dir*dist_in;
+            float3 q = (float3)(p.x, p.y, 0.0f);
+            float ql = length(q);
+            if (ql > 0.0f) {
+                q /= ql;
+            }
+            *enter = dist_in;
+            *exit = roots[i + 1] + tc;
+            *norm = normalize(p - q);
+            return true;
+        }
+    }
+    return false;
+}
@@ -0,0 +1,82 @@
+#pragma once
+
+#include <clay_core/shape/shape.h>
+
+
+#define TRIANGLE_EPS 1e-8f
+
+// Möller–Trumbore ray-triangle intersection,
+// returns the distance and the barycentric coordinates of the hit point.
+bool triangle_intersect(
+    Ray ray, float3 v0, float3 v1, float3 v2,
+    float *t, float *u, float *v
+) {
+    float3 e1 = v1 - v0;
+    float3 e2 = v2 - v0;
+
+    float3 p = cross(ray.dir, e2);
+    float det = dot(e1, p);
+    if (fabs(det) < TRIANGLE_EPS) {
+        return false;
+    }
+    float inv_det = 1.0f/det;
+
+    float3 s = ray.start - v0;
+    *u = dot(s, p)*inv_det;
+    if (*u < 0.0f || *u > 1.0f) {
+        return false;
+    }
+
+    float3 q = cross(s, e1);
+    *v = dot(ray.dir, q)*inv_det;
+    if (*v < 0.0f || *u + *v > 1.0f) {
+        return false;
+    }
+
+    *t = dot(e2, q)*inv_det;
+    return *t >= 0.0f;
+}
+
+// Normal of the two-sided triangle facing the ray,
+// smoothly interpolated over the triangle if `smooth` is set.
+float3 triangle_norm(
+    Ray ray, float3 v0, float3 v1, float3 v2,
+    bool smooth, float3 n0, float3 n1, float3 n2,
+    float u, float v
+) {
+    float3 gnorm = cross(v1 - v0, v2 - v0);
+    float3 n = gnorm;
+    if (smooth) {
+        n = (1.0f - u - v)*n0 + u*n1 + v*n2;
+        if (dot(n, gnorm) < 0.0f) {
+            n = -n;
+        }
+    }
+    if (dot(gnorm, ray.dir) > 0.0f) {
+        n = -n;
+    }
+    return normalize(n);
+}
+
+// The triangle is two-sided, so the normal always faces the ray.
+SHAPE_HIT_RET triangle_hit(
+    SHAPE_HIT_ARGS_DEF
+) {
+    float3 v0 = vload3(0, fbuf);
+    float3 v1 = vload3(1, fbuf);
+    float3 v2 = vload3(2, fbuf);
+
+    float t, u, v;
+    if (!triangle_intersect(ray, v0, v1, v2, &t, &u, &v)) {
+        return false;
+    }
+
+    *enter = t;
+    *exit = t;
+    *norm = triangle_norm(
+        ray, v0, v1, v2,
+        ibuf[0] != 0, vload3(3, fbuf), vload3(4, fbuf), vload3(5, fbuf),
+        u, v
+    );
+    return true;
+}
@@ -0,0 +1,54 @@
+#include <clay_core/ray.h>
+#include <clay_core/random.h>
+#include <clay/view/shutter.h>
+#include <clay/view/screen.h>
+
+
+#define VIEW_ARGS_DEF \
+    float3 view_pos, \
+    float16 view_map, \
+    float view_field, \
+    int view_mapping, \
+    SHUTTER_ARGS_DEF
+
+#define VIEW_ARGS \
+    view_pos, \
+    view_map, \
+    view_field, \
+    view_mapping, \
+    SHUTTER_ARGS
+
+#define FISHEYE_EQUIDISTANT 0
+#define FISHEYE_EQUISOLID 1
+
+
+// Fisheye projection - the circle inscribed into the image height covers
+// the `view_field` angle, rays outside of it carry no light
+// and are dropped by the scene without tracing.
+Ray __view_emit(
+    uint *seed,
+    int2 pos,
+    int2 size,
+    VIEW_ARGS_DEF
+) {
+    shutter_sample(seed, &view_pos, &view_map, SHUTTER_ARGS);
+    float2 v = 2.0f*ptos_rand(seed, pos, size);
+    float r = length(v);
+    Ray ray = ray_new();
+    ray.start = view_pos;
+    ray.color = (float3)(1.0f, 1.0f, 1.0f);
+
+    float theta = 0.0f;
+    if (view_mapping == FISHEYE_EQUISOLID) {
+        theta = 2.0f*asin(min(r*sin(0.25f*view_field), 1.0f));
+    } else {
+        theta = 0.5f*r*view_field;
+    }
+    if (r > 1.0f) {
+        ray.color = (float3)(0.0f);
+    }
+
+    float2 d = r > 0.0f ? v/r : (float2)(0.0f);
+    ray.dir = sin(theta)*(d.x*view_map.s012 + d.y*view_map.s456) - cos(theta)*view_map.s89a;
+    return ray;
+}
@@ -0,0 +1,50 @@
+#pragma once
+
+#include <clay_core/ray.h>
+#include <clay_core/random.h>
+
+
+// Uniformly samples the point of the aperture of unit radius.
+// The aperture is a disk if `blades` is less than three,
+// otherwise it is a regular polygon with vertices on the unit circle
+// and the first one rotated by `rotation` from `x` axis.
+float2 lens_sample(uint *seed, int blades, float rotation) {
+    if (blades < 3) {
+        float phi = 2.0f*M_PI_F*random_uniform(seed);
+        float r = sqrt(random_uniform(seed));
+        return r*(float2)(cos(phi), sin(phi));
+    }
+    // Choose the triangle between the center and the blade edge
+    int k = min((int)(blades*random_uniform(seed)), blades - 1);
+    float step = 2.0f*M_PI_F/blades;
+    float phi = rotation + k*step;
+    float2 a = (float2)(cos(phi), sin(phi));
+    float2 b = (float2)(cos(phi + step), sin(phi + step));
+    float u = random_uniform(seed), v = random_uniform(seed);
+    if (u + v > 1.0f) {
+        u = 1.0f - u;
+        v = 1.0f - v;
+    }
+    return u*a + v*b;
+}
+
+// Emits the ray of the thin lens camera located at `start`.
+// The `dir` is the direction of the pinhole ray scaled to have `1/fov` depth,
+// rays are focused at the `focus` depth if `aperture` is non-zero.
+Ray lens_emit(
+    uint *seed, float3 start, float3 dir, float16 view_map,
+    float fov, float aperture, float focus, int blades, float blade_rotation
+) {
+    Ray ray = ray_new();
+    ray.start = start;
+    if (aperture > 0.0f) {
+        // Thin lens - all rays through the pixel converge at the focal plane
+        float3 target = start + (focus*fov)*dir;
+        float2 l = aperture*lens_sample(seed, blades, blade_rotation);
+        ray.start += l.x*view_map.s012 + l.y*view_map.s456;
+        dir = target - ray.start;
+    }
+    ray.dir = normalize(dir);
+    ray.color = (float3)(1.0f, 1.0f, 1.0f);
+    return ray;
+}
@@ -0,0 +1,33 @@
+#include <clay_core/ray.h>
+#include <clay_core/random.h>
+#include <clay/view/shutter.h>
+#include <clay/view/screen.h>
+
+
+#define VIEW_ARGS_DEF \
+    float3 view_pos, \
+    float16 view_map, \
+    float view_size, \
+    SHUTTER_ARGS_DEF
+
+#define VIEW_ARGS \
+    view_pos, \
+    view_map, \
+    view_size, \
+    SHUTTER_ARGS
+
+
+Ray __view_emit(
+    uint *seed,
+    int2 pos,
+    int2 size,
+    VIEW_ARGS_DEF
+) {
+    shutter_sample(seed, &view_pos, &view_map, SHUTTER_ARGS);
+    float2 v = view_size*ptos_rand(seed, pos, size);
+    Ray ray = ray_new();
+    ray.start = view_pos + v.x*view_map.s012 + v.y*view_map.s456;
+    ray.dir = -view_map.s89a;
+    ray.color = (float3)(1.0f, 1.0f, 1.0f);
+    return ray;
+}
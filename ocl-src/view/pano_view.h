@@ -0,0 +1,35 @@
+#include <clay_core/ray.h>
+#include <clay_core/random.h>
+#include <clay/view/shutter.h>
+
+
+#define VIEW_ARGS_DEF \
+    float3 view_pos, \
+    float16 view_map, \
+    SHUTTER_ARGS_DEF
+
+#define VIEW_ARGS \
+    view_pos, \
+    view_map, \
+    SHUTTER_ARGS
+
+
+// Equirectangular projection - the image width covers the full turn
+// around the view `y` axis and the height goes from the zenith to the nadir.
+Ray __view_emit(
+    uint *seed,
+    int2 pos,
+    int2 size,
+    VIEW_ARGS_DEF
+) {
+    shutter_sample(seed, &view_pos, &view_map, SHUTTER_ARGS);
+    float2 p = convert_float2(pos) + (float2)(random_uniform(seed), random_uniform(seed));
+    p /= convert_float2(size);
+    float phi = 2.0f*M_PI_F*(p.x - 0.5f);
+    float theta = M_PI_F*(0.5f - p.y);
+    Ray ray = ray_new();
+    ray.start = view_pos;
+    ray.dir = cos(theta)*(sin(phi)*view_map.s012 - cos(phi)*view_map.s89a) + sin(theta)*view_map.s456;
+    ray.color = (float3)(1.0f, 1.0f, 1.0f);
+    return ray;
+}
@@ -1,5 +1,8 @@
 #include <clay_core/ray.h>
 #include <clay_core/random.h>
+#include <clay/view/shutter.h>
+#include <clay/view/screen.h>
+#include <clay/view/lens.h>
 
 
 typedef struct {
@@ -10,26 +13,22 @@ typedef struct {
 #define VIEW_ARGS_DEF \
     float3 view_pos, \
     float16 view_map, \
-    float fov
+    float fov, \
+    float aperture, \
+    float focus, \
+    int blades, \
+    float blade_rotation, \
+    SHUTTER_ARGS_DEF
 
 #define VIEW_ARGS \
     view_pos, \
     view_map,\
-    fov
-
-
-float2 ptos(int2 pos, int2 size) {
-    float2 p = convert_float2(pos) - 0.5f*convert_float2(size);
-    p.y = -p.y;
-    return p/(float)size.y;
-}
-
-float2 ptos_rand(uint *seed, int2 pos, int2 size) {
-    float2 p = convert_float2(pos) - 0.5f*convert_float2(size);
-    p.y = -p.y;
-    p += (float2)(random_uniform(seed), random_uniform(seed)) - 0.5f;
-    return p/(float)size.y;
-}
+    fov, \
+    aperture, \
+    focus, \
+    blades, \
+    blade_rotation, \
+    SHUTTER_ARGS
 
 
 Ray __view_emit(
@@ -38,10 +37,11 @@ Ray __view_emit(
     int2 size,
     VIEW_ARGS_DEF
 ) {
+    shutter_sample(seed, &view_pos, &view_map, SHUTTER_ARGS);
     float2 v = ptos_rand(seed, pos, size);
-    Ray ray = ray_new();
-    ray.start = view_pos;
-    ray.dir = normalize(v.x*view_map.s012 + v.y*view_map.s456 - 1.0f/fov*view_map.s89a);
-    ray.color = (float3)(1.0f, 1.0f, 1.0f);
-    return ray;
+    float3 dir = v.x*view_map.s012 + v.y*view_map.s456 - 1.0f/fov*view_map.s89a;
+    return lens_emit(
+        seed, view_pos, dir, view_map,
+        fov, aperture, focus, blades, blade_rotation
+    );
 }
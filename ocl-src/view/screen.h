@@ -0,0 +1,17 @@
+#pragma once
+
+#include <clay_core/random.h>
+
+
+float2 ptos(int2 pos, int2 size) {
+    float2 p = convert_float2(pos) - 0.5f*convert_float2(size);
+    p.y = -p.y;
+    return p/(float)size.y;
+}
+
+float2 ptos_rand(uint *seed, int2 pos, int2 size) {
+    float2 p = convert_float2(pos) - 0.5f*convert_float2(size);
+    p.y = -p.y;
+    p += (float2)(random_uniform(seed), random_uniform(seed)) - 0.5f;
+    return p/(float)size.y;
+}
@@ -0,0 +1,49 @@
+#pragma once
+
+#include <clay_core/random.h>
+
+
+#define SHUTTER_ARGS_DEF \
+    float3 shutter_shift, \
+    float3 shutter_rot, \
+    float shutter_open, \
+    float shutter_close
+
+#define SHUTTER_ARGS \
+    shutter_shift, \
+    shutter_rot, \
+    shutter_open, \
+    shutter_close
+
+
+// Rotates the vector around the unit `axis` by the angle with cosine `c` and sine `s`.
+float3 shutter_rotate(float3 v, float3 axis, float c, float s) {
+    return c*v + s*cross(axis, v) + (1.0f - c)*dot(axis, v)*axis;
+}
+
+// Moves the view frame to the random moment within the shutter interval.
+//
+// The `shutter_shift` is the displacement of the view during the frame,
+// `shutter_rot` is the rotation during the frame relative to the view axes
+// represented as its axis scaled by the angle.
+void shutter_sample(uint *seed, float3 *pos, float16 *map, SHUTTER_ARGS_DEF) {
+    if (length(shutter_shift) == 0.0f && length(shutter_rot) == 0.0f) {
+        return;
+    }
+    float t = mix(shutter_open, shutter_close, random_uniform(seed));
+    *pos += t*shutter_shift;
+
+    float angle = t*length(shutter_rot);
+    if (angle == 0.0f) {
+        return;
+    }
+    float3 axis = normalize(shutter_rot);
+    float c = cos(angle), s = sin(angle);
+    float3 x = map->s012, y = map->s456, z = map->s89a;
+    float3 rx = shutter_rotate((float3)(1.0f, 0.0f, 0.0f), axis, c, s);
+    float3 ry = shutter_rotate((float3)(0.0f, 1.0f, 0.0f), axis, c, s);
+    float3 rz = shutter_rotate((float3)(0.0f, 0.0f, 1.0f), axis, c, s);
+    map->s012 = rx.x*x + rx.y*y + rx.z*z;
+    map->s456 = ry.x*x + ry.y*y + ry.z*z;
+    map->s89a = rz.x*x + rz.y*y + rz.z*z;
+}
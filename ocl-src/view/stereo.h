@@ -0,0 +1,34 @@
+#pragma once
+
+#define STEREO_SIDE_BY_SIDE 0
+#define STEREO_TOP_BOTTOM 1
+
+
+// Size of the image of one eye.
+int2 stereo_eye_size(int2 size, int layout) {
+    if (layout == STEREO_TOP_BOTTOM) {
+        return (int2)(size.x, size.y/2);
+    } else {
+        return (int2)(size.x/2, size.y);
+    }
+}
+
+// Whether the pixel belongs to the right eye image.
+bool stereo_is_right(int2 pos, int2 size, int layout) {
+    int2 eye_size = stereo_eye_size(size, layout);
+    if (layout == STEREO_TOP_BOTTOM) {
+        return pos.y >= eye_size.y;
+    } else {
+        return pos.x >= eye_size.x;
+    }
+}
+
+// Position of the pixel relative to its eye image.
+int2 stereo_eye_pos(int2 pos, int2 size, int layout) {
+    int2 eye_size = stereo_eye_size(size, layout);
+    if (layout == STEREO_TOP_BOTTOM) {
+        return (int2)(pos.x, pos.y % eye_size.y);
+    } else {
+        return (int2)(pos.x % eye_size.x, pos.y);
+    }
+}
@@ -0,0 +1,58 @@
+#include <clay_core/ray.h>
+#include <clay_core/random.h>
+#include <clay/view/shutter.h>
+#include <clay/view/screen.h>
+#include <clay/view/lens.h>
+#include <clay/view/stereo.h>
+
+
+#define VIEW_ARGS_DEF \
+    float3 view_pos, \
+    float16 view_map, \
+    float fov, \
+    float aperture, \
+    float focus, \
+    int blades, \
+    float blade_rotation, \
+    SHUTTER_ARGS_DEF, \
+    float stereo_ipd, \
+    float stereo_convergence, \
+    int stereo_layout
+
+#define VIEW_ARGS \
+    view_pos, \
+    view_map, \
+    fov, \
+    aperture, \
+    focus, \
+    blades, \
+    blade_rotation, \
+    SHUTTER_ARGS, \
+    stereo_ipd, \
+    stereo_convergence, \
+    stereo_layout
+
+
+// Renders the left eye into the left (or top) half of the image
+// and the right eye into the other half.
+Ray __view_emit(
+    uint *seed,
+    int2 pos,
+    int2 size,
+    VIEW_ARGS_DEF
+) {
+    shutter_sample(seed, &view_pos, &view_map, SHUTTER_ARGS);
+    int2 eye_size = stereo_eye_size(size, stereo_layout);
+    bool right = stereo_is_right(pos, size, stereo_layout);
+    int2 eye_pos = stereo_eye_pos(pos, size, stereo_layout);
+
+    float e = (right ? 0.5f : -0.5f)*stereo_ipd;
+    float2 v = ptos_rand(seed, eye_pos, eye_size);
+    // Off-axis frustum - eye images coincide at the convergence distance
+    float3 dir = (v.x - e/(stereo_convergence*fov))*view_map.s012
+        + v.y*view_map.s456 - 1.0f/fov*view_map.s89a;
+    return lens_emit(
+        seed, view_pos + e*view_map.s012, dir, view_map,
+        fov, aperture, focus, blades, blade_rotation
+    );
+}
@@ -0,0 +1,122 @@
+use crate::map::*;
+use nalgebra::Vector3;
+
+/// Map that could be applied on the host.
+pub trait CpuMap {
+    /// Maps relative vector (e.g. direction).
+    fn rel(&self, v: &Vector3<f64>) -> Vector3<f64>;
+    /// Maps absolute vector (e.g. point).
+    fn abs(&self, v: &Vector3<f64>) -> Vector3<f64>;
+    fn rel_inv(&self, v: &Vector3<f64>) -> Vector3<f64>;
+    fn abs_inv(&self, v: &Vector3<f64>) -> Vector3<f64>;
+    /// Maps normal, the result is not normalized.
+    fn norm(&self, v: &Vector3<f64>) -> Vector3<f64>;
+}
+
+impl CpuMap for Shift {
+    fn rel(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        *v
+    }
+    fn abs(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        v + self.0
+    }
+    fn rel_inv(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        *v
+    }
+    fn abs_inv(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        v - self.0
+    }
+    fn norm(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        *v
+    }
+}
+
+impl CpuMap for Scale {
+    fn rel(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        v * self.0
+    }
+    fn abs(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.rel(v)
+    }
+    fn rel_inv(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        v / self.0
+    }
+    fn abs_inv(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.rel_inv(v)
+    }
+    fn norm(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        *v
+    }
+}
+
+impl CpuMap for Scale3 {
+    fn rel(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        v.component_mul(&self.0)
+    }
+    fn abs(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.rel(v)
+    }
+    fn rel_inv(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        v.component_div(&self.0)
+    }
+    fn abs_inv(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.rel_inv(v)
+    }
+    fn norm(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.rel_inv(v)
+    }
+}
+
+impl CpuMap for Rotation {
+    fn rel(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.0 * v
+    }
+    fn abs(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.rel(v)
+    }
+    fn rel_inv(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.0.inverse() * v
+    }
+    fn abs_inv(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.rel_inv(v)
+    }
+    fn norm(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.rel(v)
+    }
+}
+
+impl CpuMap for Linear {
+    fn rel(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.0 * v
+    }
+    fn abs(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.rel(v)
+    }
+    fn rel_inv(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.0.try_inverse().unwrap() * v
+    }
+    fn abs_inv(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.rel_inv(v)
+    }
+    fn norm(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.0.try_inverse().unwrap().transpose() * v
+    }
+}
+
+impl<A: Map + CpuMap, B: Map + CpuMap> CpuMap for Chain<A, B> {
+    fn rel(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.second.rel(&self.first.rel(v))
+    }
+    fn abs(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.second.abs(&self.first.abs(v))
+    }
+    fn rel_inv(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.first.rel_inv(&self.second.rel_inv(v))
+    }
+    fn abs_inv(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.first.abs_inv(&self.second.abs_inv(v))
+    }
+    fn norm(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.second.norm(&self.first.norm(v))
+    }
+}
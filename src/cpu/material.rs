@@ -0,0 +1,132 @@
+use crate::{cpu::*, material::*};
+use nalgebra::Vector3;
+use rand::Rng;
+
+/// Material that could bounce a ray on the host.
+pub trait CpuMaterial {
+    /// Adds the emitted light to `color` and returns the secondary ray if any.
+    ///
+    /// Only `start`, `dir` and `color` of the secondary ray are meaningful.
+    fn bounce<R: Rng>(
+        &self,
+        rng: &mut R,
+        ray: &Ray,
+        pos: &Vector3<f64>,
+        norm: &Vector3<f64>,
+        color: &mut Vector3<f64>,
+    ) -> Option<Ray>;
+}
+
+impl CpuMaterial for Diffuse {
+    fn bounce<R: Rng>(
+        &self,
+        rng: &mut R,
+        ray: &Ray,
+        pos: &Vector3<f64>,
+        norm: &Vector3<f64>,
+        _color: &mut Vector3<f64>,
+    ) -> Option<Ray> {
+        let dir = basis(norm).transpose() * random_hemisphere_cosine(rng);
+        let mut new_ray = Ray::new(*pos, dir);
+        new_ray.color = ray.color;
+        Some(new_ray)
+    }
+}
+
+impl CpuMaterial for Reflective {
+    fn bounce<R: Rng>(
+        &self,
+        _rng: &mut R,
+        ray: &Ray,
+        pos: &Vector3<f64>,
+        norm: &Vector3<f64>,
+        _color: &mut Vector3<f64>,
+    ) -> Option<Ray> {
+        let dir = ray.dir - 2.0 * norm * norm.dot(&ray.dir);
+        let mut new_ray = Ray::new(*pos, dir);
+        new_ray.color = ray.color;
+        Some(new_ray)
+    }
+}
+
+/// Reflectance of the surface, see `fresnel_reflectance` in `refractive.h`.
+pub fn fresnel_reflectance(fresnel: Fresnel, eta: f64, cos_i: f64, cos_t: f64) -> f64 {
+    match fresnel {
+        Fresnel::Schlick => {
+            let r0 = ((eta - 1.0) / (eta + 1.0)).powi(2);
+            let c = 1.0 - if eta > 1.0 { cos_t } else { cos_i };
+            r0 + (1.0 - r0) * c.powi(5)
+        }
+        Fresnel::Exact => {
+            let rs = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+            let rp = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+            0.5 * (rs * rs + rp * rp)
+        }
+    }
+}
+
+impl CpuMaterial for Refractive {
+    fn bounce<R: Rng>(
+        &self,
+        rng: &mut R,
+        ray: &Ray,
+        pos: &Vector3<f64>,
+        norm: &Vector3<f64>,
+        _color: &mut Vector3<f64>,
+    ) -> Option<Ray> {
+        let mut n = *norm;
+        let mut cos_i = -ray.dir.dot(norm);
+        let mut eta = 1.0 / self.ior;
+        if cos_i < 0.0 {
+            n = -n;
+            cos_i = -cos_i;
+            eta = self.ior;
+        }
+
+        let sin2_t = eta * eta * (1.0 - cos_i * cos_i);
+        let mut cos_t = 0.0;
+        let mut refl = 1.0;
+        if sin2_t < 1.0 {
+            cos_t = (1.0 - sin2_t).sqrt();
+            refl = fresnel_reflectance(self.fresnel, eta, cos_i, cos_t);
+        }
+
+        let dir = if rng.gen::<f64>() < refl {
+            ray.dir + 2.0 * cos_i * n
+        } else {
+            (eta * ray.dir + (eta * cos_i - cos_t) * n).normalize()
+        };
+        let mut new_ray = Ray::new(*pos, dir);
+        new_ray.color = ray.color;
+        Some(new_ray)
+    }
+}
+
+impl CpuMaterial for Luminous {
+    fn bounce<R: Rng>(
+        &self,
+        _rng: &mut R,
+        ray: &Ray,
+        _pos: &Vector3<f64>,
+        _norm: &Vector3<f64>,
+        color: &mut Vector3<f64>,
+    ) -> Option<Ray> {
+        *color += ray.color;
+        None
+    }
+}
+
+impl<M: Material + CpuMaterial> CpuMaterial for Colored<M> {
+    fn bounce<R: Rng>(
+        &self,
+        rng: &mut R,
+        ray: &Ray,
+        pos: &Vector3<f64>,
+        norm: &Vector3<f64>,
+        color: &mut Vector3<f64>,
+    ) -> Option<Ray> {
+        let mut r = ray.clone();
+        r.color = ray.color.component_mul(&self.color);
+        self.material.bounce(rng, &r, pos, norm, color)
+    }
+}
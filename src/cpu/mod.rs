@@ -0,0 +1,55 @@
+//! The implementation mirrors the OpenCL code from `ocl-src` and is intended for testing:
+//! the shapes and maps could be checked against analytic answers,
+//! and the rendered images could be statistically compared with the device output.
+
+mod random;
+pub use random::*;
+mod map;
+pub use map::*;
+mod shape;
+pub use shape::*;
+mod sdf;
+pub use sdf::*;
+mod material;
+pub use material::*;
+mod scene;
+pub use scene::*;
+mod view;
+pub use view::*;
+
+use nalgebra::Vector3;
+
+/// Ray of light traced on the host.
+#[derive(Clone, Debug)]
+pub struct Ray {
+    pub start: Vector3<f64>,
+    pub dir: Vector3<f64>,
+    pub color: Vector3<f64>,
+    /// Index of the object the ray was emitted from.
+    pub origin: Option<usize>,
+    /// The ray propagates inside of its origin object.
+    pub inside: bool,
+}
+
+impl Ray {
+    pub fn new(start: Vector3<f64>, dir: Vector3<f64>) -> Self {
+        Self {
+            start,
+            dir,
+            color: Vector3::repeat(1.0),
+            origin: None,
+            inside: false,
+        }
+    }
+}
+
+/// Intersection of a ray with a shape.
+#[derive(Clone, Debug)]
+pub struct Hit {
+    /// Distance to the point where the ray enters the shape.
+    pub enter: f64,
+    /// Distance to the point where the ray exits the shape.
+    pub exit: f64,
+    /// Normal at the enter point.
+    pub norm: Vector3<f64>,
+}
@@ -0,0 +1,39 @@
+use nalgebra::{Matrix3, Vector3};
+use rand::Rng;
+use std::f64::consts::PI;
+
+/// Uniformly distributed direction.
+pub fn random_sphere<R: Rng>(rng: &mut R) -> Vector3<f64> {
+    let phi = 2.0 * PI * rng.gen::<f64>();
+    let z = 2.0 * rng.gen::<f64>() - 1.0;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    Vector3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+/// Uniformly distributed direction inside the cone around `z` axis.
+pub fn random_sphere_cap<R: Rng>(rng: &mut R, cos_alpha: f64) -> Vector3<f64> {
+    let phi = 2.0 * PI * rng.gen::<f64>();
+    let z = 1.0 - (1.0 - cos_alpha) * rng.gen::<f64>();
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    Vector3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+/// Cosine-weighted direction in the hemisphere around `z` axis.
+pub fn random_hemisphere_cosine<R: Rng>(rng: &mut R) -> Vector3<f64> {
+    let phi = 2.0 * PI * rng.gen::<f64>();
+    let r2 = rng.gen::<f64>();
+    let r = r2.sqrt();
+    Vector3::new(r * phi.cos(), r * phi.sin(), (1.0 - r2).max(0.0).sqrt())
+}
+
+/// Orthonormal basis with the given `z` axis, the vectors are the rows of the matrix.
+pub fn basis(z: &Vector3<f64>) -> Matrix3<f64> {
+    let a = if z.x.abs() < 0.5 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    let x = a.cross(z).normalize();
+    let y = z.cross(&x);
+    Matrix3::from_rows(&[x.transpose(), y.transpose(), z.transpose()])
+}
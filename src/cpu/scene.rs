@@ -0,0 +1,205 @@
+use crate::{
+    cpu::*,
+    material::Material,
+    object::*,
+    scene::*,
+    shape::{Aabb, Bounded, Shape},
+};
+use nalgebra::Vector3;
+use rand::Rng;
+
+/// Distance the rays are moved by to get off the surface, see `INSIDE_EPS` in `inside.h`.
+const INSIDE_EPS: f64 = 1e-4;
+
+/// Object that could be traced on the host.
+pub trait CpuObject {
+    fn hit(&self, ray: &Ray) -> Option<Hit>;
+    fn bounce<R: Rng>(
+        &self,
+        rng: &mut R,
+        ray: &Ray,
+        pos: &Vector3<f64>,
+        norm: &Vector3<f64>,
+        color: &mut Vector3<f64>,
+    ) -> Option<Ray>;
+}
+
+impl<S: Shape + CpuShape, M: Material + CpuMaterial> CpuObject for Covered<S, M> {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        self.shape.hit(ray)
+    }
+    fn bounce<R: Rng>(
+        &self,
+        rng: &mut R,
+        ray: &Ray,
+        pos: &Vector3<f64>,
+        norm: &Vector3<f64>,
+        color: &mut Vector3<f64>,
+    ) -> Option<Ray> {
+        self.material.bounce(rng, ray, pos, norm, color)
+    }
+}
+
+/// Background that could be evaluated on the host.
+pub trait CpuBackground {
+    /// Color of the ray that hits nothing.
+    fn color(&self, ray: &Ray) -> Vector3<f64>;
+}
+
+impl CpuBackground for ConstantBackground {
+    fn color(&self, ray: &Ray) -> Vector3<f64> {
+        ray.color.component_mul(&self.color)
+    }
+}
+
+impl CpuBackground for GradientBackground {
+    fn color(&self, ray: &Ray) -> Vector3<f64> {
+        let z = 0.5 * (ray.dir.dot(&self.dir) + 1.0);
+        ray.color
+            .component_mul(&(z * self.front + (1.0 - z) * self.back))
+    }
+}
+
+/// Scene that could be traced on the host.
+pub trait CpuScene {
+    /// Traces the ray through the scene and returns the collected color.
+    fn trace<R: Rng>(&self, rng: &mut R, ray: Ray) -> Vector3<f64>;
+}
+
+/// Finds the point where the ray propagating inside of the object leaves it,
+/// see `inside_object_hit` in `inside.h`.
+pub fn inside_object_hit<O: CpuObject>(object: &O, ray: &Ray) -> Option<Hit> {
+    let mut r = ray.clone();
+    r.start = ray.start - INSIDE_EPS * ray.dir;
+    let dist = object.hit(&r)?.exit - INSIDE_EPS;
+    if dist < INSIDE_EPS {
+        return None;
+    }
+    r.start = ray.start + (dist + INSIDE_EPS) * ray.dir;
+    r.dir = -ray.dir;
+    let hit = object.hit(&r)?;
+    Some(Hit {
+        enter: dist,
+        exit: dist,
+        norm: hit.norm,
+    })
+}
+
+/// Single step of tracing through the objects, see `scene_trace` in `list_scene.h`.
+fn trace_step<O: CpuObject, B: CpuBackground, R: Rng>(
+    objects: &[O],
+    background: &B,
+    rng: &mut R,
+    ray: &Ray,
+    color: &mut Vector3<f64>,
+) -> Option<Ray> {
+    let mut nearest: Option<(usize, Hit)> = None;
+    for (i, object) in objects.iter().enumerate() {
+        let hit = if ray.origin != Some(i) {
+            object.hit(ray)
+        } else if ray.inside {
+            inside_object_hit(object, ray)
+        } else {
+            None
+        };
+        if let Some(hit) = hit {
+            let closer = match &nearest {
+                Some((_, h)) => hit.enter < h.enter,
+                None => true,
+            };
+            if closer {
+                nearest = Some((i, hit));
+            }
+        }
+    }
+
+    match nearest {
+        Some((i, hit)) => {
+            let pos = ray.start + ray.dir * hit.enter;
+            objects[i]
+                .bounce(rng, ray, &pos, &hit.norm, color)
+                .map(|mut new_ray| {
+                    new_ray.origin = Some(i);
+                    new_ray.inside = new_ray.dir.dot(&hit.norm) < 0.0;
+                    new_ray
+                })
+        }
+        None => {
+            *color += background.color(ray);
+            None
+        }
+    }
+}
+
+/// Russian roulette path termination, see `roulette_survive` in `roulette.h`.
+pub fn roulette_survive<R: Rng>(
+    rng: &mut R,
+    ray: &mut Ray,
+    depth: usize,
+    roulette: Option<RussianRoulette>,
+) -> bool {
+    let roulette = match roulette {
+        Some(r) if depth >= r.depth => r,
+        _ => return true,
+    };
+    let prob = ray.color.max().min(1.0).max(roulette.min_prob);
+    if rng.gen::<f64>() >= prob {
+        return false;
+    }
+    ray.color /= prob;
+    true
+}
+
+fn trace_objects<O: CpuObject, B: CpuBackground, R: Rng>(
+    objects: &[O],
+    background: &B,
+    max_depth: usize,
+    roulette: Option<RussianRoulette>,
+    rng: &mut R,
+    ray: Ray,
+) -> Vector3<f64> {
+    let mut color = Vector3::zeros();
+    let mut current_ray = ray;
+    for i in 0..max_depth {
+        match trace_step(objects, background, rng, &current_ray, &mut color) {
+            Some(mut next_ray) => {
+                if !roulette_survive(rng, &mut next_ray, i + 1, roulette) {
+                    break;
+                }
+                current_ray = next_ray;
+            }
+            None => break,
+        }
+    }
+    color
+}
+
+impl<O: Object + CpuObject, B: Background + CpuBackground> CpuScene for ListScene<O, B> {
+    fn trace<R: Rng>(&self, rng: &mut R, ray: Ray) -> Vector3<f64> {
+        trace_objects(
+            self.objects(),
+            self.background(),
+            self.max_depth(),
+            self.russian_roulette(),
+            rng,
+            ray,
+        )
+    }
+}
+
+/// The hierarchy is not used on the host, the objects are checked one by one
+/// which gives the same result as the hierarchy traversal does.
+impl<O: Object + Bounded<Aabb> + CpuObject, B: Background + CpuBackground> CpuScene
+    for BvhScene<O, B>
+{
+    fn trace<R: Rng>(&self, rng: &mut R, ray: Ray) -> Vector3<f64> {
+        trace_objects(
+            self.objects(),
+            self.background(),
+            self.max_depth(),
+            self.russian_roulette(),
+            rng,
+            ray,
+        )
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{cpu::*, shape::*};
+use nalgebra::Vector3;
+
+/// Distance function evaluated on the host.
+pub trait CpuSdf {
+    /// Signed distance from the point to the surface, see `SDF_DIST_ARGS_DEF` in `sdf.h`.
+    fn dist(&self, p: &Vector3<f64>) -> f64;
+}
+
+impl CpuSdf for SdfSphere {
+    fn dist(&self, p: &Vector3<f64>) -> f64 {
+        (p - self.pos).norm() - self.rad
+    }
+}
+
+impl CpuSdf for SdfBox {
+    fn dist(&self, p: &Vector3<f64>) -> f64 {
+        let q = (p - self.pos).abs() - self.half_size;
+        q.map(|x| x.max(0.0)).norm() + q.max().min(0.0)
+    }
+}
+
+impl CpuSdf for SdfTorus {
+    fn dist(&self, p: &Vector3<f64>) -> f64 {
+        let r = p - self.pos;
+        (r.xy().norm() - self.major).hypot(r.z) - self.minor
+    }
+}
+
+/// See `MANDELBULB_BAILOUT` in `primitives.h`.
+const MANDELBULB_BAILOUT: f64 = 2.0;
+
+impl CpuSdf for Mandelbulb {
+    fn dist(&self, p: &Vector3<f64>) -> f64 {
+        let n = self.power;
+        let mut z = *p;
+        let mut dr = 1.0;
+        let mut r = z.norm();
+        for _ in 0..self.iterations {
+            if r <= 0.0 || r >= MANDELBULB_BAILOUT {
+                break;
+            }
+            let theta = (z.z / r).acos() * n;
+            let phi = z.y.atan2(z.x) * n;
+            dr = r.powf(n - 1.0) * n * dr + 1.0;
+            z = r.powf(n)
+                * Vector3::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                )
+                + p;
+            r = z.norm();
+        }
+        if r <= 0.0 {
+            return 0.0;
+        }
+        0.5 * r.ln() * r / dr
+    }
+}
+
+/// See `sdf_smooth_min` in `ops.h`.
+pub fn sdf_smooth_min(a: f64, b: f64, k: f64) -> f64 {
+    if k <= 0.0 {
+        return a.min(b);
+    }
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+    b + (a - b) * h - k * h * (1.0 - h)
+}
+
+impl<A: Sdf + CpuSdf, B: Sdf + CpuSdf> CpuSdf for SmoothUnion<A, B> {
+    fn dist(&self, p: &Vector3<f64>) -> f64 {
+        sdf_smooth_min(self.first.dist(p), self.second.dist(p), self.k)
+    }
+}
+
+impl<A: Sdf + CpuSdf, B: Sdf + CpuSdf> CpuSdf for Blend<A, B> {
+    fn dist(&self, p: &Vector3<f64>) -> f64 {
+        let (a, b) = (self.first.dist(p), self.second.dist(p));
+        a + (b - a) * self.t
+    }
+}
+
+/// Sphere tracing, see `SDF_SHAPE_FN_DEF` in `sdf.h`.
+impl<D: Sdf + CpuSdf> CpuShape for SdfShape<D> {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        let sphere: Sphere = self.bound()?;
+        let (center, rad) = (sphere.0.map.second.0, sphere.0.map.first.0);
+        let s = ray.start - center;
+        let b = -ray.dir.dot(&s);
+        let d = b * b - s.dot(&s) + rad * rad;
+        if d < 0.0 || b + d.sqrt() < 0.0 {
+            return None;
+        }
+        let (t0, t1) = (b - d.sqrt(), b + d.sqrt());
+
+        let eps = self.eps;
+        let dist = |t: f64| self.sdf.dist(&(ray.start + ray.dir * t));
+        let mut t = t0.max(0.0);
+        if dist(t) < 0.0 {
+            return None;
+        }
+        let mut steps = 0;
+        loop {
+            if steps >= self.max_steps || t > t1 {
+                return None;
+            }
+            let d = dist(t);
+            if d < eps {
+                break;
+            }
+            t += d;
+            steps += 1;
+        }
+
+        let p = ray.start + ray.dir * t;
+        let grad = Vector3::from_iterator((0..3).map(|i| {
+            let mut e = Vector3::zeros();
+            e[i] = eps;
+            self.sdf.dist(&(p + e)) - self.sdf.dist(&(p - e))
+        }));
+        let enter = t;
+
+        t += 2.0 * eps;
+        for _ in 0..self.max_steps {
+            if t >= t1 {
+                break;
+            }
+            let d = dist(t);
+            if d >= 0.0 {
+                break;
+            }
+            t += (-d).max(eps);
+        }
+        Some(Hit {
+            enter,
+            exit: t.min(t1),
+            norm: grad.normalize(),
+        })
+    }
+}
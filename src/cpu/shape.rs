@@ -0,0 +1,880 @@
+use crate::{cpu::*, map::*, prelude::*, shape::*};
+use nalgebra::Vector3;
+
+/// Shape that could be hit by a ray on the host.
+pub trait CpuShape {
+    /// Finds the nearest intersection of the ray with the shape.
+    fn hit(&self, ray: &Ray) -> Option<Hit>;
+}
+
+/// Sign function that returns zero for zero like the OpenCL one does.
+fn sign(x: f64) -> f64 {
+    if x > 0.0 {
+        1.0
+    } else if x < 0.0 {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+/// Intersection with the unit sphere, see `unit_sphere_hit` in `sphere.h`.
+pub fn unit_sphere_hit(ray: &Ray) -> Option<Hit> {
+    // t^2 - 2*b*t + c = 0
+    let b = -ray.dir.dot(&ray.start);
+    let c = ray.start.dot(&ray.start) - 1.0;
+    let d = b * b - c;
+    if d < 0.0 {
+        return None;
+    }
+    let d = d.sqrt();
+    let e = b - d;
+    if e < 0.0 {
+        return None;
+    }
+    Some(Hit {
+        enter: e,
+        exit: b + d,
+        norm: ray.start + ray.dir * e,
+    })
+}
+
+/// Intersection with the unit plane, see `unit_plane_hit` in `plane.h`.
+pub fn unit_plane_hit(ray: &Ray) -> Option<Hit> {
+    if ray.dir.z == 0.0 {
+        return None;
+    }
+    let t = -ray.start.z / ray.dir.z;
+    if t < 0.0 {
+        return None;
+    }
+    Some(Hit {
+        enter: t,
+        exit: t,
+        norm: Vector3::new(0.0, 0.0, -sign(ray.dir.z)),
+    })
+}
+
+/// Intersection with the unit disk, see `unit_disk_hit` in `disk.h`.
+pub fn unit_disk_hit(ray: &Ray) -> Option<Hit> {
+    unit_plane_hit(ray).filter(|hit| {
+        let p = ray.start + ray.dir * hit.enter;
+        p.x * p.x + p.y * p.y <= 1.0
+    })
+}
+
+/// Interval inside of the infinite unit cylinder, see `_cylinder_interval` in `cylinder.h`.
+fn cylinder_interval(ray: &Ray) -> Option<(f64, f64)> {
+    let a = ray.dir.x * ray.dir.x + ray.dir.y * ray.dir.y;
+    let b = ray.start.x * ray.dir.x + ray.start.y * ray.dir.y;
+    let c = ray.start.x * ray.start.x + ray.start.y * ray.start.y - 1.0;
+    if a == 0.0 {
+        return if c <= 0.0 {
+            Some((-std::f64::INFINITY, std::f64::INFINITY))
+        } else {
+            None
+        };
+    }
+    let d = b * b - a * c;
+    if d < 0.0 {
+        return None;
+    }
+    let d = d.sqrt();
+    Some(((-b - d) / a, (-b + d) / a))
+}
+
+/// Interval inside of the slab `-h <= z <= h`, see `_slab_interval` in `cylinder.h`.
+fn slab_interval(ray: &Ray, h: f64) -> Option<(f64, f64)> {
+    if ray.dir.z == 0.0 {
+        return if ray.start.z.abs() <= h {
+            Some((-std::f64::INFINITY, std::f64::INFINITY))
+        } else {
+            None
+        };
+    }
+    let a = (-h - ray.start.z) / ray.dir.z;
+    let b = (h - ray.start.z) / ray.dir.z;
+    Some((a.min(b), a.max(b)))
+}
+
+/// Intersection with the unit cylinder, see `unit_cylinder_hit` in `cylinder.h`.
+pub fn unit_cylinder_hit(ray: &Ray, capped: bool) -> Option<Hit> {
+    let (c0, c1) = cylinder_interval(ray)?;
+    let (s0, s1) = slab_interval(ray, 1.0)?;
+
+    if capped {
+        let (dist_in, dist_out) = (c0.max(s0), c1.min(s1));
+        if dist_in < 0.0 || dist_in > dist_out {
+            return None;
+        }
+        let norm = if c0 > s0 {
+            let p = ray.start + ray.dir * dist_in;
+            Vector3::new(p.x, p.y, 0.0)
+        } else {
+            Vector3::new(0.0, 0.0, -sign(ray.dir.z))
+        };
+        return Some(Hit {
+            enter: dist_in,
+            exit: dist_out,
+            norm,
+        });
+    }
+
+    let dist = [c0, c1]
+        .iter()
+        .cloned()
+        .find(|&t| t >= 0.0 && t >= s0 && t <= s1)?;
+    let p = ray.start + ray.dir * dist;
+    let n = Vector3::new(p.x, p.y, 0.0);
+    Some(Hit {
+        enter: dist,
+        exit: dist,
+        norm: if n.dot(&ray.dir) > 0.0 { -n } else { n },
+    })
+}
+
+/// Intersection with the unit cone, see `unit_cone_hit` in `cone.h`.
+pub fn unit_cone_hit(ray: &Ray) -> Option<Hit> {
+    let (s0, s1) = slab_interval(ray, 1.0)?;
+
+    let w0 = 0.5 * (1.0 - ray.start.z);
+    let a = ray.dir.x * ray.dir.x + ray.dir.y * ray.dir.y - 0.25 * ray.dir.z * ray.dir.z;
+    let b = ray.start.x * ray.dir.x + ray.start.y * ray.dir.y + 0.5 * w0 * ray.dir.z;
+    let c = ray.start.x * ray.start.x + ray.start.y * ray.start.y - w0 * w0;
+
+    let (mut c0, mut c1) = (-std::f64::INFINITY, std::f64::INFINITY);
+    if a == 0.0 {
+        if b > 0.0 {
+            c1 = -0.5 * c / b;
+        } else if b < 0.0 {
+            c0 = -0.5 * c / b;
+        } else if c > 0.0 {
+            return None;
+        }
+    } else {
+        let d = b * b - a * c;
+        if d < 0.0 {
+            if a > 0.0 {
+                return None;
+            }
+        } else {
+            let d = d.sqrt();
+            let (r0, r1) = ((-b - d) / a, (-b + d) / a);
+            if a > 0.0 {
+                c0 = r0;
+                c1 = r1;
+            } else if r1 > s0 {
+                c1 = r1;
+            } else {
+                c0 = r0;
+            }
+        }
+    }
+
+    let (dist_in, dist_out) = (c0.max(s0), c1.min(s1));
+    if dist_in < 0.0 || dist_in > dist_out {
+        return None;
+    }
+    let norm = if c0 > s0 {
+        let p = ray.start + ray.dir * dist_in;
+        Vector3::new(p.x, p.y, 0.25 * (1.0 - p.z)).normalize()
+    } else {
+        Vector3::new(0.0, 0.0, -sign(ray.dir.z))
+    };
+    Some(Hit {
+        enter: dist_in,
+        exit: dist_out,
+        norm,
+    })
+}
+
+/// Intersection with the unit capsule, see `unit_capsule_hit` in `capsule.h`.
+pub fn unit_capsule_hit(ray: &Ray, half_len: f64) -> Option<Hit> {
+    let mut dist_in = std::f64::INFINITY;
+    let mut dist_out = -std::f64::INFINITY;
+    let mut norm_in = Vector3::zeros();
+
+    if let (Some((c0, c1)), Some((s0, s1))) = (cylinder_interval(ray), slab_interval(ray, half_len))
+    {
+        let (t0, t1) = (c0.max(s0), c1.min(s1));
+        if t0 <= t1 {
+            dist_in = t0;
+            dist_out = t1;
+            let p = ray.start + ray.dir * t0;
+            norm_in = Vector3::new(p.x, p.y, 0.0);
+        }
+    }
+
+    for &z in [-half_len, half_len].iter() {
+        let pos = Vector3::new(0.0, 0.0, z);
+        let s = ray.start - pos;
+        let b = -ray.dir.dot(&s);
+        let d = b * b - s.dot(&s) + 1.0;
+        if d >= 0.0 {
+            let d = d.sqrt();
+            if b - d < dist_in {
+                dist_in = b - d;
+                norm_in = ray.start + ray.dir * dist_in - pos;
+            }
+            dist_out = dist_out.max(b + d);
+        }
+    }
+
+    if dist_in < 0.0 || dist_in > dist_out {
+        return None;
+    }
+    Some(Hit {
+        enter: dist_in,
+        exit: dist_out,
+        norm: norm_in,
+    })
+}
+
+const POLY_BISECT_ITERS: usize = 64;
+
+/// Evaluates the polynomial `c[0] + c[1]*t + ... + c[n]*t^n`.
+fn poly_eval(c: &[f64], t: f64) -> f64 {
+    c.iter().rev().fold(0.0, |v, x| v * t + x)
+}
+
+/// Roots of the polynomial on `[a, b]` isolated by its sorted critical points,
+/// see `_poly_roots` in `torus.h`.
+fn poly_roots(c: &[f64], crit: &[f64], a: f64, b: f64) -> Vec<f64> {
+    let mut roots = Vec::new();
+    let (mut lo, mut flo) = (a, poly_eval(c, a));
+    for &hi in crit.iter().chain([b].iter()) {
+        let fhi = poly_eval(c, hi);
+        if (flo < 0.0) != (fhi < 0.0) {
+            let (mut x, mut y, mut fx) = (lo, hi, flo);
+            for _ in 0..POLY_BISECT_ITERS {
+                let m = 0.5 * (x + y);
+                let fm = poly_eval(c, m);
+                if (fm < 0.0) == (fx < 0.0) {
+                    x = m;
+                    fx = fm;
+                } else {
+                    y = m;
+                }
+            }
+            roots.push(0.5 * (x + y));
+        }
+        lo = hi;
+        flo = fhi;
+    }
+    roots
+}
+
+/// Intersection with the unit torus, see `unit_torus_hit` in `torus.h`.
+pub fn unit_torus_hit(ray: &Ray, minor: f64) -> Option<Hit> {
+    let bound = 1.0 + minor;
+    let tc = -ray.start.dot(&ray.dir);
+    let o = ray.start + ray.dir * tc;
+    let h2 = bound * bound - o.dot(&o);
+    if h2 <= 0.0 {
+        return None;
+    }
+    let h = h2.sqrt();
+
+    let d = ray.dir;
+    let k = o.dot(&o) + 1.0 - minor * minor;
+    let quartic = [
+        k * k - 4.0 * (o.x * o.x + o.y * o.y),
+        -8.0 * (o.x * d.x + o.y * d.y),
+        2.0 * k - 4.0 * (d.x * d.x + d.y * d.y),
+        0.0,
+        1.0,
+    ];
+    let cubic = [quartic[1], 2.0 * quartic[2], 0.0, 4.0];
+
+    let mut inflect = Vec::new();
+    if quartic[2] < 0.0 {
+        let s = (-quartic[2] / 6.0).sqrt();
+        if s < h {
+            inflect.extend_from_slice(&[-s, s]);
+        }
+    }
+    let crit = poly_roots(&cubic, &inflect, -h, h);
+    let roots = poly_roots(&quartic, &crit, -h, h);
+
+    roots.chunks(2).filter(|p| p.len() == 2).find_map(|p| {
+        let dist_in = p[0] + tc;
+        if dist_in < 0.0 {
+            return None;
+        }
+        let pos = ray.start + ray.dir * dist_in;
+        let q = Vector3::new(pos.x, pos.y, 0.0);
+        let ql = q.norm();
+        let q = if ql > 0.0 { q / ql } else { q };
+        Some(Hit {
+            enter: dist_in,
+            exit: p[1] + tc,
+            norm: (pos - q).normalize(),
+        })
+    })
+}
+
+/// Roots of `a*t^2 + b*t + c` in `[0, l]`, see `_hf_roots` in `heightfield.h`.
+fn heightfield_roots(a: f64, b: f64, c: f64, l: f64) -> Vec<f64> {
+    let mut roots = Vec::new();
+    if a.abs() < 1e-8 {
+        if b != 0.0 {
+            roots.push(-c / b);
+        }
+    } else {
+        let d = b * b - 4.0 * a * c;
+        if d >= 0.0 {
+            let d = d.sqrt();
+            let q = -0.5 * (b + if b < 0.0 { -d } else { d });
+            let r0 = q / a;
+            let r1 = if q != 0.0 { c / q } else { r0 };
+            roots.push(r0.min(r1));
+            roots.push(r0.max(r1));
+        }
+    }
+    roots.retain(|&t| t >= 0.0 && t <= l);
+    roots
+}
+
+/// Height gradient at the grid node, see `_hf_grad` in `heightfield.h`.
+fn heightfield_grad<const N: usize>(
+    field: &SizedHeightfield<N>,
+    d: (f64, f64),
+    i: usize,
+    j: usize,
+) -> (f64, f64) {
+    let (w, h) = field.dims();
+    let (i0, i1) = (i.saturating_sub(1), (i + 1).min(w - 1));
+    let (j0, j1) = (j.saturating_sub(1), (j + 1).min(h - 1));
+    (
+        (field.height(i1, j) - field.height(i0, j)) / ((i1 - i0) as f64 * d.0),
+        (field.height(i, j1) - field.height(i, j0)) / ((j1 - j0) as f64 * d.1),
+    )
+}
+
+/// Intersection with the heightfield, see `heightfield_hit` in `heightfield.h`.
+pub fn heightfield_hit<const N: usize>(ray: &Ray, field: &SizedHeightfield<N>) -> Option<Hit> {
+    let (w, h) = field.dims();
+    let (dx, dy) = (2.0 / (w - 1) as f64, 2.0 / (h - 1) as f64);
+    let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+
+    let inv_dir = ray.dir.map(|x| 1.0 / x);
+    let vmin = (Vector3::new(-1.0, -1.0, 0.0) - ray.start).component_mul(&inv_dir);
+    let vmax = (Vector3::new(1.0, 1.0, field.max_height()) - ray.start).component_mul(&inv_dir);
+    let near = vmin.zip_map(&vmax, f64::min);
+    let far = vmin.zip_map(&vmax, f64::max);
+    let tb0 = near.max();
+    let tb1 = far.min();
+    if tb0 > tb1 || tb1 < 0.0 {
+        return None;
+    }
+
+    let mut t = tb0.max(0.0);
+    let p = ray.start + ray.dir * t;
+    let gx = ((p.x + 1.0) / dx).max(0.0).min((w - 1) as f64);
+    let gy = ((p.y + 1.0) / dy).max(0.0).min((h - 1) as f64);
+    let mut i = (gx.floor() as isize).min(w as isize - 2);
+    let mut j = (gy.floor() as isize).min(h as isize - 2);
+
+    let mut enter = None;
+    let (iu, ju) = (i as usize, j as usize);
+    let (s, r) = (gx - iu as f64, gy - ju as f64);
+    let base = lerp(
+        lerp(field.height(iu, ju), field.height(iu + 1, ju), s),
+        lerp(field.height(iu, ju + 1), field.height(iu + 1, ju + 1), s),
+        r,
+    );
+    if p.z <= base {
+        if tb0 < 0.0 {
+            return None;
+        }
+        let norm = if near.x >= near.y && near.x >= near.z {
+            Vector3::new(-sign(ray.dir.x), 0.0, 0.0)
+        } else if near.y >= near.z {
+            Vector3::new(0.0, -sign(ray.dir.y), 0.0)
+        } else {
+            Vector3::new(0.0, 0.0, -sign(ray.dir.z))
+        };
+        enter = Some((tb0, norm));
+    }
+
+    let step_x = if ray.dir.x > 0.0 { 1 } else { -1 };
+    let step_y = if ray.dir.y > 0.0 { 1 } else { -1 };
+    let (mut tmax_x, mut tdelta_x) = (std::f64::INFINITY, std::f64::INFINITY);
+    let (mut tmax_y, mut tdelta_y) = (std::f64::INFINITY, std::f64::INFINITY);
+    if ray.dir.x != 0.0 {
+        let edge = (i + (step_x > 0) as isize) as f64;
+        tmax_x = (-1.0 + edge * dx - ray.start.x) * inv_dir.x;
+        tdelta_x = dx * inv_dir.x.abs();
+    }
+    if ray.dir.y != 0.0 {
+        let edge = (j + (step_y > 0) as isize) as f64;
+        tmax_y = (-1.0 + edge * dy - ray.start.y) * inv_dir.y;
+        tdelta_y = dy * inv_dir.y.abs();
+    }
+
+    while t < tb1 {
+        let tn = tb1.min(tmax_x).min(tmax_y);
+        let (iu, ju) = (i as usize, j as usize);
+
+        let q = ray.start + ray.dir * t;
+        let (s0, r0) = ((q.x + 1.0) / dx - iu as f64, (q.y + 1.0) / dy - ju as f64);
+        let (ds, dr) = (ray.dir.x / dx, ray.dir.y / dy);
+        let h00 = field.height(iu, ju);
+        let h10 = field.height(iu + 1, ju);
+        let h01 = field.height(iu, ju + 1);
+        let h11 = field.height(iu + 1, ju + 1);
+        let (bs, br, c) = (h10 - h00, h01 - h00, h00 - h10 - h01 + h11);
+        let f0 = q.z - (h00 + bs * s0 + br * r0 + c * s0 * r0);
+        let f1 = ray.dir.z - (bs * ds + br * dr + c * (s0 * dr + r0 * ds));
+        let f2 = -c * ds * dr;
+
+        for tau in heightfield_roots(f2, f1, f0, tn - t) {
+            let df = f1 + 2.0 * f2 * tau;
+            match enter {
+                None if df < 0.0 => {
+                    let su = (s0 + ds * tau).max(0.0).min(1.0);
+                    let rv = (r0 + dr * tau).max(0.0).min(1.0);
+                    let grad = |a, b| heightfield_grad(field, (dx, dy), a, b);
+                    let (g00, g10) = (grad(iu, ju), grad(iu + 1, ju));
+                    let (g01, g11) = (grad(iu, ju + 1), grad(iu + 1, ju + 1));
+                    let gx = lerp(lerp(g00.0, g10.0, su), lerp(g01.0, g11.0, su), rv);
+                    let gy = lerp(lerp(g00.1, g10.1, su), lerp(g01.1, g11.1, su), rv);
+                    enter = Some((t + tau, Vector3::new(-gx, -gy, 1.0).normalize()));
+                }
+                Some((dist_in, norm)) if df > 0.0 && t + tau > dist_in => {
+                    return Some(Hit {
+                        enter: dist_in,
+                        exit: t + tau,
+                        norm,
+                    });
+                }
+                _ => (),
+            }
+        }
+
+        if tn >= tb1 {
+            break;
+        }
+        t = tn;
+        if tmax_x < tmax_y {
+            i += step_x;
+            tmax_x += tdelta_x;
+        } else {
+            j += step_y;
+            tmax_y += tdelta_y;
+        }
+        if i < 0 || i > w as isize - 2 || j < 0 || j > h as isize - 2 {
+            break;
+        }
+    }
+
+    enter.map(|(dist_in, norm)| Hit {
+        enter: dist_in,
+        exit: tb1,
+        norm,
+    })
+}
+
+fn cube_hit_nearest(near: &Vector3<f64>) -> (f64, Vector3<f64>) {
+    let (xy, yz, xz) = (near.x > near.y, near.y > near.z, near.x > near.z);
+    if xy && xz {
+        (near.x, Vector3::x())
+    } else if yz {
+        (near.y, Vector3::y())
+    } else {
+        (near.z, Vector3::z())
+    }
+}
+
+/// Intersection with the unit cube, see `cube_hit` in `cube.h`.
+pub fn cube_hit(ray: &Ray) -> Option<Hit> {
+    let inv_dir = ray.dir.map(|x| 1.0 / x);
+    let vmin = (Vector3::repeat(-1.0) - ray.start).component_mul(&inv_dir);
+    let vmax = (Vector3::repeat(1.0) - ray.start).component_mul(&inv_dir);
+    let near = vmin.zip_map(&vmax, f64::min);
+    let far = vmin.zip_map(&vmax, f64::max);
+
+    let ray_sign = ray.dir.map(sign);
+    let (dist_in, norm_in) = cube_hit_nearest(&near);
+    let (dist_out, _) = cube_hit_nearest(&-far);
+    let dist_out = -dist_out;
+
+    if dist_in < 0.0 || dist_in > dist_out {
+        return None;
+    }
+    Some(Hit {
+        enter: dist_in,
+        exit: dist_out,
+        norm: -norm_in.component_mul(&ray_sign),
+    })
+}
+
+/// Two-sided intersection with the triangle, see `triangle_hit` in `triangle.h`.
+pub fn triangle_hit(
+    ray: &Ray,
+    vertices: &[Vector3<f64>; 3],
+    normals: Option<&[Vector3<f64>; 3]>,
+) -> Option<Hit> {
+    let v0 = vertices[0];
+    let e1 = vertices[1] - v0;
+    let e2 = vertices[2] - v0;
+
+    let p = ray.dir.cross(&e2);
+    let det = e1.dot(&p);
+    if det.abs() < 1e-8 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let s = ray.start - v0;
+    let u = s.dot(&p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&e1);
+    let v = ray.dir.dot(&q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = e2.dot(&q) * inv_det;
+    if t < 0.0 {
+        return None;
+    }
+
+    let gnorm = e1.cross(&e2);
+    let mut n = gnorm;
+    if let Some(ns) = normals {
+        n = (1.0 - u - v) * ns[0] + u * ns[1] + v * ns[2];
+        if n.dot(&gnorm) < 0.0 {
+            n = -n;
+        }
+    }
+    if gnorm.dot(&ray.dir) > 0.0 {
+        n = -n;
+    }
+    Some(Hit {
+        enter: t,
+        exit: t,
+        norm: n.normalize(),
+    })
+}
+
+/// Intersection with the triangle mesh, see `mesh_hit` in `mesh.h`.
+pub fn mesh_hit(ray: &Ray, mesh: &MeshData) -> Option<Hit> {
+    let mut nearest: Option<Hit> = None;
+    let mut next = std::f64::INFINITY;
+    for face in mesh.faces.iter() {
+        let vs = [
+            mesh.vertices[face.vertices[0]],
+            mesh.vertices[face.vertices[1]],
+            mesh.vertices[face.vertices[2]],
+        ];
+        let ns = face.normals.map(|ns| {
+            [
+                mesh.normals[ns[0]],
+                mesh.normals[ns[1]],
+                mesh.normals[ns[2]],
+            ]
+        });
+        let hit = match triangle_hit(ray, &vs, ns.as_ref()) {
+            Some(hit) => hit,
+            None => continue,
+        };
+        match &nearest {
+            Some(h) if hit.enter >= h.enter => next = next.min(hit.enter),
+            _ => {
+                if let Some(h) = &nearest {
+                    next = h.enter;
+                }
+                nearest = Some(hit);
+            }
+        }
+    }
+    nearest.map(|mut hit| {
+        if next.is_finite() {
+            hit.exit = next;
+        }
+        hit
+    })
+}
+
+impl CpuShape for UnitSphere {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        unit_sphere_hit(ray)
+    }
+}
+
+impl CpuShape for UnitCube {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        cube_hit(ray)
+    }
+}
+
+impl CpuShape for UnitPlane {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        unit_plane_hit(ray)
+    }
+}
+
+impl CpuShape for UnitDisk {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        unit_disk_hit(ray)
+    }
+}
+
+impl CpuShape for UnitCylinder {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        unit_cylinder_hit(ray, self.capped)
+    }
+}
+
+impl CpuShape for UnitCone {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        unit_cone_hit(ray)
+    }
+}
+
+impl CpuShape for UnitCapsule {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        unit_capsule_hit(ray, self.half_len)
+    }
+}
+
+impl CpuShape for UnitTorus {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        unit_torus_hit(ray, self.minor)
+    }
+}
+
+impl<const N: usize> CpuShape for SizedHeightfield<N> {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        heightfield_hit(ray, self)
+    }
+}
+
+impl CpuShape for Triangle {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        triangle_hit(ray, &self.vertices, self.normals.as_ref())
+    }
+}
+
+impl<const V: usize, const F: usize> CpuShape for SizedTriangleMesh<V, F> {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        mesh_hit(ray, self.data())
+    }
+}
+
+impl<S: Shape + CpuShape, M: Map + CpuMap> CpuShape for ShapeMapper<S, M> {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        let mut r = ray.clone();
+        r.start = self.map.abs_inv(&ray.start);
+        r.dir = self.map.rel_inv(&ray.dir);
+        let len = r.dir.norm();
+        r.dir /= len;
+        self.shape.hit(&r).map(|hit| Hit {
+            enter: hit.enter / len,
+            exit: hit.exit / len,
+            norm: self.map.norm(&hit.norm).normalize(),
+        })
+    }
+}
+
+impl CpuShape for Sphere {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        self.0.hit(ray)
+    }
+}
+
+impl CpuShape for Ellipsoid {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        self.0.hit(ray)
+    }
+}
+
+impl CpuShape for Parallelepiped {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        self.0.hit(ray)
+    }
+}
+
+impl CpuShape for Plane {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        self.0.hit(ray)
+    }
+}
+
+impl CpuShape for Disk {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        self.0.hit(ray)
+    }
+}
+
+impl CpuShape for Cylinder {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        self.0.hit(ray)
+    }
+}
+
+impl CpuShape for Cone {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        self.0.hit(ray)
+    }
+}
+
+impl CpuShape for Capsule {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        self.0.hit(ray)
+    }
+}
+
+impl CpuShape for Torus {
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        self.0.hit(ray)
+    }
+}
+
+/// See `CSG_EPS` in `csg.h`.
+const CSG_EPS: f64 = 1e-4;
+
+/// Interval of the ray inside of the CSG operand, see `CSG_OPERAND_HIT` in `csg.h`.
+fn csg_operand_hit<S: CpuShape + Bounded<Aabb>>(shape: &S, ray: &Ray) -> Option<Hit> {
+    if let Some(hit) = shape.hit(ray) {
+        return Some(hit);
+    }
+    let probe = csg_probe(shape);
+    if probe <= 0.0 {
+        return None;
+    }
+    let mut r = ray.clone();
+    r.start -= probe * ray.dir;
+    shape
+        .hit(&r)
+        .map(|hit| Hit {
+            enter: hit.enter - probe,
+            exit: hit.exit - probe,
+            norm: hit.norm,
+        })
+        .filter(|hit| hit.enter <= 0.0 && hit.exit >= 0.0)
+}
+
+#[derive(Clone, Copy)]
+enum CsgOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+#[derive(Clone, Copy)]
+enum CsgNorm {
+    First,
+    Second,
+    SecondExit,
+}
+
+/// Combination of the operands intervals, see `csg_combine` and `CSG_FN_DEF` in `csg.h`.
+fn csg_hit<A, B>(op: CsgOp, first: &A, second: &B, ray: &Ray) -> Option<Hit>
+where
+    A: CpuShape + Bounded<Aabb>,
+    B: CpuShape + Bounded<Aabb>,
+{
+    let (a, b) = (csg_operand_hit(first, ray), csg_operand_hit(second, ray));
+    let mut pieces = Vec::new();
+    match op {
+        CsgOp::Union => match (&a, &b) {
+            (Some(a), Some(b)) if a.enter <= b.exit && b.enter <= a.exit => {
+                let norm = if a.enter <= b.enter {
+                    CsgNorm::First
+                } else {
+                    CsgNorm::Second
+                };
+                pieces.push((a.enter.min(b.enter), a.exit.max(b.exit), norm));
+            }
+            _ => {
+                if let Some(a) = &a {
+                    pieces.push((a.enter, a.exit, CsgNorm::First));
+                }
+                if let Some(b) = &b {
+                    pieces.push((b.enter, b.exit, CsgNorm::Second));
+                }
+                if pieces.len() == 2 && pieces[1].0 < pieces[0].0 {
+                    pieces.swap(0, 1);
+                }
+            }
+        },
+        CsgOp::Intersection => {
+            if let (Some(a), Some(b)) = (&a, &b) {
+                let norm = if a.enter >= b.enter {
+                    CsgNorm::First
+                } else {
+                    CsgNorm::Second
+                };
+                let (enter, exit) = (a.enter.max(b.enter), a.exit.min(b.exit));
+                if enter <= exit {
+                    pieces.push((enter, exit, norm));
+                }
+            }
+        }
+        CsgOp::Difference => match (&a, &b) {
+            (Some(a), None) => pieces.push((a.enter, a.exit, CsgNorm::First)),
+            (Some(a), Some(b)) => {
+                if a.enter < b.enter {
+                    pieces.push((a.enter, a.exit.min(b.enter), CsgNorm::First));
+                }
+                if b.exit < a.exit {
+                    if b.exit > a.enter {
+                        pieces.push((b.exit, a.exit, CsgNorm::SecondExit));
+                    } else {
+                        pieces.push((a.enter, a.exit, CsgNorm::First));
+                    }
+                }
+            }
+            _ => (),
+        },
+    }
+
+    let (enter, exit, src) = pieces.into_iter().find(|p| p.0 >= 0.0)?;
+    let norm = match src {
+        CsgNorm::First => a?.norm,
+        CsgNorm::Second => b?.norm,
+        CsgNorm::SecondExit => {
+            let mut r = ray.clone();
+            r.start = ray.start + ray.dir * (enter + CSG_EPS);
+            r.dir = -ray.dir;
+            match second.hit(&r) {
+                Some(hit) => -hit.norm,
+                None => -ray.dir,
+            }
+        }
+    };
+    Some(Hit { enter, exit, norm })
+}
+
+impl<A, B> CpuShape for Union<A, B>
+where
+    A: Shape + CpuShape + Bounded<Aabb>,
+    B: Shape + CpuShape + Bounded<Aabb>,
+{
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        csg_hit(CsgOp::Union, &self.first, &self.second, ray)
+    }
+}
+
+impl<A, B> CpuShape for Intersection<A, B>
+where
+    A: Shape + CpuShape + Bounded<Aabb>,
+    B: Shape + CpuShape + Bounded<Aabb>,
+{
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        csg_hit(CsgOp::Intersection, &self.first, &self.second, ray)
+    }
+}
+
+impl<A, B> CpuShape for Difference<A, B>
+where
+    A: Shape + CpuShape + Bounded<Aabb>,
+    B: Shape + CpuShape + Bounded<Aabb>,
+{
+    fn hit(&self, ray: &Ray) -> Option<Hit> {
+        csg_hit(CsgOp::Difference, &self.first, &self.second, ray)
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{cpu::*, view::*};
+use nalgebra::{Matrix3, Rotation3, Vector3};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::f64::consts::PI;
+
+/// View that could emit rays on the host.
+pub trait CpuView {
+    /// Emits the ray through the random point of the pixel.
+    fn emit<R: Rng>(&self, rng: &mut R, pos: (usize, usize), size: (usize, usize)) -> Ray;
+}
+
+/// Random point of the pixel in screen coordinates, see `ptos_rand` in `screen.h`.
+pub fn ptos_rand<R: Rng>(rng: &mut R, pos: (usize, usize), size: (usize, usize)) -> (f64, f64) {
+    let x = pos.0 as f64 - 0.5 * size.0 as f64 + rng.gen::<f64>() - 0.5;
+    let y = -(pos.1 as f64 - 0.5 * size.1 as f64) + rng.gen::<f64>() - 0.5;
+    (x / size.1 as f64, y / size.1 as f64)
+}
+
+/// Random point of the unit aperture, see `lens_sample` in `lens.h`.
+pub fn lens_sample<R: Rng>(rng: &mut R, blades: usize, rotation: f64) -> (f64, f64) {
+    if blades < 3 {
+        let phi = 2.0 * PI * rng.gen::<f64>();
+        let r = rng.gen::<f64>().sqrt();
+        return (r * phi.cos(), r * phi.sin());
+    }
+    let k = ((blades as f64 * rng.gen::<f64>()) as usize).min(blades - 1);
+    let step = 2.0 * PI / blades as f64;
+    let phi = rotation + k as f64 * step;
+    let (mut u, mut v) = (rng.gen::<f64>(), rng.gen::<f64>());
+    if u + v > 1.0 {
+        u = 1.0 - u;
+        v = 1.0 - v;
+    }
+    (
+        u * phi.cos() + v * (phi + step).cos(),
+        u * phi.sin() + v * (phi + step).sin(),
+    )
+}
+
+/// Position and orientation of the view at the random moment within the shutter interval,
+/// see `shutter_sample` in `shutter.h`.
+pub fn shutter_sample<R: Rng>(
+    rng: &mut R,
+    pos: &Vector3<f64>,
+    ori: &Rotation3<f64>,
+    shutter: Option<&Shutter>,
+) -> (Vector3<f64>, Rotation3<f64>) {
+    match shutter {
+        Some(s) => s.frame_at(pos, ori, s.open + (s.close - s.open) * rng.gen::<f64>()),
+        None => (*pos, *ori),
+    }
+}
+
+/// Ray of the thin lens camera of the view placed at `start`, see `lens_emit` in `lens.h`.
+fn lens_emit<R: Rng>(
+    rng: &mut R,
+    view: &ProjectionView,
+    map: &Matrix3<f64>,
+    start: Vector3<f64>,
+    dir: Vector3<f64>,
+) -> Ray {
+    if view.aperture > 0.0 {
+        let target = start + dir * (view.focus * view.fov);
+        let (lx, ly) = lens_sample(rng, view.blades, view.blade_rotation);
+        let start = start + view.aperture * (lx * map.column(0) + ly * map.column(1));
+        Ray::new(start, (target - start).normalize())
+    } else {
+        Ray::new(start, dir.normalize())
+    }
+}
+
+impl CpuView for ProjectionView {
+    fn emit<R: Rng>(&self, rng: &mut R, pos: (usize, usize), size: (usize, usize)) -> Ray {
+        let (view_pos, view_ori) = shutter_sample(rng, &self.pos, &self.ori, self.shutter.as_ref());
+        let (x, y) = ptos_rand(rng, pos, size);
+        let map = view_ori.matrix();
+        let dir = x * map.column(0) + y * map.column(1) - map.column(2) / self.fov;
+        lens_emit(rng, self, map, view_pos, dir)
+    }
+}
+
+impl CpuView for StereoView {
+    fn emit<R: Rng>(&self, rng: &mut R, pos: (usize, usize), size: (usize, usize)) -> Ray {
+        let (eye_size, right, eye_pos) = match self.layout {
+            StereoLayout::SideBySide => {
+                let w = size.0 / 2;
+                ((w, size.1), pos.0 >= w, (pos.0 % w, pos.1))
+            }
+            StereoLayout::TopBottom => {
+                let h = size.1 / 2;
+                ((size.0, h), pos.1 >= h, (pos.0, pos.1 % h))
+            }
+        };
+        let view = &self.view;
+        let (view_pos, view_ori) = shutter_sample(rng, &view.pos, &view.ori, view.shutter.as_ref());
+        let e = if right { 0.5 } else { -0.5 } * self.ipd;
+        let (x, y) = ptos_rand(rng, eye_pos, eye_size);
+        let map = view_ori.matrix();
+        let x = x - e / (self.convergence * view.fov);
+        let dir = x * map.column(0) + y * map.column(1) - map.column(2) / view.fov;
+        lens_emit(rng, view, map, view_pos + e * map.column(0), dir)
+    }
+}
+
+impl CpuView for OrthographicView {
+    fn emit<R: Rng>(&self, rng: &mut R, pos: (usize, usize), size: (usize, usize)) -> Ray {
+        let (view_pos, view_ori) = shutter_sample(rng, &self.pos, &self.ori, self.shutter.as_ref());
+        let (x, y) = ptos_rand(rng, pos, size);
+        let map = view_ori.matrix();
+        let start = view_pos + self.size * (x * map.column(0) + y * map.column(1));
+        Ray::new(start, -map.column(2))
+    }
+}
+
+impl CpuView for PanoramicView {
+    fn emit<R: Rng>(&self, rng: &mut R, pos: (usize, usize), size: (usize, usize)) -> Ray {
+        let (view_pos, view_ori) = shutter_sample(rng, &self.pos, &self.ori, self.shutter.as_ref());
+        let u = (pos.0 as f64 + rng.gen::<f64>()) / size.0 as f64;
+        let v = (pos.1 as f64 + rng.gen::<f64>()) / size.1 as f64;
+        let (phi, theta) = (2.0 * PI * (u - 0.5), PI * (0.5 - v));
+        let map = view_ori.matrix();
+        let dir = theta.cos() * (phi.sin() * map.column(0) - phi.cos() * map.column(2))
+            + theta.sin() * map.column(1);
+        Ray::new(view_pos, dir)
+    }
+}
+
+impl CpuView for FisheyeView {
+    fn emit<R: Rng>(&self, rng: &mut R, pos: (usize, usize), size: (usize, usize)) -> Ray {
+        let (view_pos, view_ori) = shutter_sample(rng, &self.pos, &self.ori, self.shutter.as_ref());
+        let (x, y) = ptos_rand(rng, pos, size);
+        let (x, y) = (2.0 * x, 2.0 * y);
+        let r = x.hypot(y);
+        let theta = match self.mapping {
+            FisheyeMapping::Equidistant => 0.5 * r * self.field,
+            FisheyeMapping::Equisolid => 2.0 * (r * (0.25 * self.field).sin()).min(1.0).asin(),
+        };
+        let (dx, dy) = if r > 0.0 { (x / r, y / r) } else { (0.0, 0.0) };
+        let map = view_ori.matrix();
+        let dir =
+            theta.sin() * (dx * map.column(0) + dy * map.column(1)) - theta.cos() * map.column(2);
+        let mut ray = Ray::new(view_pos, dir);
+        if r > 1.0 {
+            ray.color = Vector3::zeros();
+        }
+        ray
+    }
+}
+
+/// Renders the scene on the host.
+///
+/// Returns the linear RGB color of pixels averaged over `passes` samples,
+/// the layout is the same as the one of `process::read_hdr`.
+pub fn render<S: CpuScene, V: CpuView>(
+    scene: &S,
+    view: &V,
+    dims: (usize, usize),
+    passes: usize,
+    seed: u64,
+) -> Vec<f32> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut data = Vec::with_capacity(3 * dims.0 * dims.1);
+    for y in 0..dims.1 {
+        for x in 0..dims.0 {
+            let mut color = Vector3::zeros();
+            for _ in 0..passes {
+                let ray = view.emit(&mut rng, (x, y), dims);
+                color += scene.trace(&mut rng, ray);
+            }
+            color /= passes.max(1) as f64;
+            data.extend(color.iter().map(|&c| c as f32));
+        }
+    }
+    data
+}
@@ -0,0 +1,278 @@
+use crate::{
+    desc::*,
+    map::*,
+    material::*,
+    scene::{BvhScene, GradientBackground, ListScene, TargetListScene},
+    shape::*,
+    view::ProjectionView,
+    Error, Result,
+};
+use nalgebra::{Matrix3, Rotation3, Unit, Vector3};
+use std::{fs, path::Path};
+
+pub type DescListScene = ListScene<DescObject, GradientBackground>;
+pub type DescTargetListScene = TargetListScene<DescObject, Sphere, GradientBackground>;
+pub type DescBvhScene = BvhScene<DescObject, GradientBackground>;
+
+fn vector(v: &[f64; 3]) -> Vector3<f64> {
+    Vector3::from_column_slice(v)
+}
+
+fn format_error<E: std::fmt::Display>(e: E) -> Error {
+    Error::from(format!("scene description error: {}", e))
+}
+
+impl TransformDesc {
+    /// Linear part of the transform.
+    pub fn ori(&self) -> Matrix3<f64> {
+        let rotation = match &self.rotation {
+            Some(r) => {
+                *Rotation3::from_axis_angle(&Unit::new_normalize(vector(&r.axis)), r.angle).matrix()
+            }
+            None => Matrix3::identity(),
+        };
+        let matrix = match &self.matrix {
+            Some(m) => Matrix3::from_row_slice(&[
+                m[0][0], m[0][1], m[0][2], m[1][0], m[1][1], m[1][2], m[2][0], m[2][1], m[2][2],
+            ]),
+            None => Matrix3::identity(),
+        };
+        rotation * matrix * Matrix3::from_diagonal(&vector(&self.scale))
+    }
+    /// Translational part of the transform.
+    pub fn pos(&self) -> Vector3<f64> {
+        vector(&self.pos)
+    }
+}
+
+impl BackgroundDesc {
+    pub fn build(&self) -> GradientBackground {
+        match self {
+            BackgroundDesc::Constant { color } => {
+                GradientBackground::new(vector(color), vector(color), Vector3::z())
+            }
+            BackgroundDesc::Gradient { front, back, dir } => {
+                GradientBackground::new(vector(front), vector(back), vector(dir).normalize())
+            }
+        }
+    }
+}
+
+impl ViewDesc {
+    pub fn build(&self) -> ProjectionView {
+        let mut view = ProjectionView::new(
+            vector(&self.pos),
+            Rotation3::face_towards(&-vector(&self.dir), &vector(&self.up)),
+        );
+        view.fov = self.fov;
+        view
+    }
+}
+
+impl ShapeDesc {
+    /// Creates solid shape that could be an operand of CSG.
+    fn build_solid(&self) -> Result<DescSolid> {
+        Ok(match self {
+            ShapeDesc::Sphere { radius, pos } => Sphere::new(*radius, vector(pos)).into(),
+            ShapeDesc::Ellipsoid { transform: t } => Ellipsoid::try_new(t.ori(), t.pos())?.into(),
+            ShapeDesc::Parallelepiped { transform: t } => {
+                Parallelepiped::try_new(t.ori(), t.pos())?.into()
+            }
+            ShapeDesc::Cylinder {
+                transform: t,
+                open: false,
+            } => Cylinder::try_new(t.ori(), t.pos())?.into(),
+            ShapeDesc::Cone { transform: t } => Cone::try_new(t.ori(), t.pos())?.into(),
+            ShapeDesc::Capsule { radius, a, b } => {
+                Capsule::try_new(*radius, vector(a), vector(b))?.into()
+            }
+            _ => return Err(format_error(format!("{:?} is not a CSG operand", self))),
+        })
+    }
+
+    /// Creates shapes, a mesh produces a shape for each of its triangles.
+    pub fn build(&self, base_dir: &Path) -> Result<Vec<DescShape>> {
+        Ok(match self {
+            ShapeDesc::Sphere { .. }
+            | ShapeDesc::Ellipsoid { .. }
+            | ShapeDesc::Parallelepiped { .. }
+            | ShapeDesc::Cylinder { open: false, .. }
+            | ShapeDesc::Cone { .. }
+            | ShapeDesc::Capsule { .. } => vec![self.build_solid()?.into()],
+            ShapeDesc::Cylinder {
+                transform: t,
+                open: true,
+            } => vec![Cylinder::try_new_open(t.ori(), t.pos())?.into()],
+            ShapeDesc::Triangle { vertices, normals } => {
+                let vs = [
+                    vector(&vertices[0]),
+                    vector(&vertices[1]),
+                    vector(&vertices[2]),
+                ];
+                vec![match normals {
+                    Some(ns) => {
+                        Triangle::new_smooth(vs, [vector(&ns[0]), vector(&ns[1]), vector(&ns[2])])
+                    }
+                    None => Triangle::new(vs),
+                }
+                .into()]
+            }
+            ShapeDesc::Mesh { path, transform } => {
+                let mut mesh = MeshData::load_obj(base_dir.join(path))?;
+                mesh.transform(&transform.ori(), &transform.pos());
+                mesh.triangles().map(|t| t.into()).collect()
+            }
+            ShapeDesc::Plane { pos, norm } => {
+                vec![Plane::try_new(vector(pos), vector(norm))?.into()]
+            }
+            ShapeDesc::Disk { radius, pos, norm } => {
+                vec![Disk::try_new(*radius, vector(pos), vector(norm))?.into()]
+            }
+            ShapeDesc::Torus {
+                minor,
+                transform: t,
+            } => vec![Torus::try_new(*minor, t.ori(), t.pos())?.into()],
+            ShapeDesc::TriangleMesh { path, transform } => {
+                let mut mesh = MeshData::load_obj(base_dir.join(path))?;
+                mesh.transform(&transform.ori(), &transform.pos());
+                vec![DescTriangleMesh::new(mesh)?.into()]
+            }
+            ShapeDesc::Heightfield { path, transform: t } => {
+                let field =
+                    SizedHeightfield::<DESC_HEIGHTFIELD_MAX_SIZE>::load_image(base_dir.join(path))?;
+                let map = Linear::try_new(t.ori())?.chain(Shift::from(t.pos()));
+                vec![field.map(map).into()]
+            }
+            ShapeDesc::Csg { op, first, second } => {
+                let (a, b) = (first.build_solid()?, second.build_solid()?);
+                vec![match op {
+                    CsgOpDesc::Union => Union::new(a, b).into(),
+                    CsgOpDesc::Intersection => Intersection::new(a, b).into(),
+                    CsgOpDesc::Difference => Difference::new(a, b).into(),
+                }]
+            }
+            ShapeDesc::Sdf(sdf) => vec![match sdf {
+                SdfDesc::Sphere { radius, pos } => {
+                    SdfShape::new(SdfSphere::new(*radius, vector(pos))).into()
+                }
+                SdfDesc::Box { half_size, pos } => {
+                    SdfShape::new(SdfBox::new(vector(half_size), vector(pos))).into()
+                }
+                SdfDesc::Torus { major, minor, pos } => {
+                    SdfShape::new(SdfTorus::new(*major, *minor, vector(pos))).into()
+                }
+                SdfDesc::Mandelbulb { power, iterations } => {
+                    SdfShape::new(Mandelbulb::new(*power, *iterations)).into()
+                }
+            }],
+        })
+    }
+}
+
+impl MaterialDesc {
+    pub fn build(&self) -> DescMaterial {
+        match self {
+            MaterialDesc::Diffuse { color } => Diffuse {}.color_with(vector(color)).into(),
+            MaterialDesc::Reflective { color } => Reflective {}.color_with(vector(color)).into(),
+            MaterialDesc::Refractive {
+                ior,
+                schlick,
+                color,
+            } => {
+                let fresnel = if *schlick {
+                    Fresnel::Schlick
+                } else {
+                    Fresnel::Exact
+                };
+                Refractive::with_fresnel(*ior, fresnel)
+                    .color_with(vector(color))
+                    .into()
+            }
+            MaterialDesc::Glossy { reflect, color } => DescGlossy::new(
+                (*reflect, Reflective {}),
+                (1.0 - *reflect, Diffuse {}.color_with(vector(color))),
+            )
+            .into(),
+            MaterialDesc::Luminous { color } => Luminous {}.color_with(vector(color)).into(),
+        }
+    }
+}
+
+impl SceneDesc {
+    pub fn from_json(text: &str) -> Result<Self> {
+        serde_json::from_str(text).map_err(format_error)
+    }
+    pub fn from_ron(text: &str) -> Result<Self> {
+        ron::de::from_str(text).map_err(format_error)
+    }
+
+    /// Loads the description from JSON or RON file depending on its extension.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        let mut desc = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json(&text)?,
+            Some("ron") => Self::from_ron(&text)?,
+            _ => {
+                return Err(format_error(format!(
+                    "unknown file format: {}",
+                    path.display()
+                )))
+            }
+        };
+        desc.base_dir = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+        Ok(desc)
+    }
+
+    /// Creates objects paired with the flag of being a target.
+    pub fn build_objects(&self) -> Result<Vec<(DescObject, bool)>> {
+        let mut objects = Vec::new();
+        for od in self.objects.iter() {
+            for shape in od.shape.build(&self.base_dir)? {
+                objects.push((shape.cover(od.material.build()), od.target));
+            }
+        }
+        Ok(objects)
+    }
+
+    pub fn build_view(&self) -> ProjectionView {
+        self.view.build()
+    }
+
+    /// Creates scene with all objects, target flags are ignored.
+    pub fn build_list_scene(&self) -> Result<DescListScene> {
+        let mut scene = ListScene::new(self.background.build());
+        scene.set_max_depth(self.max_depth);
+        for (object, _) in self.build_objects()? {
+            scene.add(object);
+        }
+        Ok(scene)
+    }
+
+    /// Creates scene with importance sampling of target objects.
+    pub fn build_target_list_scene(&self) -> Result<DescTargetListScene> {
+        let mut scene = TargetListScene::new(self.background.build());
+        scene.set_max_depth(self.max_depth);
+        if let Some(target_prob) = self.target_prob {
+            scene.set_target_prob(target_prob);
+        }
+        for (object, target) in self.build_objects()? {
+            if target {
+                scene.add_targeted(object);
+            } else {
+                scene.add(object);
+            }
+        }
+        Ok(scene)
+    }
+
+    /// Creates scene with bounding volume hierarchy, target flags are ignored.
+    pub fn build_bvh_scene(&self) -> Result<DescBvhScene> {
+        let mut scene = BvhScene::new(self.background.build());
+        scene.set_max_depth(self.max_depth);
+        for (object, _) in self.build_objects()? {
+            scene.add(object);
+        }
+        Ok(scene)
+    }
+}
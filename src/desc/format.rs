@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+fn zero() -> [f64; 3] {
+    [0.0, 0.0, 0.0]
+}
+fn one() -> [f64; 3] {
+    [1.0, 1.0, 1.0]
+}
+fn up() -> [f64; 3] {
+    [0.0, 0.0, 1.0]
+}
+fn default_fov() -> f64 {
+    1.0
+}
+fn default_max_depth() -> usize {
+    4
+}
+
+/// Description of the whole scene.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SceneDesc {
+    pub background: BackgroundDesc,
+    pub view: ViewDesc,
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+    /// Probability of sampling targets, used only for scenes with targets.
+    #[serde(default)]
+    pub target_prob: Option<f64>,
+    pub objects: Vec<ObjectDesc>,
+    /// Directory relative to which the paths in the description are resolved.
+    #[serde(skip)]
+    pub base_dir: PathBuf,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum BackgroundDesc {
+    Constant {
+        color: [f64; 3],
+    },
+    Gradient {
+        front: [f64; 3],
+        back: [f64; 3],
+        #[serde(default = "up")]
+        dir: [f64; 3],
+    },
+}
+
+/// Perspective view looking in the `dir` direction.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ViewDesc {
+    pub pos: [f64; 3],
+    pub dir: [f64; 3],
+    #[serde(default = "up")]
+    pub up: [f64; 3],
+    #[serde(default = "default_fov")]
+    pub fov: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ObjectDesc {
+    pub shape: ShapeDesc,
+    pub material: MaterialDesc,
+    /// Whether the object is a target for importance sampling (e.g. light source).
+    #[serde(default)]
+    pub target: bool,
+}
+
+/// Affine transform.
+///
+/// The linear part is the product `rotation * matrix * scale`,
+/// each of them is an identity if omitted.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransformDesc {
+    #[serde(default = "zero")]
+    pub pos: [f64; 3],
+    #[serde(default = "one")]
+    pub scale: [f64; 3],
+    #[serde(default)]
+    pub rotation: Option<RotationDesc>,
+    /// Matrix given by rows.
+    #[serde(default)]
+    pub matrix: Option<[[f64; 3]; 3]>,
+}
+
+impl Default for TransformDesc {
+    fn default() -> Self {
+        Self {
+            pos: zero(),
+            scale: one(),
+            rotation: None,
+            matrix: None,
+        }
+    }
+}
+
+/// Rotation around the axis by the angle in radians.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RotationDesc {
+    pub axis: [f64; 3],
+    pub angle: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ShapeDesc {
+    Sphere {
+        radius: f64,
+        pos: [f64; 3],
+    },
+    Ellipsoid {
+        transform: TransformDesc,
+    },
+    Parallelepiped {
+        transform: TransformDesc,
+    },
+    Triangle {
+        vertices: [[f64; 3]; 3],
+        #[serde(default)]
+        normals: Option<[[f64; 3]; 3]>,
+    },
+    /// Triangle mesh loaded from the Wavefront OBJ file.
+    Mesh {
+        path: PathBuf,
+        #[serde(default)]
+        transform: TransformDesc,
+    },
+    /// Infinite plane passing through `pos` and orthogonal to `norm`.
+    Plane {
+        pos: [f64; 3],
+        norm: [f64; 3],
+    },
+    Disk {
+        radius: f64,
+        pos: [f64; 3],
+        norm: [f64; 3],
+    },
+    /// Unit cylinder along `z` axis transformed, optionally without caps.
+    Cylinder {
+        transform: TransformDesc,
+        #[serde(default)]
+        open: bool,
+    },
+    /// Unit cone along `z` axis with the apex at `(0, 0, 1)` transformed.
+    Cone {
+        transform: TransformDesc,
+    },
+    /// Capsule around the segment between `a` and `b`.
+    Capsule {
+        radius: f64,
+        a: [f64; 3],
+        b: [f64; 3],
+    },
+    /// Unit torus with the tube radius `minor` transformed.
+    Torus {
+        minor: f64,
+        transform: TransformDesc,
+    },
+    /// Triangle mesh loaded from the Wavefront OBJ file into a single shape.
+    ///
+    /// Unlike `Mesh` that produces separate triangles, the mesh must fit into
+    /// `DESC_MESH_MAX_VERTICES` vertices and `DESC_MESH_MAX_FACES` faces.
+    TriangleMesh {
+        path: PathBuf,
+        #[serde(default)]
+        transform: TransformDesc,
+    },
+    /// Heightfield loaded from the grayscale PGM or PNG image and transformed.
+    ///
+    /// Images larger than `DESC_HEIGHTFIELD_MAX_SIZE` are resampled.
+    Heightfield {
+        path: PathBuf,
+        #[serde(default)]
+        transform: TransformDesc,
+    },
+    /// Constructive solid geometry, the operands are spheres, ellipsoids,
+    /// parallelepipeds, cylinders, cones or capsules.
+    Csg {
+        op: CsgOpDesc,
+        first: Box<ShapeDesc>,
+        second: Box<ShapeDesc>,
+    },
+    /// Shape defined by the signed distance function.
+    Sdf(SdfDesc),
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum CsgOpDesc {
+    Union,
+    Intersection,
+    Difference,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SdfDesc {
+    Sphere {
+        radius: f64,
+        pos: [f64; 3],
+    },
+    Box {
+        half_size: [f64; 3],
+        pos: [f64; 3],
+    },
+    /// Torus around `z` axis.
+    Torus {
+        major: f64,
+        minor: f64,
+        pos: [f64; 3],
+    },
+    /// Mandelbulb fractal centered at the origin.
+    Mandelbulb {
+        power: f64,
+        iterations: usize,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum MaterialDesc {
+    Diffuse {
+        color: [f64; 3],
+    },
+    Reflective {
+        #[serde(default = "one")]
+        color: [f64; 3],
+    },
+    Refractive {
+        ior: f64,
+        #[serde(default)]
+        schlick: bool,
+        #[serde(default = "one")]
+        color: [f64; 3],
+    },
+    /// Combination of reflective and diffuse materials.
+    Glossy {
+        reflect: f64,
+        color: [f64; 3],
+    },
+    Luminous {
+        color: [f64; 3],
+    },
+}
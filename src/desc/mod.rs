@@ -0,0 +1,6 @@
+mod select;
+pub use select::*;
+mod format;
+pub use format::*;
+mod build;
+pub use build::*;
@@ -0,0 +1,68 @@
+use crate::{
+    map::*, material::*, material_combine, material_pdf_combine, material_pdf_select,
+    material_select, object::*, shape::*, shape_select,
+};
+
+/// Maximal number of vertices of the mesh packed into a single shape.
+pub const DESC_MESH_MAX_VERTICES: usize = 64;
+/// Maximal number of faces of the mesh packed into a single shape.
+pub const DESC_MESH_MAX_FACES: usize = 64;
+/// Maximal number of grid nodes along each side of the heightfield.
+pub const DESC_HEIGHTFIELD_MAX_SIZE: usize = 32;
+
+pub type DescTriangleMesh = SizedTriangleMesh<DESC_MESH_MAX_VERTICES, DESC_MESH_MAX_FACES>;
+pub type DescHeightfield = ShapeMapper<SizedHeightfield<DESC_HEIGHTFIELD_MAX_SIZE>, Affine>;
+
+// Convex solid shapes that could be the operands of CSG.
+shape_select!(DescSolid {
+    Sphere(TS=Sphere),
+    Ellipsoid(TE=Ellipsoid),
+    Parallelepiped(TP=Parallelepiped),
+    Cylinder(TY=Cylinder),
+    Cone(TC=Cone),
+    Capsule(TA=Capsule),
+});
+
+// Shapes available in scene description.
+//
+// Every object takes the space of the largest shape in the pack buffers,
+// that is the heightfield of `DESC_HEIGHTFIELD_MAX_SIZE^2` nodes (about 4 KiB).
+shape_select!(DescShape {
+    Solid(TS=DescSolid),
+    Plane(TP=Plane),
+    Disk(TD=Disk),
+    Torus(TO=Torus),
+    Triangle(TT=Triangle),
+    TriangleMesh(TM=DescTriangleMesh),
+    Heightfield(TH=DescHeightfield),
+    Union(TU=Union<DescSolid, DescSolid>),
+    Intersection(TI=Intersection<DescSolid, DescSolid>),
+    Difference(TF=Difference<DescSolid, DescSolid>),
+    SdfSphere(TSS=SdfShape<SdfSphere>),
+    SdfBox(TSB=SdfShape<SdfBox>),
+    SdfTorus(TST=SdfShape<SdfTorus>),
+    Mandelbulb(TSM=SdfShape<Mandelbulb>),
+});
+
+material_combine!(DescGlossy {
+    reflect: Reflective,
+    diffuse: Colored<Diffuse>,
+});
+material_pdf_combine!(DescGlossy { reflect, diffuse });
+material_select!(DescMaterial {
+    Diffuse(TD=Colored<Diffuse>),
+    Reflective(TR=Colored<Reflective>),
+    Refractive(TF=Colored<Refractive>),
+    Glossy(TG=DescGlossy),
+    Luminous(TL=Colored<Luminous>),
+});
+material_pdf_select!(DescMaterial {
+    Diffuse,
+    Reflective,
+    Refractive,
+    Glossy,
+    Luminous,
+});
+
+/// Object that covers all shapes and materials available in scene description.
+pub type DescObject = Covered<DescShape, DescMaterial>;
@@ -0,0 +1,39 @@
+use crate::{filter::Filter, view::StereoLayout, Push};
+use ocl::{self, builders::KernelBuilder};
+use std::collections::HashSet;
+
+/// Red/cyan anaglyph filter.
+///
+/// Composes the image of `StereoView` taking red channel from the left eye
+/// and the others from the right one. The result is repeated in both halves of the image.
+pub struct AnaglyphFilter {
+    layout: StereoLayout,
+}
+
+impl AnaglyphFilter {
+    pub fn new(layout: StereoLayout) -> Self {
+        Self { layout }
+    }
+}
+
+impl Filter for AnaglyphFilter {
+    fn inst_name() -> String {
+        "anaglyph_filter".to_string()
+    }
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/filter/anaglyph.h>".to_string()
+    }
+}
+
+impl Push for AnaglyphFilter {
+    fn args_count() -> usize {
+        1
+    }
+    fn args_def(kb: &mut KernelBuilder) {
+        kb.arg(&0i32);
+    }
+    fn args_set(&mut self, i: usize, k: &mut ocl::Kernel) -> crate::Result<()> {
+        k.set_arg(i, &(self.layout as i32))?;
+        Ok(())
+    }
+}
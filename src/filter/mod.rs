@@ -5,3 +5,6 @@ pub use glare::*;
 
 mod log;
 pub use log::*;
+
+mod anaglyph;
+pub use anaglyph::*;
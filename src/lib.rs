@@ -17,6 +17,13 @@ pub mod scene;
 /// View of the scene.
 pub mod view;
 
+/// Declarative scene description.
+pub mod desc;
+
+/// Host implementation of the device code for validation.
+#[cfg(feature = "cpu")]
+pub mod cpu;
+
 /// Filter for rendered image postprocessing.
 pub mod filter;
 /// Functionality for rendering pipeline.
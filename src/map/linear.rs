@@ -1,10 +1,26 @@
-use crate::{map::*, prelude::*};
+use crate::{map::*, prelude::*, Error, Result};
 use nalgebra::Matrix3;
 use std::collections::HashSet;
 
 /// Linear transformation represented by a matrix.
+///
+/// The matrix must be invertible, the singular one is packed with NaN inverse
+/// and rejected by scenes on data creation (*see `Linear::try_new()`*).
 pub struct Linear(pub Matrix3<f64>);
 
+impl Linear {
+    /// Creates linear transformation checking that its matrix and the inverse
+    /// are finite in single precision.
+    pub fn try_new(x: Matrix3<f64>) -> Result<Self> {
+        match x.try_inverse() {
+            Some(inv) if x.iter().chain(inv.iter()).all(|&a| (a as f32).is_finite()) => {
+                Ok(Linear(x))
+            }
+            _ => Err(Error::from(format!("singular matrix of linear map: {}", x))),
+        }
+    }
+}
+
 impl From<Matrix3<f64>> for Linear {
     fn from(x: Matrix3<f64>) -> Self {
         Linear(x)
@@ -30,9 +46,14 @@ impl Pack for Linear {
         2 * Matrix3::<f64>::size_float()
     }
     fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
-        let inverse = self.0.try_inverse().unwrap();
+        let inverse = self
+            .0
+            .try_inverse()
+            .unwrap_or_else(|| Matrix3::repeat(std::f64::NAN));
+        // Matrices are packed by columns but `matrix3_load` reads them by rows,
+        // see the `linear_row_order` render test that compares the device with the host
         Packer::new(buffer_int, buffer_float)
-            .pack(&self.0)
-            .pack(&inverse);
+            .pack(&self.0.transpose())
+            .pack(&inverse.transpose());
     }
 }
@@ -4,6 +4,10 @@ mod shift;
 pub use shift::*;
 mod scale;
 pub use scale::*;
+mod scale3;
+pub use scale3::*;
+mod rotation;
+pub use rotation::*;
 mod linear;
 pub use linear::*;
 mod affine;
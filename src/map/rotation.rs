@@ -0,0 +1,48 @@
+use crate::{map::*, prelude::*};
+use nalgebra::{Matrix3, Rotation3, UnitQuaternion};
+use std::collections::HashSet;
+
+/// Rotation around the origin.
+///
+/// Unlike `Linear` it packs only the rotation matrix
+/// and uses its transpose as the inverse.
+pub struct Rotation(pub Rotation3<f64>);
+
+impl From<Rotation3<f64>> for Rotation {
+    fn from(x: Rotation3<f64>) -> Self {
+        Rotation(x)
+    }
+}
+
+impl From<UnitQuaternion<f64>> for Rotation {
+    fn from(x: UnitQuaternion<f64>) -> Self {
+        Rotation(x.to_rotation_matrix())
+    }
+}
+
+impl Map for Rotation {}
+
+impl Instance<MapClass> for Rotation {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/map/rotation.h>".to_string()
+    }
+    fn inst_name() -> String {
+        "rotation".to_string()
+    }
+}
+
+impl Pack for Rotation {
+    fn size_int() -> usize {
+        Matrix3::<f64>::size_int()
+    }
+    fn size_float() -> usize {
+        Matrix3::<f64>::size_float()
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        // The matrix is packed by columns but `matrix3_load` reads it by rows
+        self.0
+            .matrix()
+            .transpose()
+            .pack_to(buffer_int, buffer_float);
+    }
+}
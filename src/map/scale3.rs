@@ -0,0 +1,53 @@
+use crate::{map::*, prelude::*, Error, Result};
+use nalgebra::Vector3;
+use std::collections::HashSet;
+
+/// Non-uniform scaling along the coordinate axes.
+///
+/// The factors must be non-zero, because the inverse and normal maps divide by them
+/// (*see `Scale3::try_new()`*).
+pub struct Scale3(pub Vector3<f64>);
+
+impl Scale3 {
+    /// Creates scaling checking that its factors and their inverses
+    /// are finite in single precision.
+    pub fn try_new(x: Vector3<f64>) -> Result<Self> {
+        if x.iter().all(|&a| {
+            let a = a as f32;
+            a.is_finite() && (1.0 / a).is_finite()
+        }) {
+            Ok(Scale3(x))
+        } else {
+            Err(Error::from(format!("degenerate scaling factors: {}", x)))
+        }
+    }
+}
+
+impl From<Vector3<f64>> for Scale3 {
+    fn from(x: Vector3<f64>) -> Self {
+        Scale3(x)
+    }
+}
+
+impl Map for Scale3 {}
+
+impl Instance<MapClass> for Scale3 {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/map/scale3.h>".to_string()
+    }
+    fn inst_name() -> String {
+        "scale3".to_string()
+    }
+}
+
+impl Pack for Scale3 {
+    fn size_int() -> usize {
+        Vector3::<f64>::size_int()
+    }
+    fn size_float() -> usize {
+        Vector3::<f64>::size_float()
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        self.0.pack_to(buffer_int, buffer_float);
+    }
+}
@@ -2,7 +2,11 @@ pub use crate::core::material::*;
 
 mod reflective;
 pub use reflective::*;
+mod refractive;
+pub use refractive::*;
 mod diffuse;
 pub use diffuse::*;
 mod luminous;
 pub use luminous::*;
+mod pdf;
+pub use pdf::*;
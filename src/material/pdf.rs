@@ -0,0 +1,86 @@
+use crate::{material::*, object::*, shape::*};
+
+/// Material that reports the density of the directions sampled by its bounce,
+/// it's needed for the multiple importance sampling (*see `MisHeuristic`*).
+///
+/// Each bounce of the materials of this crate either samples the direction
+/// as `Diffuse` does or is specular and has no density for the other directions,
+/// so the density is `diffuse_prob()` times the density of `Diffuse`.
+///
+/// Materials created by `material_combine!` and `material_select!` implement
+/// the trait with `material_pdf_combine!` and `material_pdf_select!` respectively.
+pub trait MaterialPdf {
+    /// Probability of the bounce to sample the direction as `Diffuse`.
+    fn diffuse_prob(&self) -> f64;
+}
+
+impl MaterialPdf for Diffuse {
+    fn diffuse_prob(&self) -> f64 {
+        1.0
+    }
+}
+
+impl MaterialPdf for Reflective {
+    fn diffuse_prob(&self) -> f64 {
+        0.0
+    }
+}
+
+impl MaterialPdf for Refractive {
+    fn diffuse_prob(&self) -> f64 {
+        0.0
+    }
+}
+
+impl MaterialPdf for Luminous {
+    fn diffuse_prob(&self) -> f64 {
+        0.0
+    }
+}
+
+impl<M: Material + MaterialPdf> MaterialPdf for Colored<M> {
+    fn diffuse_prob(&self) -> f64 {
+        self.material.diffuse_prob()
+    }
+}
+
+impl<S: Shape, M: Material + MaterialPdf> MaterialPdf for Covered<S, M> {
+    fn diffuse_prob(&self) -> f64 {
+        self.material.diffuse_prob()
+    }
+}
+
+/// Implements `MaterialPdf` for the material created by `material_combine!`
+/// mixing the probabilities of its components by their weights.
+#[macro_export]
+macro_rules! material_pdf_combine {
+    ($Combine:ident { $( $field:ident ),+ $(,)? }) => {
+        impl $crate::material::MaterialPdf for $Combine {
+            fn diffuse_prob(&self) -> f64 {
+                let total = 0.0 $( + self.$field.0 )+;
+                let mixed = 0.0 $(
+                    + self.$field.0 * $crate::material::MaterialPdf::diffuse_prob(&self.$field.1)
+                )+;
+                if total > 0.0 {
+                    mixed / total
+                } else {
+                    0.0
+                }
+            }
+        }
+    };
+}
+
+/// Implements `MaterialPdf` for the material created by `material_select!`.
+#[macro_export]
+macro_rules! material_pdf_select {
+    ($Select:ident { $( $Enum:ident ),+ $(,)? }) => {
+        impl $crate::material::MaterialPdf for $Select {
+            fn diffuse_prob(&self) -> f64 {
+                match self {
+                    $( $Select::$Enum(m) => $crate::material::MaterialPdf::diffuse_prob(m), )+
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,69 @@
+use crate::{material::*, prelude::*};
+use std::collections::HashSet;
+
+/// Way to compute the ratio of reflected and transmitted light.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Fresnel {
+    /// Exact Fresnel equations for unpolarized light.
+    #[default]
+    Exact,
+    /// Schlick's approximation.
+    Schlick,
+}
+
+/// Refractive dielectric material (e.g. glass or water).
+///
+/// Randomly reflects or refracts the ray according to the Fresnel equations,
+/// the total internal reflection occurs when the ray cannot leave the object.
+/// Intended to be used with closed shapes.
+#[derive(Clone, Debug)]
+pub struct Refractive {
+    /// Index of refraction.
+    pub ior: f64,
+    pub fresnel: Fresnel,
+}
+
+impl Refractive {
+    pub fn new(ior: f64) -> Self {
+        Self {
+            ior,
+            fresnel: Fresnel::default(),
+        }
+    }
+    pub fn with_fresnel(ior: f64, fresnel: Fresnel) -> Self {
+        Self { ior, fresnel }
+    }
+}
+
+impl Material for Refractive {
+    fn brightness(&self) -> f64 {
+        0.0
+    }
+}
+
+impl Instance<MaterialClass> for Refractive {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/material/refractive.h>".to_string()
+    }
+    fn inst_name() -> String {
+        "refractive".to_string()
+    }
+}
+
+impl Pack for Refractive {
+    fn size_int() -> usize {
+        1
+    }
+    fn size_float() -> usize {
+        1
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        let mode = match self.fresnel {
+            Fresnel::Exact => 0i32,
+            Fresnel::Schlick => 1,
+        };
+        Packer::new(buffer_int, buffer_float)
+            .pack(&mode)
+            .pack(&self.ior);
+    }
+}
@@ -2,3 +2,5 @@ mod render;
 pub use render::*;
 mod postproc;
 pub use postproc::*;
+mod output;
+pub use output::*;
@@ -0,0 +1,223 @@
+use crate::{
+    filter::Filter,
+    process::{Postproc, RenderWorker},
+    scene::Scene,
+    view::View,
+    Error,
+};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    time::Duration,
+};
+
+/// Amount of work to be done by the worker.
+#[derive(Clone, Copy, Debug)]
+pub enum Budget {
+    /// Fixed number of render passes (samples per pixel).
+    Passes(usize),
+    /// Render as many passes as fit into the time interval.
+    Time(Duration),
+}
+
+/// Format of the output image file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// 8-bit PNG of the postprocessed image.
+    Png,
+    /// Binary 8-bit PPM of the postprocessed image.
+    Ppm,
+    /// Portable float map with linear HDR color, bypassing the postprocessing.
+    Pfm,
+    /// Uncompressed OpenEXR with linear HDR color, bypassing the postprocessing.
+    Exr,
+}
+
+impl ImageFormat {
+    /// Guesses the image format by the file extension.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "png" => Some(ImageFormat::Png),
+            "ppm" => Some(ImageFormat::Ppm),
+            "pfm" => Some(ImageFormat::Pfm),
+            "exr" => Some(ImageFormat::Exr),
+            _ => None,
+        }
+    }
+}
+
+/// Runs the worker until the budget is exhausted and returns the number of passes done.
+pub fn run_budget<S: Scene, V: View>(
+    worker: &mut RenderWorker<S, V>,
+    budget: Budget,
+) -> crate::Result<usize> {
+    match budget {
+        Budget::Passes(n) => {
+            for _ in 0..n {
+                worker.run()?;
+            }
+            Ok(n)
+        }
+        Budget::Time(time) => worker.run_for(time),
+    }
+}
+
+/// Reads the postprocessed image as 8-bit RGB data.
+pub fn read_ldr<S: Scene, V: View, F: Filter>(
+    worker: &RenderWorker<S, V>,
+    postproc: &mut Postproc<F>,
+    dims: (usize, usize),
+) -> crate::Result<Vec<u8>> {
+    postproc.process_one(worker.data().buffer())?;
+    postproc.make_image()?;
+    let data = postproc.image().read()?;
+    let channels = data.len() / (dims.0 * dims.1);
+    Ok(data
+        .chunks(channels)
+        .flat_map(|px| px[0..3].iter().cloned())
+        .collect())
+}
+
+/// Reads the unfiltered linear color as RGB floats averaged over the passes.
+pub fn read_hdr<S: Scene, V: View>(
+    worker: &RenderWorker<S, V>,
+    dims: (usize, usize),
+) -> crate::Result<Vec<f32>> {
+    let buffer = worker.data().buffer();
+    let mut data = vec![0f32; 3 * dims.0 * dims.1];
+    buffer.color().read(&mut data).enq()?;
+    // The color buffer accumulates the samples of all passes
+    let n = buffer.n_passes().max(1) as f32;
+    data.iter_mut().for_each(|c| *c /= n);
+    Ok(data)
+}
+
+fn encoding_error<E: std::fmt::Display>(e: E) -> Error {
+    Error::from(format!("image encoding error: {}", e))
+}
+
+/// Writes 8-bit RGB data as PNG.
+pub fn write_png<W: Write>(writer: W, dims: (usize, usize), rgb: &[u8]) -> crate::Result<()> {
+    let mut encoder = png::Encoder::new(writer, dims.0 as u32, dims.1 as u32);
+    encoder.set_color(png::ColorType::RGB);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut w| w.write_image_data(rgb))
+        .map_err(encoding_error)
+}
+
+/// Writes 8-bit RGB data as binary PPM.
+pub fn write_ppm<W: Write>(mut writer: W, dims: (usize, usize), rgb: &[u8]) -> crate::Result<()> {
+    write!(writer, "P6\n{} {}\n255\n", dims.0, dims.1)?;
+    writer.write_all(rgb)?;
+    Ok(())
+}
+
+/// Writes RGB floats as little-endian PFM.
+pub fn write_pfm<W: Write>(mut writer: W, dims: (usize, usize), rgb: &[f32]) -> crate::Result<()> {
+    write!(writer, "PF\n{} {}\n-1.0\n", dims.0, dims.1)?;
+    // Rows are stored from bottom to top
+    for row in rgb.chunks(3 * dims.0).rev() {
+        for x in row {
+            writer.write_all(&x.to_bits().to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+fn exr_ints(xs: &[i32]) -> Vec<u8> {
+    xs.iter().flat_map(|x| x.to_le_bytes().to_vec()).collect()
+}
+
+/// Appends the OpenEXR header attribute.
+fn exr_attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    for s in [name, kind].iter() {
+        header.extend_from_slice(s.as_bytes());
+        header.push(0);
+    }
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+/// Writes RGB floats as single-part scanline OpenEXR without compression.
+pub fn write_exr<W: Write>(mut writer: W, dims: (usize, usize), rgb: &[f32]) -> crate::Result<()> {
+    let (width, height) = (dims.0 as i32, dims.1 as i32);
+    // Magic number and version 2 without any flags
+    let mut header = vec![0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0];
+    // Channels must be sorted by name, each is stored as 32-bit float
+    let mut channels = Vec::new();
+    for name in ["B", "G", "R"].iter() {
+        channels.extend_from_slice(name.as_bytes());
+        channels.push(0);
+        channels.extend_from_slice(&exr_ints(&[2, 0, 1, 1]));
+    }
+    channels.push(0);
+    exr_attribute(&mut header, "channels", "chlist", &channels);
+    exr_attribute(&mut header, "compression", "compression", &[0]);
+    let window = exr_ints(&[0, 0, width - 1, height - 1]);
+    exr_attribute(&mut header, "dataWindow", "box2i", &window);
+    exr_attribute(&mut header, "displayWindow", "box2i", &window);
+    exr_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    let one = 1f32.to_le_bytes();
+    exr_attribute(&mut header, "pixelAspectRatio", "float", &one);
+    exr_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    exr_attribute(&mut header, "screenWindowWidth", "float", &one);
+    header.push(0);
+    writer.write_all(&header)?;
+
+    // Offset table, each uncompressed chunk contains a single scanline
+    let line_size = 3 * 4 * dims.0;
+    let table_end = header.len() + 8 * dims.1;
+    for y in 0..dims.1 {
+        let offset = (table_end + y * (8 + line_size)) as u64;
+        writer.write_all(&offset.to_le_bytes())?;
+    }
+    for (y, row) in rgb.chunks(3 * dims.0).enumerate() {
+        writer.write_all(&exr_ints(&[y as i32, line_size as i32]))?;
+        // Channels of the scanline are stored one after another in B, G, R order
+        for c in (0..3).rev() {
+            for px in row.chunks(3) {
+                writer.write_all(&px[c].to_bits().to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Saves the current result of the worker to the image file.
+pub fn save_image<S: Scene, V: View, F: Filter, P: AsRef<Path>>(
+    path: P,
+    format: ImageFormat,
+    worker: &RenderWorker<S, V>,
+    postproc: &mut Postproc<F>,
+    dims: (usize, usize),
+) -> crate::Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    match format {
+        ImageFormat::Png => write_png(writer, dims, &read_ldr(worker, postproc, dims)?),
+        ImageFormat::Ppm => write_ppm(writer, dims, &read_ldr(worker, postproc, dims)?),
+        ImageFormat::Pfm => write_pfm(writer, dims, &read_hdr(worker, dims)?),
+        ImageFormat::Exr => write_exr(writer, dims, &read_hdr(worker, dims)?),
+    }
+}
+
+/// Renders the image within the budget and saves it to the file
+/// of the format determined by its extension.
+///
+/// Returns the number of passes done.
+pub fn render_to_file<S: Scene, V: View, F: Filter, P: AsRef<Path>>(
+    path: P,
+    budget: Budget,
+    worker: &mut RenderWorker<S, V>,
+    postproc: &mut Postproc<F>,
+    dims: (usize, usize),
+) -> crate::Result<usize> {
+    let format = ImageFormat::from_path(&path)
+        .ok_or_else(|| Error::from(format!("unknown image format: {}", path.as_ref().display())))?;
+    let n = run_budget(worker, budget)?;
+    save_image(path, format, worker, postproc, dims)?;
+    Ok(n)
+}
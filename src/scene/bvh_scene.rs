@@ -0,0 +1,342 @@
+use crate::{
+    buffer::InstanceBuffer,
+    object::*,
+    prelude::*,
+    scene::{Background, RussianRoulette, Scene},
+    shape::*,
+    Context, Error,
+};
+use ocl::{self, builders::KernelBuilder};
+use std::{cmp::Ordering, collections::HashSet};
+use uuid::Uuid;
+
+/// Maximum number of objects in a leaf node.
+const LEAF_SIZE: usize = 4;
+/// Size of the node stack used by the device traversal,
+/// the hierarchy deeper than `BVH_STACK_SIZE - 1` is rejected.
+const BVH_STACK_SIZE: usize = 32;
+
+/// Node of the hierarchy.
+///
+/// Leaf node contains `count` objects starting from `index`,
+/// inner node has zero `count` and `index` pointing to its right child.
+/// The left child of an inner node is always stored right after it.
+struct Node {
+    bounds: Aabb,
+    count: usize,
+    index: usize,
+}
+
+impl Pack for Node {
+    fn size_int() -> usize {
+        2 + Aabb::size_int()
+    }
+    fn size_float() -> usize {
+        Aabb::size_float()
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        Packer::new(buffer_int, buffer_float)
+            .pack(&(self.count as i32))
+            .pack(&(self.index as i32))
+            .pack(&self.bounds);
+    }
+}
+
+/// Recursively builds the hierarchy over `boxes[order[..]]` splitting by median
+/// along the axis of the largest extent. The `order` slice is permuted in place,
+/// `offset` is the position of its first element in the resulting object buffer.
+/// Returns the depth of the subtree.
+fn build_node(boxes: &[Aabb], order: &mut [usize], offset: usize, nodes: &mut Vec<Node>) -> usize {
+    let bounds = order.iter().fold(Aabb::empty(), |b, &i| b.join(&boxes[i]));
+    let index = nodes.len();
+    nodes.push(Node {
+        bounds,
+        count: order.len(),
+        index: offset,
+    });
+    if order.len() <= LEAF_SIZE {
+        return 0;
+    }
+
+    let centers = order
+        .iter()
+        .fold(Aabb::empty(), |b, &i| b.join_point(&boxes[i].center()));
+    let axis = (centers.max - centers.min).imax();
+    order.sort_by(|&a, &b| {
+        boxes[a].center()[axis]
+            .partial_cmp(&boxes[b].center()[axis])
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let mid = order.len() / 2;
+    let (left, right) = order.split_at_mut(mid);
+    let left_depth = build_node(boxes, left, offset, nodes);
+    let right_index = nodes.len();
+    let right_depth = build_node(boxes, right, offset + mid, nodes);
+
+    nodes[index].count = 0;
+    nodes[index].index = right_index;
+    1 + left_depth.max(right_depth)
+}
+
+/// Scene with logarithmic complexity of object search
+/// provided by bounding volume hierarchy.
+///
+/// Objects that have no bounds are checked separately in a linear manner.
+pub struct BvhScene<O: Object + Bounded<Aabb>, B: Background> {
+    objects: Vec<O>,
+    uuid: Uuid,
+    background: B,
+    max_depth: usize,
+    roulette: Option<RussianRoulette>,
+}
+
+impl<O: Object + Bounded<Aabb>, B: Background> BvhScene<O, B> {
+    pub fn new(background: B) -> Self {
+        Self {
+            objects: Vec::new(),
+            background,
+            uuid: Uuid::new_v4(),
+            max_depth: 4,
+            roulette: None,
+        }
+    }
+
+    pub fn add(&mut self, object: O) {
+        self.objects.push(object);
+        self.uuid = Uuid::new_v4();
+    }
+
+    pub fn objects(&self) -> &[O] {
+        &self.objects
+    }
+
+    pub fn background(&self) -> &B {
+        &self.background
+    }
+    pub fn background_mut(&mut self) -> &mut B {
+        &mut self.background
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn russian_roulette(&self) -> Option<RussianRoulette> {
+        self.roulette
+    }
+    /// Enables Russian roulette path termination or disables it if `None` is passed.
+    pub fn set_russian_roulette(&mut self, roulette: Option<RussianRoulette>) {
+        self.roulette = roulette;
+    }
+
+    /// Returns the order of objects in device buffer, the number of unbounded objects
+    /// (they are placed first) and the nodes of the hierarchy.
+    ///
+    /// The device traversal keeps a pending sibling for each level of the hierarchy,
+    /// so the hierarchy that doesn't fit into its stack is reported as an error.
+    fn build(&self) -> crate::Result<(Vec<usize>, usize, Vec<Node>)> {
+        let mut unbounded = Vec::new();
+        let mut bounded = Vec::new();
+        let mut boxes = Vec::new();
+        for (i, object) in self.objects.iter().enumerate() {
+            match object.bound() {
+                Some(bounds) => {
+                    bounded.push(i);
+                    boxes.push(bounds);
+                }
+                None => unbounded.push(i),
+            }
+        }
+
+        let mut order = (0..boxes.len()).collect::<Vec<_>>();
+        let mut nodes = Vec::new();
+        if !order.is_empty() {
+            let depth = build_node(&boxes, &mut order, unbounded.len(), &mut nodes);
+            if depth + 1 > BVH_STACK_SIZE {
+                return Err(Error::from(format!(
+                    "bounding volume hierarchy of depth {} exceeds traversal stack size {}",
+                    depth, BVH_STACK_SIZE,
+                )));
+            }
+        }
+
+        let unbounded_count = unbounded.len();
+        unbounded.extend(order.into_iter().map(|k| bounded[k]));
+        Ok((unbounded, unbounded_count, nodes))
+    }
+}
+
+impl<O: Object + Bounded<Aabb>, B: Background> Scene for BvhScene<O, B> {
+    fn source(cache: &mut HashSet<u64>) -> String {
+        [
+            O::source(cache),
+            B::source(cache),
+            ObjectClass::methods()
+                .into_iter()
+                .map(|method| format!("#define __object_{} {}_{}", method, O::inst_name(), method,))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            format!("#define OBJECT_SIZE_INT {}", O::size_int()),
+            format!("#define OBJECT_SIZE_FLOAT {}", O::size_float()),
+            format!("#define BVH_STACK_SIZE {}", BVH_STACK_SIZE),
+            "#include <clay/scene/bvh_scene.h>".to_string(),
+        ]
+        .join("\n")
+    }
+}
+
+pub struct BvhSceneData<O: Object + Bounded<Aabb>, B: Background> {
+    object_buffer: InstanceBuffer<O>,
+    node_buffer: InstanceBuffer<Node>,
+    unbounded_count: usize,
+    background: B::Data,
+    uuid: Uuid,
+    max_depth: usize,
+    roulette: Option<RussianRoulette>,
+}
+
+impl<O: Object + Bounded<Aabb>, B: Background> Store for BvhScene<O, B> {
+    type Data = BvhSceneData<O, B>;
+    fn create_data(&self, context: &Context) -> clay_core::Result<Self::Data> {
+        let (order, unbounded_count, nodes) = self.build()?;
+        Ok(BvhSceneData {
+            object_buffer: InstanceBuffer::new(context, order.iter().map(|&i| &self.objects[i]))?,
+            node_buffer: InstanceBuffer::new(context, nodes.iter())?,
+            unbounded_count,
+            background: self.background.create_data(context)?,
+            uuid: self.uuid,
+            max_depth: self.max_depth,
+            roulette: self.roulette,
+        })
+    }
+    fn update_data(&self, context: &Context, data: &mut Self::Data) -> clay_core::Result<()> {
+        if self.uuid != data.uuid {
+            *data = self.create_data(context)?;
+        } else {
+            data.max_depth = self.max_depth;
+            data.roulette = self.roulette;
+            self.background.update_data(context, &mut data.background)?;
+        }
+        Ok(())
+    }
+}
+
+impl<O: Object + Bounded<Aabb>, B: Background> Push for BvhSceneData<O, B> {
+    fn args_def(kb: &mut KernelBuilder) {
+        InstanceBuffer::<O>::args_def(kb);
+        kb.arg(0i32);
+        InstanceBuffer::<Node>::args_def(kb);
+        kb.arg(0i32);
+        RussianRoulette::args_def(kb);
+        B::Data::args_def(kb);
+    }
+    fn args_set(&mut self, i: usize, k: &mut ocl::Kernel) -> crate::Result<()> {
+        let mut j = i;
+        self.object_buffer.args_set(j, k)?;
+        j += InstanceBuffer::<O>::args_count();
+        k.set_arg(j, &(self.unbounded_count as i32))?;
+        j += 1;
+        self.node_buffer.args_set(j, k)?;
+        j += InstanceBuffer::<Node>::args_count();
+        k.set_arg(j, &(self.max_depth as i32))?;
+        j += 1;
+        RussianRoulette::args_set(self.roulette, j, k)?;
+        j += RussianRoulette::args_count();
+        self.background.args_set(j, k)
+    }
+    fn args_count() -> usize {
+        InstanceBuffer::<O>::args_count()
+            + 1
+            + InstanceBuffer::<Node>::args_count()
+            + 1
+            + RussianRoulette::args_count()
+            + B::Data::args_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Diffuse, scene::ConstantBackground, shape_select};
+    use nalgebra::Vector3;
+
+    shape_select!(TestShape {
+        Sphere(TS=Sphere),
+        Plane(TP=Plane),
+    });
+    type TestObject = Covered<TestShape, Diffuse>;
+
+    fn contains(outer: &Aabb, inner: &Aabb) -> bool {
+        (0..3).all(|i| outer.min[i] <= inner.min[i] && inner.max[i] <= outer.max[i])
+    }
+
+    /// Checks the subtree and returns the range of its objects and its depth.
+    fn check_node(nodes: &[Node], boxes: &[Option<Aabb>], index: usize) -> (usize, usize, usize) {
+        let node = &nodes[index];
+        if node.count > 0 {
+            assert!(node.count <= LEAF_SIZE);
+            for b in boxes[node.index..(node.index + node.count)].iter() {
+                assert!(contains(&node.bounds, b.as_ref().unwrap()));
+            }
+            return (node.index, node.count, 0);
+        }
+        assert!(node.index > index + 1);
+        for &child in [index + 1, node.index].iter() {
+            assert!(contains(&node.bounds, &nodes[child].bounds));
+        }
+        let (left_first, left_count, left_depth) = check_node(nodes, boxes, index + 1);
+        let (right_first, right_count, right_depth) = check_node(nodes, boxes, node.index);
+        assert_eq!(right_first, left_first + left_count);
+        (
+            left_first,
+            left_count + right_count,
+            1 + left_depth.max(right_depth),
+        )
+    }
+
+    #[test]
+    fn hierarchy() {
+        let count = 200;
+        let mut scene: BvhScene<TestObject, _> =
+            BvhScene::new(ConstantBackground::new(Vector3::zeros()));
+        for i in 0..count {
+            let pos = Vector3::new((i % 7) as f64, ((i / 7) % 5) as f64, (i / 35) as f64);
+            let rad = 0.2 + 0.1 * (i % 3) as f64;
+            scene.add(TestShape::from(Sphere::new(rad, pos)).cover(Diffuse {}));
+            if i % 50 == 0 {
+                scene.add(TestShape::from(Plane::new(pos, Vector3::z())).cover(Diffuse {}));
+            }
+        }
+
+        let (order, unbounded_count, nodes) = scene.build().unwrap();
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..scene.objects().len()).collect::<Vec<_>>());
+
+        let boxes = order
+            .iter()
+            .map(|&i| scene.objects()[i].bound())
+            .collect::<Vec<Option<Aabb>>>();
+        assert_eq!(unbounded_count, 4);
+        assert!(boxes[..unbounded_count].iter().all(|b| b.is_none()));
+
+        let (first, total, depth) = check_node(&nodes, &boxes, 0);
+        assert_eq!((first, total), (unbounded_count, count));
+        assert!(depth < BVH_STACK_SIZE);
+        assert!((1 << depth) < 2 * count);
+    }
+
+    #[test]
+    fn no_bounded_objects() {
+        let mut scene: BvhScene<TestObject, _> =
+            BvhScene::new(ConstantBackground::new(Vector3::zeros()));
+        scene.add(TestShape::from(Plane::new(Vector3::zeros(), Vector3::z())).cover(Diffuse {}));
+        let (order, unbounded_count, nodes) = scene.build().unwrap();
+        assert_eq!((order, unbounded_count), (vec![0], 1));
+        assert!(nodes.is_empty());
+    }
+}
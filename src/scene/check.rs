@@ -0,0 +1,17 @@
+use crate::{prelude::*, Error};
+
+/// Checks that the instances packed on the host contain only finite values.
+///
+/// The instances are validated on construction (e.g. `Linear::try_new()`),
+/// but packing is infallible, so the ones created without validation
+/// (e.g. `Linear` map with singular matrix created by `Linear::from()`)
+/// are packed with NaN values, they are detected here instead of rendering garbage.
+pub(crate) fn check_packed<T: Pack>(buffer_float: &[f32]) -> crate::Result<()> {
+    match buffer_float.iter().position(|x| !x.is_finite()) {
+        Some(p) => Err(Error::from(format!(
+            "instance {} is invalid, e.g. has singular transformation",
+            p / T::size_float().max(1)
+        ))),
+        None => Ok(()),
+    }
+}
@@ -2,36 +2,100 @@ use crate::{
     buffer::InstanceBuffer,
     object::*,
     prelude::*,
-    scene::{Background, Scene},
+    scene::{check_packed, Background, RussianRoulette, Scene},
     Context,
 };
 use ocl::{self, builders::KernelBuilder};
-use std::collections::HashSet;
+use std::{collections::HashSet, ops::Range};
 use uuid::Uuid;
 
 /// Scene with linear complexity of object search.
+///
+/// Objects changed through `get_mut` are tracked, so that only the contiguous ranges
+/// of them are rewritten on the device instead of the whole buffer.
 pub struct ListScene<O: Object, B: Background> {
     objects: Vec<O>,
+    /// Value of `version` at the moment of the last change of each object.
+    versions: Vec<u64>,
+    version: u64,
     uuid: Uuid,
     background: B,
     max_depth: usize,
+    roulette: Option<RussianRoulette>,
 }
 
 impl<O: Object, B: Background> ListScene<O, B> {
     pub fn new(background: B) -> Self {
         Self {
             objects: Vec::new(),
+            versions: Vec::new(),
+            version: 0,
             background,
             uuid: Uuid::new_v4(),
             max_depth: 4,
+            roulette: None,
         }
     }
 
+    /// Adds the object, the next update recreates the device buffer.
     pub fn add(&mut self, object: O) {
         self.objects.push(object);
+        self.versions.push(self.version);
         self.uuid = Uuid::new_v4();
     }
 
+    /// Removes the object at `index` shifting the following ones.
+    ///
+    /// The number of objects changes, so the next update recreates
+    /// the whole device buffer instead of rewriting the changed objects.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> O {
+        self.versions.remove(index);
+        self.uuid = Uuid::new_v4();
+        self.objects.remove(index)
+    }
+
+    /// Removes all objects, the next update recreates the device buffer.
+    pub fn clear(&mut self) {
+        self.objects.clear();
+        self.versions.clear();
+        self.uuid = Uuid::new_v4();
+    }
+
+    pub fn objects(&self) -> &[O] {
+        &self.objects
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, O> {
+        self.objects.iter()
+    }
+
+    /// Provides mutable access to the object and marks it as changed.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut O> {
+        let object = self.objects.get_mut(index)?;
+        self.version += 1;
+        self.versions[index] = self.version;
+        Some(object)
+    }
+
+    /// Contiguous ranges of objects changed since the `version`.
+    fn changed_since(&self, version: u64) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for (i, _) in self
+            .versions
+            .iter()
+            .enumerate()
+            .filter(|(_, &v)| v > version)
+        {
+            match ranges.last_mut() {
+                Some(range) if range.end == i => range.end += 1,
+                _ => ranges.push(i..(i + 1)),
+            }
+        }
+        ranges
+    }
+
     pub fn background(&self) -> &B {
         &self.background
     }
@@ -45,6 +109,14 @@ impl<O: Object, B: Background> ListScene<O, B> {
     pub fn set_max_depth(&mut self, max_depth: usize) {
         self.max_depth = max_depth;
     }
+
+    pub fn russian_roulette(&self) -> Option<RussianRoulette> {
+        self.roulette
+    }
+    /// Enables Russian roulette path termination or disables it if `None` is passed.
+    pub fn set_russian_roulette(&mut self, roulette: Option<RussianRoulette>) {
+        self.roulette = roulette;
+    }
 }
 
 impl<O: Object, B: Background> Scene for ListScene<O, B> {
@@ -65,11 +137,46 @@ impl<O: Object, B: Background> Scene for ListScene<O, B> {
     }
 }
 
+/// Rewrites the `objects` in the device buffer starting from the `offset` one.
+fn write_objects<O: Object>(
+    buffer: &InstanceBuffer<O>,
+    offset: usize,
+    objects: &[O],
+) -> crate::Result<()> {
+    let (size_int, size_float) = (O::size_int(), O::size_float());
+    let mut buffer_int = vec![0; size_int * objects.len()];
+    let mut buffer_float = vec![0.0; size_float * objects.len()];
+    for (i, object) in objects.iter().enumerate() {
+        object.pack_to(
+            &mut buffer_int[(i * size_int)..((i + 1) * size_int)],
+            &mut buffer_float[(i * size_float)..((i + 1) * size_float)],
+        );
+    }
+    check_packed::<O>(&buffer_float)?;
+    if !buffer_int.is_empty() {
+        buffer
+            .buffer_int()
+            .write(&buffer_int)
+            .offset(offset * size_int)
+            .enq()?;
+    }
+    if !buffer_float.is_empty() {
+        buffer
+            .buffer_float()
+            .write(&buffer_float)
+            .offset(offset * size_float)
+            .enq()?;
+    }
+    Ok(())
+}
+
 pub struct ListSceneData<O: Object, B: Background> {
     buffer: InstanceBuffer<O>,
     background: B::Data,
     uuid: Uuid,
+    version: u64,
     max_depth: usize,
+    roulette: Option<RussianRoulette>,
 }
 
 impl<O: Object, B: Background> Store for ListScene<O, B> {
@@ -79,14 +186,21 @@ impl<O: Object, B: Background> Store for ListScene<O, B> {
             buffer: InstanceBuffer::new(context, self.objects.iter())?,
             background: self.background.create_data(context)?,
             uuid: self.uuid,
+            version: self.version,
             max_depth: self.max_depth,
+            roulette: self.roulette,
         })
     }
     fn update_data(&self, context: &Context, data: &mut Self::Data) -> clay_core::Result<()> {
         if self.uuid != data.uuid {
             *data = self.create_data(context)?;
         } else {
+            for range in self.changed_since(data.version) {
+                write_objects(&data.buffer, range.start, &self.objects[range])?;
+            }
+            data.version = self.version;
             data.max_depth = self.max_depth;
+            data.roulette = self.roulette;
             self.background.update_data(context, &mut data.background)?;
         }
         Ok(())
@@ -97,6 +211,7 @@ impl<O: Object, B: Background> Push for ListSceneData<O, B> {
     fn args_def(kb: &mut KernelBuilder) {
         InstanceBuffer::<O>::args_def(kb);
         kb.arg(0i32);
+        RussianRoulette::args_def(kb);
         B::Data::args_def(kb);
     }
     fn args_set(&mut self, i: usize, k: &mut ocl::Kernel) -> crate::Result<()> {
@@ -105,9 +220,94 @@ impl<O: Object, B: Background> Push for ListSceneData<O, B> {
         j += InstanceBuffer::<O>::args_count();
         k.set_arg(j, &(self.max_depth as i32))?;
         j += 1;
+        RussianRoulette::args_set(self.roulette, j, k)?;
+        j += RussianRoulette::args_count();
         self.background.args_set(j, k)
     }
     fn args_count() -> usize {
-        InstanceBuffer::<O>::args_count() + 1 + B::Data::args_count()
+        InstanceBuffer::<O>::args_count()
+            + 1
+            + RussianRoulette::args_count()
+            + B::Data::args_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Diffuse, scene::ConstantBackground, shape::Sphere};
+    use nalgebra::Vector3;
+    use ocl::{flags::DeviceType, Device, Platform};
+
+    fn scene(count: usize) -> ListScene<Covered<Sphere, Diffuse>, ConstantBackground> {
+        let mut scene = ListScene::new(ConstantBackground::new(Vector3::zeros()));
+        for i in 0..count {
+            scene.add(Sphere::new(1.0, Vector3::new(i as f64, 0.0, 0.0)).cover(Diffuse {}));
+        }
+        scene
+    }
+
+    fn cpu_context() -> Option<Context> {
+        Platform::list().into_iter().find_map(|platform| {
+            let device = Device::list(platform, Some(DeviceType::CPU))
+                .ok()?
+                .into_iter()
+                .next()?;
+            Context::new(platform, device).ok()
+        })
+    }
+
+    #[test]
+    fn changed_ranges() {
+        let mut scene = scene(6);
+        let version = scene.version;
+        assert!(scene.changed_since(version).is_empty());
+
+        for &i in [1, 4, 2].iter() {
+            scene.get_mut(i).unwrap();
+        }
+        assert_eq!(scene.changed_since(version), vec![1..3, 4..5]);
+        assert_eq!(scene.changed_since(version + 2), vec![2..3]);
+    }
+
+    #[test]
+    fn partial_update() {
+        let context = match cpu_context() {
+            Some(context) => context,
+            None => {
+                eprintln!("no CPU OpenCL device found, skipping the test");
+                return;
+            }
+        };
+        let mut scene = scene(6);
+        let mut data = scene.create_data(&context).unwrap();
+        let size = Covered::<Sphere, Diffuse>::size_float();
+        let sentinel = vec![-1.0f32; 6 * size];
+        data.buffer.buffer_float().write(&sentinel).enq().unwrap();
+
+        for &i in [1, 2, 4].iter() {
+            scene.get_mut(i).unwrap().shape = Sphere::new(2.0, Vector3::new(0.0, i as f64, 0.0));
+        }
+        scene.update_data(&context, &mut data).unwrap();
+
+        // Only the changed objects are rewritten
+        let mut buffer_float = vec![0.0f32; 6 * size];
+        data.buffer
+            .buffer_float()
+            .read(&mut buffer_float)
+            .enq()
+            .unwrap();
+        let mut expected = vec![0.0f32; size];
+        for (i, packed) in buffer_float.chunks(size).enumerate() {
+            if [1, 2, 4].contains(&i) {
+                scene.objects()[i].pack_to(
+                    &mut vec![0; Covered::<Sphere, Diffuse>::size_int()],
+                    &mut expected,
+                );
+                assert_eq!(packed, &expected[..]);
+            } else {
+                assert_eq!(packed, &sentinel[..size]);
+            }
+        }
     }
 }
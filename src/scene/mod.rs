@@ -4,6 +4,12 @@ mod list_scene;
 pub use list_scene::*;
 mod target_list_scene;
 pub use target_list_scene::*;
+mod bvh_scene;
+pub use bvh_scene::*;
+mod check;
+pub(crate) use check::*;
+mod roulette;
+pub use roulette::*;
 
 mod background;
 pub use background::*;
@@ -0,0 +1,95 @@
+use crate::Error;
+use ocl::{self, builders::KernelBuilder};
+
+/// Parameters of the Russian roulette path termination.
+///
+/// After the path reaches the `depth` number of bounces it is randomly terminated
+/// with probability depending on its throughput (the maximum component of the ray color),
+/// and the color of the survived rays is increased accordingly, so the result stays unbiased.
+#[derive(Clone, Copy, Debug)]
+pub struct RussianRoulette {
+    /// Number of bounces after which the roulette starts.
+    pub depth: usize,
+    /// Minimum probability of the path survival, it must be in `(0, 1]`.
+    pub min_prob: f64,
+}
+
+impl RussianRoulette {
+    /// Panics if `min_prob` is not in `(0, 1]`.
+    pub fn new(depth: usize, min_prob: f64) -> Self {
+        let roulette = Self { depth, min_prob };
+        assert!(
+            roulette.is_valid(),
+            "minimum survival probability {} is not in (0, 1]",
+            min_prob
+        );
+        roulette
+    }
+
+    /// Checks that the survival probability is positive in single precision,
+    /// so that the survived rays are not divided by zero, and doesn't exceed one,
+    /// otherwise the `clamp` in the kernel is undefined.
+    fn is_valid(&self) -> bool {
+        let min_prob = self.min_prob as f32;
+        min_prob > 0.0 && min_prob <= 1.0
+    }
+
+    pub(crate) fn args_def(kb: &mut KernelBuilder) {
+        kb.arg(0i32).arg(0f32);
+    }
+    /// Sets kernel arguments, the disabled roulette is passed as the negative depth.
+    pub(crate) fn args_set(
+        roulette: Option<Self>,
+        i: usize,
+        k: &mut ocl::Kernel,
+    ) -> crate::Result<()> {
+        let (depth, min_prob) = match roulette {
+            Some(r) if !r.is_valid() => {
+                return Err(Error::from(format!(
+                    "minimum survival probability {} is not in (0, 1]",
+                    r.min_prob
+                )))
+            }
+            Some(r) => (r.depth as i32, r.min_prob as f32),
+            None => (-1, 0.0),
+        };
+        k.set_arg(i, &depth)?;
+        k.set_arg(i + 1, &min_prob)?;
+        Ok(())
+    }
+    pub(crate) fn args_count() -> usize {
+        2
+    }
+}
+
+impl Default for RussianRoulette {
+    fn default() -> Self {
+        Self::new(3, 0.05)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_prob() {
+        assert!(RussianRoulette::new(3, 1.0).is_valid());
+        assert!(RussianRoulette::default().is_valid());
+        for &min_prob in [0.0, -0.1, 1.5, 1e-50, std::f64::NAN].iter() {
+            assert!(!RussianRoulette { depth: 3, min_prob }.is_valid());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn min_prob_above_one() {
+        RussianRoulette::new(3, 1.5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn min_prob_zero() {
+        RussianRoulette::new(3, 0.0);
+    }
+}
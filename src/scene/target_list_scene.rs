@@ -1,8 +1,9 @@
 use crate::{
     buffer::InstanceBuffer,
+    material::MaterialPdf,
     object::*,
     prelude::*,
-    scene::{Background, Scene},
+    scene::{Background, RussianRoulette, Scene},
     shape::*,
     Context,
 };
@@ -13,6 +14,10 @@ use uuid::Uuid;
 struct TargetData<T: Target> {
     object_index: usize,
     brightness: f64,
+    /// Probability of the target to be chosen.
+    prob: f64,
+    /// Cumulative probability of this and all previous targets.
+    cdf: f64,
     target: Rc<T>,
 }
 
@@ -21,27 +26,53 @@ impl<T: Target> Pack for TargetData<T> {
         1 + T::size_int()
     }
     fn size_float() -> usize {
-        1 + T::size_float()
+        3 + T::size_float()
     }
     fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
         buffer_int.pack(&(self.object_index as i32));
         buffer_float.pack(&(self.brightness as f32));
+        buffer_float[1..].pack(&(self.prob as f32));
+        buffer_float[2..].pack(&(self.cdf as f32));
         self.target
-            .pack_to(&mut buffer_int[1..], &mut buffer_float[1..]);
+            .pack_to(&mut buffer_int[1..], &mut buffer_float[3..]);
+    }
+}
+
+/// Sets the probabilities of targets to be proportional to their brightness.
+/// If all the targets are dark then they are chosen uniformly.
+fn target_distribution<T: Target>(targets: &mut [TargetData<T>]) {
+    let total = targets.iter().map(|t| t.brightness.max(0.0)).sum::<f64>();
+    let count = targets.len() as f64;
+    let mut cdf = 0.0;
+    for t in targets.iter_mut() {
+        t.prob = if total > 0.0 {
+            t.brightness.max(0.0) / total
+        } else {
+            1.0 / count
+        };
+        cdf += t.prob;
+        t.cdf = cdf;
+    }
+    // Protect from rounding errors, so that the dark targets are never chosen
+    if let Some(k) = targets.iter().rposition(|t| t.prob > 0.0) {
+        for t in targets[k..].iter_mut() {
+            t.cdf = 1.0;
+        }
     }
 }
 
-struct ObjectData<O: Object> {
+/// The object is preceded by its probability of diffuse bounce (*see `MaterialPdf`*).
+struct ObjectData<O: Object + MaterialPdf> {
     target_index: Option<usize>,
     object: Rc<O>,
 }
 
-impl<O: Object> Pack for ObjectData<O> {
+impl<O: Object + MaterialPdf> Pack for ObjectData<O> {
     fn size_int() -> usize {
         1 + O::size_int()
     }
     fn size_float() -> usize {
-        O::size_float()
+        1 + O::size_float()
     }
     fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
         buffer_int.pack(
@@ -50,29 +81,67 @@ impl<O: Object> Pack for ObjectData<O> {
                 None => -1i32,
             }),
         );
-        self.object.pack_to(&mut buffer_int[1..], buffer_float);
+        buffer_float.pack(&(self.object.diffuse_prob() as f32));
+        self.object
+            .pack_to(&mut buffer_int[1..], &mut buffer_float[1..]);
     }
 }
 
 type Element<O, T> = (O, Option<(T, f64)>);
 
+/// Heuristic of multiple importance sampling that combines
+/// the light target sampling and the material sampling.
+///
+/// The heuristics need the density of the material sampling,
+/// it is reported by the objects through `MaterialPdf`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MisHeuristic {
+    /// No MIS, diffuse rays that hit targets are discarded
+    /// and the light is gathered only by the target sampling.
+    Disabled,
+    /// Balance heuristic.
+    Balance,
+    /// Power heuristic with the exponent of two.
+    Power,
+}
+
+impl Default for MisHeuristic {
+    fn default() -> Self {
+        MisHeuristic::Power
+    }
+}
+
+impl MisHeuristic {
+    fn code(self) -> i32 {
+        match self {
+            MisHeuristic::Disabled => 0,
+            MisHeuristic::Balance => 1,
+            MisHeuristic::Power => 2,
+        }
+    }
+}
+
 /// Scene with linear complexity and importance sampling for bright objects.
-pub struct TargetListScene<O: Object + Targeted<T>, T: Target, B: Background> {
+pub struct TargetListScene<O: Object + Targeted<T> + MaterialPdf, T: Target, B: Background> {
     elements: Cell<Vec<Element<O, T>>>,
     background: B,
     uuid: Uuid,
     max_depth: usize,
+    roulette: Option<RussianRoulette>,
     target_prob: f64,
+    mis: MisHeuristic,
 }
 
-impl<O: Object + Targeted<T>, T: Target, B: Background> TargetListScene<O, T, B> {
+impl<O: Object + Targeted<T> + MaterialPdf, T: Target, B: Background> TargetListScene<O, T, B> {
     pub fn new(background: B) -> Self {
         Self {
             elements: Cell::new(Vec::new()),
             background,
             uuid: Uuid::new_v4(),
             max_depth: 4,
+            roulette: None,
             target_prob: 0.5,
+            mis: MisHeuristic::default(),
         }
     }
     pub fn add(&mut self, object: O) {
@@ -99,24 +168,43 @@ impl<O: Object + Targeted<T>, T: Target, B: Background> TargetListScene<O, T, B>
         self.max_depth = max_depth;
     }
 
+    pub fn russian_roulette(&self) -> Option<RussianRoulette> {
+        self.roulette
+    }
+    /// Enables Russian roulette path termination or disables it if `None` is passed.
+    pub fn set_russian_roulette(&mut self, roulette: Option<RussianRoulette>) {
+        self.roulette = roulette;
+    }
+
     pub fn target_prob(&self) -> f64 {
         self.target_prob
     }
     pub fn set_target_prob(&mut self, target_prob: f64) {
         self.target_prob = target_prob;
     }
+
+    pub fn mis(&self) -> MisHeuristic {
+        self.mis
+    }
+    pub fn set_mis(&mut self, mis: MisHeuristic) {
+        self.mis = mis;
+    }
 }
 
-pub struct TargetListSceneData<O: Object + Targeted<T>, T: Target, B: Background> {
+pub struct TargetListSceneData<O: Object + Targeted<T> + MaterialPdf, T: Target, B: Background> {
     object_buffer: InstanceBuffer<ObjectData<O>>,
     target_buffer: InstanceBuffer<TargetData<T>>,
     background: B::Data,
     uuid: Uuid,
     max_depth: usize,
+    roulette: Option<RussianRoulette>,
     target_prob: f64,
+    mis: MisHeuristic,
 }
 
-impl<O: Object + Targeted<T>, T: Target, B: Background> Scene for TargetListScene<O, T, B> {
+impl<O: Object + Targeted<T> + MaterialPdf, T: Target, B: Background> Scene
+    for TargetListScene<O, T, B>
+{
     fn source(cache: &mut HashSet<u64>) -> String {
         [
             O::source(cache),
@@ -132,6 +220,12 @@ impl<O: Object + Targeted<T>, T: Target, B: Background> Scene for TargetListScen
                 .map(|method| format!("#define __target_{} {}_{}", method, T::inst_name(), method,))
                 .collect::<Vec<_>>()
                 .join("\n"),
+            // Targets whose density depends on the direction provide it
+            // with the `pdf` function marked by the macro of the same name
+            format!(
+                "#ifdef {0}_pdf\n#define __target_pdf {0}_pdf\n#endif",
+                T::inst_name()
+            ),
             format!("#define OBJECT_SIZE_INT {}", ObjectData::<O>::size_int()),
             format!(
                 "#define OBJECT_SIZE_FLOAT {}",
@@ -148,7 +242,9 @@ impl<O: Object + Targeted<T>, T: Target, B: Background> Scene for TargetListScen
     }
 }
 
-impl<O: Object + Targeted<T>, T: Target, B: Background> Store for TargetListScene<O, T, B> {
+impl<O: Object + Targeted<T> + MaterialPdf, T: Target, B: Background> Store
+    for TargetListScene<O, T, B>
+{
     type Data = TargetListSceneData<O, T, B>;
     fn create_data(&self, context: &Context) -> clay_core::Result<Self::Data> {
         let elems = self
@@ -171,6 +267,8 @@ impl<O: Object + Targeted<T>, T: Target, B: Background> Store for TargetListScen
                         object_index: i,
                         target: target.clone(),
                         brightness: *brightness,
+                        prob: 0.0,
+                        cdf: 0.0,
                     });
                 }
                 None => {
@@ -182,6 +280,8 @@ impl<O: Object + Targeted<T>, T: Target, B: Background> Store for TargetListScen
             }
         }
 
+        target_distribution(&mut targets);
+
         let res = InstanceBuffer::new(context, objects.iter())
             .and_then(|ob| InstanceBuffer::new(context, targets.iter()).map(|tb| (ob, tb)));
         let _ = (objects, targets);
@@ -220,7 +320,9 @@ impl<O: Object + Targeted<T>, T: Target, B: Background> Store for TargetListScen
             background: self.background.create_data(context)?,
             uuid: self.uuid,
             max_depth: self.max_depth,
+            roulette: self.roulette,
             target_prob: self.target_prob,
+            mis: self.mis,
         })
     }
     fn update_data(&self, context: &Context, data: &mut Self::Data) -> clay_core::Result<()> {
@@ -228,19 +330,25 @@ impl<O: Object + Targeted<T>, T: Target, B: Background> Store for TargetListScen
             *data = self.create_data(context)?;
         } else {
             data.max_depth = self.max_depth;
+            data.roulette = self.roulette;
             data.target_prob = self.target_prob;
+            data.mis = self.mis;
             self.background.update_data(context, &mut data.background)?;
         }
         Ok(())
     }
 }
 
-impl<O: Object + Targeted<T>, T: Target, B: Background> Push for TargetListSceneData<O, T, B> {
+impl<O: Object + Targeted<T> + MaterialPdf, T: Target, B: Background> Push
+    for TargetListSceneData<O, T, B>
+{
     fn args_def(kb: &mut KernelBuilder) {
         InstanceBuffer::<ObjectData<O>>::args_def(kb);
         InstanceBuffer::<TargetData<T>>::args_def(kb);
         kb.arg(0i32);
         kb.arg(0f32);
+        kb.arg(0i32);
+        RussianRoulette::args_def(kb);
         B::Data::args_def(kb);
     }
     fn args_set(&mut self, i: usize, k: &mut ocl::Kernel) -> crate::Result<()> {
@@ -249,15 +357,131 @@ impl<O: Object + Targeted<T>, T: Target, B: Background> Push for TargetListScene
         j += InstanceBuffer::<ObjectData<O>>::args_count();
         self.target_buffer.args_set(j, k)?;
         j += InstanceBuffer::<TargetData<T>>::args_count();
-        k.set_arg(j + 0, &(self.max_depth as i32))?;
+        k.set_arg(j, &(self.max_depth as i32))?;
         k.set_arg(j + 1, &(self.target_prob as f32))?;
-        j += 2;
+        k.set_arg(j + 2, &self.mis.code())?;
+        j += 3;
+        RussianRoulette::args_set(self.roulette, j, k)?;
+        j += RussianRoulette::args_count();
         self.background.args_set(j, k)
     }
     fn args_count() -> usize {
         InstanceBuffer::<ObjectData<O>>::args_count()
             + InstanceBuffer::<TargetData<T>>::args_count()
-            + 2
+            + 3
+            + RussianRoulette::args_count()
             + B::Data::args_count()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        desc::{DescGlossy, DescMaterial},
+        material::*,
+    };
+    use nalgebra::Vector3;
+
+    fn targets(brightness: &[f64]) -> Vec<TargetData<Sphere>> {
+        let mut targets = brightness
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| TargetData {
+                object_index: i,
+                brightness: b,
+                prob: 0.0,
+                cdf: 0.0,
+                target: Rc::new(Sphere::new(1.0, Vector3::zeros())),
+            })
+            .collect::<Vec<_>>();
+        target_distribution(&mut targets);
+        targets
+    }
+
+    /// Host version of `target_choose` from `target_list_scene.h` over the packed CDF.
+    fn choose(targets: &[TargetData<Sphere>], r: f32) -> usize {
+        let (mut lo, mut hi) = (0, targets.len() - 1);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if r < targets[mid].cdf as f32 {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        lo
+    }
+
+    /// The largest `f32` less than one that `random_uniform` could return.
+    const R_MAX: f32 = 1.0 - std::f32::EPSILON / 2.0;
+
+    #[test]
+    fn proportional() {
+        let ts = targets(&[1.0, 3.0, 0.0, 4.0]);
+        let probs = ts.iter().map(|t| t.prob).collect::<Vec<_>>();
+        assert_eq!(probs, vec![0.125, 0.375, 0.0, 0.5]);
+        let cdf = ts.iter().map(|t| t.cdf).collect::<Vec<_>>();
+        assert_eq!(cdf, vec![0.125, 0.5, 0.5, 1.0]);
+
+        assert_eq!(choose(&ts, 0.0), 0);
+        assert_eq!(choose(&ts, 0.125), 1);
+        // The dark target in the middle is never chosen
+        assert_eq!(choose(&ts, 0.5), 3);
+        assert_eq!(choose(&ts, R_MAX), 3);
+    }
+
+    #[test]
+    fn trailing_dark() {
+        // Probabilities that don't sum up to one exactly
+        let ts = targets(&[0.1, 0.2, 0.3, 0.0, 0.0]);
+        assert_eq!(ts[3].prob, 0.0);
+        assert_eq!(ts[4].prob, 0.0);
+        assert!(ts[2..].iter().all(|t| t.cdf == 1.0));
+        assert_eq!(choose(&ts, R_MAX), 2);
+    }
+
+    #[test]
+    fn all_dark() {
+        let ts = targets(&[0.0, 0.0, -1.0, 0.0]);
+        for (i, t) in ts.iter().enumerate() {
+            assert_eq!(t.prob, 0.25);
+            assert!((t.cdf - 0.25 * (i + 1) as f64).abs() < 1e-12);
+        }
+        for (i, &r) in [0.0, 0.3, 0.6, R_MAX].iter().enumerate() {
+            assert_eq!(choose(&ts, r), i);
+        }
+    }
+
+    #[test]
+    fn very_different() {
+        let ts = targets(&[1e-6, 1e6]);
+        assert!(ts[0].prob > 0.0);
+        assert!((ts.iter().map(|t| t.prob).sum::<f64>() - 1.0).abs() < 1e-12);
+        assert_eq!(choose(&ts, 0.0), 0);
+        assert_eq!(choose(&ts, 1e-6), 1);
+        assert_eq!(ts[1].cdf, 1.0);
+    }
+
+    fn packed_diffuse_prob<M: Material + MaterialPdf>(material: M) -> f32 {
+        let data = ObjectData {
+            target_index: None,
+            object: Rc::new(Sphere::new(1.0, Vector3::zeros()).cover(material)),
+        };
+        let mut buffer_int = vec![0; ObjectData::<Covered<Sphere, M>>::size_int()];
+        let mut buffer_float = vec![0.0; ObjectData::<Covered<Sphere, M>>::size_float()];
+        data.pack_to(&mut buffer_int, &mut buffer_float);
+        buffer_float[0]
+    }
+
+    #[test]
+    fn material_pdf() {
+        let color = Vector3::new(1.0, 1.0, 1.0);
+        assert_eq!(packed_diffuse_prob(Diffuse {}.color_with(color)), 1.0);
+        assert_eq!(packed_diffuse_prob(Refractive::new(1.5)), 0.0);
+        let glossy = DescGlossy::new((1.0, Reflective {}), (3.0, Diffuse {}.color_with(color)));
+        assert_eq!(packed_diffuse_prob(glossy), 0.75);
+        let selected = DescMaterial::from(Reflective {}.color_with(color));
+        assert_eq!(packed_diffuse_prob(selected), 0.0);
+    }
+}
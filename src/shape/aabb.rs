@@ -0,0 +1,168 @@
+use crate::{map::*, prelude::*, shape::*};
+use nalgebra::{Matrix3, Vector3};
+use std::collections::HashSet;
+
+/// Axis-aligned bounding box.
+#[derive(Clone, Debug)]
+pub struct Aabb {
+    /// Minimal corner of the box.
+    pub min: Vector3<f64>,
+    /// Maximal corner of the box.
+    pub max: Vector3<f64>,
+}
+
+impl Aabb {
+    pub fn new(min: Vector3<f64>, max: Vector3<f64>) -> Self {
+        Self { min, max }
+    }
+    /// Box that contains nothing, the identity for `join`.
+    pub fn empty() -> Self {
+        Self::new(
+            Vector3::repeat(std::f64::INFINITY),
+            Vector3::repeat(-std::f64::INFINITY),
+        )
+    }
+    /// Box that contains the unit sphere and the unit cube.
+    pub fn unit() -> Self {
+        Self::new(-Vector3::repeat(1.0), Vector3::repeat(1.0))
+    }
+    /// Box of the specified half size around the center.
+    pub fn from_center(center: Vector3<f64>, half_size: Vector3<f64>) -> Self {
+        Self::new(center - half_size, center + half_size)
+    }
+
+    pub fn center(&self) -> Vector3<f64> {
+        0.5 * (self.min + self.max)
+    }
+    pub fn half_size(&self) -> Vector3<f64> {
+        0.5 * (self.max - self.min)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.min.iter().zip(self.max.iter()).any(|(a, b)| a > b)
+    }
+    /// Smallest sphere that contains the box.
+    pub fn bounding_sphere(&self) -> Sphere {
+        Sphere::new(self.half_size().norm(), self.center())
+    }
+
+    /// Smallest box that contains both boxes.
+    pub fn join(&self, other: &Self) -> Self {
+        Self::new(
+            self.min.zip_map(&other.min, f64::min),
+            self.max.zip_map(&other.max, f64::max),
+        )
+    }
+    /// Largest box that is contained in both boxes.
+    pub fn meet(&self, other: &Self) -> Self {
+        Self::new(
+            self.min.zip_map(&other.min, f64::max),
+            self.max.zip_map(&other.max, f64::min),
+        )
+    }
+    /// Smallest box that contains the box and the point.
+    pub fn join_point(&self, point: &Vector3<f64>) -> Self {
+        self.join(&Self::new(*point, *point))
+    }
+
+    /// Smallest box that contains the image of the box under the affine transform.
+    pub fn map_affine(&self, linear: &Matrix3<f64>, shift: &Vector3<f64>) -> Self {
+        if self.is_empty() {
+            return self.clone();
+        }
+        Self::from_center(
+            linear * self.center() + shift,
+            linear.abs() * self.half_size(),
+        )
+    }
+}
+
+impl Bound for Aabb {}
+impl Instance<BoundClass> for Aabb {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/shape/aabb.h>".to_string()
+    }
+    fn inst_name() -> String {
+        "aabb".to_string()
+    }
+}
+
+impl Pack for Aabb {
+    fn size_int() -> usize {
+        2 * Vector3::<f64>::size_int()
+    }
+    fn size_float() -> usize {
+        2 * Vector3::<f64>::size_float()
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        Packer::new(buffer_int, buffer_float)
+            .pack(&self.min)
+            .pack(&self.max);
+    }
+}
+
+impl<S: Shape + Bounded<Aabb>> Bounded<Aabb> for ShapeMapper<S, Affine> {
+    fn bound(&self) -> Option<Aabb> {
+        self.shape
+            .bound()
+            .map(|b| b.map_affine(&self.map.first.0, &self.map.second.0))
+    }
+}
+
+impl<S: Shape + Bounded<Aabb>> Bounded<Sphere> for ShapeMapper<S, Affine> {
+    fn bound(&self) -> Option<Sphere> {
+        Bounded::<Aabb>::bound(self)
+            .filter(|b| !b.is_empty())
+            .map(|b| b.bounding_sphere())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &Vector3<f64>, b: &Vector3<f64>) {
+        assert!((a - b).amax() < 1e-12, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn join_meet() {
+        let a = Aabb::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(2.0, 1.0, 1.0));
+        let b = Aabb::new(Vector3::new(1.0, -1.0, 0.5), Vector3::new(3.0, 0.5, 2.0));
+
+        let j = a.join(&b);
+        assert_eq!(j.min, Vector3::new(0.0, -1.0, 0.0));
+        assert_eq!(j.max, Vector3::new(3.0, 1.0, 2.0));
+
+        let m = a.meet(&b);
+        assert_eq!(m.min, Vector3::new(1.0, 0.0, 0.5));
+        assert_eq!(m.max, Vector3::new(2.0, 0.5, 1.0));
+
+        let far = Aabb::from_center(Vector3::new(10.0, 0.0, 0.0), Vector3::repeat(1.0));
+        assert!(a.meet(&far).is_empty());
+
+        let e = Aabb::empty();
+        assert!(e.is_empty());
+        assert_eq!(e.join(&a).min, a.min);
+        assert_eq!(e.join(&a).max, a.max);
+        assert!(e.meet(&a).is_empty());
+    }
+
+    #[test]
+    fn map_affine() {
+        let a = Aabb::new(Vector3::new(-1.0, 0.0, 1.0), Vector3::new(1.0, 2.0, 4.0));
+        let linear = Matrix3::new(0.5, -1.0, 0.0, 2.0, 0.0, 1.0, 0.0, -3.0, 0.25);
+        let shift = Vector3::new(1.0, -2.0, 3.0);
+        let b = a.map_affine(&linear, &shift);
+
+        // The box of an affine image is spanned by the images of the corners
+        let corners = (0..8).map(|i| {
+            let t = Vector3::new((i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64);
+            linear * (a.min + (a.max - a.min).component_mul(&t)) + shift
+        });
+        let c = corners.fold(Aabb::empty(), |c, p| c.join_point(&p));
+        assert_close(&b.min, &c.min);
+        assert_close(&b.max, &c.max);
+
+        assert!(Aabb::empty().map_affine(&linear, &shift).is_empty());
+    }
+}
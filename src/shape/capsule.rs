@@ -0,0 +1,82 @@
+use crate::{map::*, prelude::*, shape::*, Error, Result};
+use nalgebra::{linalg::SVD, Vector3};
+use std::collections::HashSet;
+
+type CapsuleBase = ShapeMapper<UnitCapsule, Affine>;
+/// Capsule defined by affine transform on unit capsule.
+pub struct Capsule(pub CapsuleBase);
+
+impl Capsule {
+    /// Creates capsule of radius `rad` around the segment from `a` to `b`.
+    pub fn new(rad: f64, a: Vector3<f64>, b: Vector3<f64>) -> Self {
+        let axis = b - a;
+        let len = axis.norm();
+        let ori = if len > 0.0 {
+            axis_ori(&axis, rad, rad)
+        } else {
+            axis_ori(&Vector3::z(), rad, rad)
+        };
+        Self::from(
+            UnitCapsule::new(0.5 * len / rad)
+                .map(Linear::from(ori).chain(Shift::from(0.5 * (a + b)))),
+        )
+    }
+    /// Creates capsule failing if `rad` is not positive.
+    pub fn try_new(rad: f64, a: Vector3<f64>, b: Vector3<f64>) -> Result<Self> {
+        if rad > 0.0 && rad.is_finite() {
+            Ok(Self::new(rad, a, b))
+        } else {
+            Err(Error::from(format!("invalid radius of capsule: {}", rad)))
+        }
+    }
+}
+impl From<CapsuleBase> for Capsule {
+    fn from(base: CapsuleBase) -> Self {
+        Self(base)
+    }
+}
+
+impl Shape for Capsule {}
+
+impl Instance<ShapeClass> for Capsule {
+    fn source(cache: &mut HashSet<u64>) -> String {
+        CapsuleBase::source(cache)
+    }
+    fn inst_name() -> String {
+        CapsuleBase::inst_name()
+    }
+}
+
+impl Pack for Capsule {
+    fn size_int() -> usize {
+        CapsuleBase::size_int()
+    }
+    fn size_float() -> usize {
+        CapsuleBase::size_float()
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        self.0.pack_to(buffer_int, buffer_float);
+    }
+}
+
+impl Bounded<Sphere> for Capsule {
+    fn bound(&self) -> Option<Sphere> {
+        let ori = self.0.map.first.0;
+        let rad = SVD::new(ori, false, false)
+            .singular_values
+            .as_slice()
+            .iter()
+            .fold(std::f64::NAN, |a, b| f64::max(a, *b));
+        let half_len = self.0.shape.half_len * ori.column(2).norm();
+        Some(Sphere::new(rad + half_len, self.0.map.second.0))
+    }
+}
+
+impl Bounded<Aabb> for Capsule {
+    fn bound(&self) -> Option<Aabb> {
+        let ori = self.0.map.first.0;
+        let h = self.0.shape.half_len;
+        let half_size = Vector3::from_iterator(ori.row_iter().map(|r| r.norm() + h * r[2].abs()));
+        Some(Aabb::from_center(self.0.map.second.0, half_size))
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{map::*, prelude::*, shape::*, Result};
+use nalgebra::{Matrix3, Vector3};
+use std::collections::HashSet;
+
+type ConeBase = ShapeMapper<UnitCone, Affine>;
+/// Cone defined by affine transform on unit cone.
+pub struct Cone(pub ConeBase);
+
+impl Cone {
+    pub fn new(ori: Matrix3<f64>, pos: Vector3<f64>) -> Self {
+        Self::from(UnitCone::new().map(Linear::from(ori).chain(Shift::from(pos))))
+    }
+    /// Creates cone failing if `ori` is singular.
+    pub fn try_new(ori: Matrix3<f64>, pos: Vector3<f64>) -> Result<Self> {
+        Ok(Self::from(
+            UnitCone::new().map(Linear::try_new(ori)?.chain(Shift::from(pos))),
+        ))
+    }
+    /// Creates cone with the base of radius `rad` centered at `base` and the apex at `apex`.
+    pub fn between(rad: f64, base: Vector3<f64>, apex: Vector3<f64>) -> Self {
+        let axis = apex - base;
+        Self::new(axis_ori(&axis, rad, 0.5 * axis.norm()), 0.5 * (base + apex))
+    }
+}
+impl From<ConeBase> for Cone {
+    fn from(base: ConeBase) -> Self {
+        Self(base)
+    }
+}
+
+impl Shape for Cone {}
+
+impl Instance<ShapeClass> for Cone {
+    fn source(cache: &mut HashSet<u64>) -> String {
+        ConeBase::source(cache)
+    }
+    fn inst_name() -> String {
+        ConeBase::inst_name()
+    }
+}
+
+impl Pack for Cone {
+    fn size_int() -> usize {
+        ConeBase::size_int()
+    }
+    fn size_float() -> usize {
+        ConeBase::size_float()
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        self.0.pack_to(buffer_int, buffer_float);
+    }
+}
+
+impl Bounded<Sphere> for Cone {
+    fn bound(&self) -> Option<Sphere> {
+        Some(cylinder_sphere(&self.0.map.first.0, self.0.map.second.0))
+    }
+}
+
+impl Bounded<Aabb> for Cone {
+    fn bound(&self) -> Option<Aabb> {
+        Some(cylinder_aabb(&self.0.map.first.0, self.0.map.second.0))
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{prelude::*, shape::*};
+use std::collections::HashSet;
+
+/// Distance to move the ray back to get out of the operand bounds,
+/// zero if the operand is unbounded.
+pub(crate) fn csg_probe<S: Bounded<Aabb>>(shape: &S) -> f64 {
+    match shape.bound() {
+        Some(b) if !b.is_empty() => 2.0 * b.half_size().norm(),
+        _ => 0.0,
+    }
+}
+
+fn csg_source<A: Shape, B: Shape>(cache: &mut HashSet<u64>, name: &str, op: &str) -> String {
+    if !cache.insert(inst_hash(name)) {
+        return String::new();
+    }
+    [
+        A::source(cache),
+        B::source(cache),
+        "#include <clay/shape/csg.h>".to_string(),
+        format!(
+            "CSG_FN_DEF({}, {}, {}, {}, {}, {})",
+            name,
+            op,
+            A::inst_name(),
+            B::inst_name(),
+            A::size_int(),
+            A::size_float(),
+        ),
+    ]
+    .join("\n")
+}
+
+fn csg_pack<A: Shape + Bounded<Aabb>, B: Shape + Bounded<Aabb>>(
+    first: &A,
+    second: &B,
+    buffer_int: &mut [i32],
+    buffer_float: &mut [f32],
+) {
+    Packer::new(buffer_int, buffer_float)
+        .pack(&csg_probe(first))
+        .pack(&csg_probe(second))
+        .pack(first)
+        .pack(second);
+}
+
+macro_rules! csg_shape {
+    ($Self:ident, $op:expr, $name:expr) => {
+        impl<A: Shape + Bounded<Aabb>, B: Shape + Bounded<Aabb>> $Self<A, B> {
+            pub fn new(first: A, second: B) -> Self {
+                Self { first, second }
+            }
+        }
+
+        impl<A: Shape + Bounded<Aabb>, B: Shape + Bounded<Aabb>> Shape for $Self<A, B> {}
+
+        impl<A: Shape + Bounded<Aabb>, B: Shape + Bounded<Aabb>> Instance<ShapeClass>
+            for $Self<A, B>
+        {
+            fn source(cache: &mut HashSet<u64>) -> String {
+                csg_source::<A, B>(cache, &Self::inst_name(), $op)
+            }
+            fn inst_name() -> String {
+                generic_inst_name($name, &[A::inst_name(), B::inst_name()])
+            }
+        }
+
+        impl<A: Shape + Bounded<Aabb>, B: Shape + Bounded<Aabb>> Pack for $Self<A, B> {
+            fn size_int() -> usize {
+                A::size_int() + B::size_int()
+            }
+            fn size_float() -> usize {
+                2 + A::size_float() + B::size_float()
+            }
+            fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+                csg_pack(&self.first, &self.second, buffer_int, buffer_float);
+            }
+        }
+
+        impl<A: Shape + Bounded<Aabb>, B: Shape + Bounded<Aabb>> Bounded<Sphere> for $Self<A, B> {
+            fn bound(&self) -> Option<Sphere> {
+                Bounded::<Aabb>::bound(self).map(|b| b.bounding_sphere())
+            }
+        }
+    };
+}
+
+/// Union of two shapes - the points that belong to any of them.
+///
+/// The operands are supposed to be solid, and each of them is taken into account
+/// only by its first interval along the ray, so they should be convex
+/// (or at least the ray should not re-enter them).
+pub struct Union<A: Shape + Bounded<Aabb>, B: Shape + Bounded<Aabb>> {
+    pub first: A,
+    pub second: B,
+}
+csg_shape!(Union, "CSG_UNION", "csg_union");
+
+impl<A: Shape + Bounded<Aabb>, B: Shape + Bounded<Aabb>> Bounded<Aabb> for Union<A, B> {
+    fn bound(&self) -> Option<Aabb> {
+        match (self.first.bound(), self.second.bound()) {
+            (Some(a), Some(b)) => Some(a.join(&b)),
+            _ => None,
+        }
+    }
+}
+
+/// Intersection of two shapes - the points that belong to both of them.
+///
+/// The same restrictions as for `Union` apply to the operands.
+pub struct Intersection<A: Shape + Bounded<Aabb>, B: Shape + Bounded<Aabb>> {
+    pub first: A,
+    pub second: B,
+}
+csg_shape!(Intersection, "CSG_INTERSECTION", "csg_intersection");
+
+impl<A: Shape + Bounded<Aabb>, B: Shape + Bounded<Aabb>> Bounded<Aabb> for Intersection<A, B> {
+    fn bound(&self) -> Option<Aabb> {
+        match (self.first.bound(), self.second.bound()) {
+            (Some(a), Some(b)) => Some(a.meet(&b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Difference of two shapes - the points of the first one that don't belong to the second one.
+///
+/// The same restrictions as for `Union` apply to the operands.
+pub struct Difference<A: Shape + Bounded<Aabb>, B: Shape + Bounded<Aabb>> {
+    pub first: A,
+    pub second: B,
+}
+csg_shape!(Difference, "CSG_DIFFERENCE", "csg_difference");
+
+impl<A: Shape + Bounded<Aabb>, B: Shape + Bounded<Aabb>> Bounded<Aabb> for Difference<A, B> {
+    fn bound(&self) -> Option<Aabb> {
+        self.first.bound()
+    }
+}
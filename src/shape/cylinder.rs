@@ -0,0 +1,85 @@
+use crate::{map::*, prelude::*, shape::*, Result};
+use nalgebra::{Matrix3, Vector3};
+use std::collections::HashSet;
+
+type CylinderBase = ShapeMapper<UnitCylinder, Affine>;
+/// Cylinder defined by affine transform on unit cylinder.
+pub struct Cylinder(pub CylinderBase);
+
+impl Cylinder {
+    /// Creates capped cylinder.
+    pub fn new(ori: Matrix3<f64>, pos: Vector3<f64>) -> Self {
+        Self::from(UnitCylinder::new().map(Linear::from(ori).chain(Shift::from(pos))))
+    }
+    /// Creates open cylinder.
+    pub fn new_open(ori: Matrix3<f64>, pos: Vector3<f64>) -> Self {
+        Self::from(UnitCylinder::open().map(Linear::from(ori).chain(Shift::from(pos))))
+    }
+    /// Creates capped cylinder failing if `ori` is singular.
+    pub fn try_new(ori: Matrix3<f64>, pos: Vector3<f64>) -> Result<Self> {
+        Ok(Self::from(
+            UnitCylinder::new().map(Linear::try_new(ori)?.chain(Shift::from(pos))),
+        ))
+    }
+    /// Creates open cylinder failing if `ori` is singular.
+    pub fn try_new_open(ori: Matrix3<f64>, pos: Vector3<f64>) -> Result<Self> {
+        Ok(Self::from(
+            UnitCylinder::open().map(Linear::try_new(ori)?.chain(Shift::from(pos))),
+        ))
+    }
+    /// Creates capped cylinder of radius `rad` with the axis from `a` to `b`.
+    pub fn between(rad: f64, a: Vector3<f64>, b: Vector3<f64>) -> Self {
+        Self::new(axis_ori(&(b - a), rad, 0.5 * (b - a).norm()), 0.5 * (a + b))
+    }
+}
+impl From<CylinderBase> for Cylinder {
+    fn from(base: CylinderBase) -> Self {
+        Self(base)
+    }
+}
+
+impl Shape for Cylinder {}
+
+impl Instance<ShapeClass> for Cylinder {
+    fn source(cache: &mut HashSet<u64>) -> String {
+        CylinderBase::source(cache)
+    }
+    fn inst_name() -> String {
+        CylinderBase::inst_name()
+    }
+}
+
+impl Pack for Cylinder {
+    fn size_int() -> usize {
+        CylinderBase::size_int()
+    }
+    fn size_float() -> usize {
+        CylinderBase::size_float()
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        self.0.pack_to(buffer_int, buffer_float);
+    }
+}
+
+/// Bounding sphere of the unit cylinder (or cone) transformed by `ori`.
+pub(crate) fn cylinder_sphere(ori: &Matrix3<f64>, pos: Vector3<f64>) -> Sphere {
+    Sphere::new(disk_radius(ori) + ori.column(2).norm(), pos)
+}
+
+/// Bounding box of the unit cylinder (or cone) transformed by `ori`.
+pub(crate) fn cylinder_aabb(ori: &Matrix3<f64>, pos: Vector3<f64>) -> Aabb {
+    let half_size = Vector3::from_iterator(ori.row_iter().map(|r| r[0].hypot(r[1]) + r[2].abs()));
+    Aabb::from_center(pos, half_size)
+}
+
+impl Bounded<Sphere> for Cylinder {
+    fn bound(&self) -> Option<Sphere> {
+        Some(cylinder_sphere(&self.0.map.first.0, self.0.map.second.0))
+    }
+}
+
+impl Bounded<Aabb> for Cylinder {
+    fn bound(&self) -> Option<Aabb> {
+        Some(cylinder_aabb(&self.0.map.first.0, self.0.map.second.0))
+    }
+}
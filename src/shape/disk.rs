@@ -0,0 +1,107 @@
+use crate::{map::*, prelude::*, shape::*, Result};
+use nalgebra::{Matrix3, Vector3};
+use std::collections::HashSet;
+
+/// Radius of the circumscribed circle of the unit disk transformed by `ori`.
+pub(crate) fn disk_radius(ori: &Matrix3<f64>) -> f64 {
+    // The largest singular value of the matrix made of the first two columns
+    let (a, b) = (ori.column(0), ori.column(1));
+    let (aa, bb, ab) = (a.dot(&a), b.dot(&b), a.dot(&b));
+    (0.5 * (aa + bb) + (0.25 * (aa - bb).powi(2) + ab * ab).sqrt()).sqrt()
+}
+
+type DiskBase = ShapeMapper<UnitDisk, Affine>;
+/// Disk (or ellipse in general) defined by affine transform on unit disk.
+///
+/// The disk could also be used as a target to make area light sources,
+/// the target points are sampled uniformly over its area.
+pub struct Disk(pub DiskBase);
+
+impl Disk {
+    /// Creates disk of radius `rad` centered at `pos` and orthogonal to the `norm` vector.
+    pub fn new(rad: f64, pos: Vector3<f64>, norm: Vector3<f64>) -> Self {
+        Self::from_affine(axis_ori(&norm, rad, 1.0), pos)
+    }
+    /// Creates disk from affine transform, the third column of `ori` must not lie in the disk plane.
+    pub fn from_affine(ori: Matrix3<f64>, pos: Vector3<f64>) -> Self {
+        Self::from(UnitDisk::new().map(Linear::from(ori).chain(Shift::from(pos))))
+    }
+    /// Creates disk failing if `rad` or `norm` is zero.
+    pub fn try_new(rad: f64, pos: Vector3<f64>, norm: Vector3<f64>) -> Result<Self> {
+        let ori = try_axis_ori(&norm, rad, 1.0)?;
+        Ok(Self::from(
+            UnitDisk::new().map(Linear::try_new(ori)?.chain(Shift::from(pos))),
+        ))
+    }
+}
+impl From<DiskBase> for Disk {
+    fn from(base: DiskBase) -> Self {
+        Self(base)
+    }
+}
+
+impl Shape for Disk {}
+
+impl Instance<ShapeClass> for Disk {
+    fn source(cache: &mut HashSet<u64>) -> String {
+        DiskBase::source(cache)
+    }
+    fn inst_name() -> String {
+        DiskBase::inst_name()
+    }
+}
+
+impl Pack for Disk {
+    fn size_int() -> usize {
+        DiskBase::size_int()
+    }
+    fn size_float() -> usize {
+        DiskBase::size_float()
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        self.0.pack_to(buffer_int, buffer_float);
+    }
+}
+
+impl Bounded<Aabb> for Disk {
+    fn bound(&self) -> Option<Aabb> {
+        let ori = self.0.map.first.0;
+        let half_size = Vector3::from_iterator(ori.row_iter().map(|r| r[0].hypot(r[1])));
+        Some(Aabb::from_center(self.0.map.second.0, half_size))
+    }
+}
+
+impl Bounded<Sphere> for Disk {
+    fn bound(&self) -> Option<Sphere> {
+        Some(Sphere::new(
+            disk_radius(&self.0.map.first.0),
+            self.0.map.second.0,
+        ))
+    }
+}
+
+impl Bounded<Disk> for Disk {
+    fn bound(&self) -> Option<Disk> {
+        Some(Disk::from_affine(self.0.map.first.0, self.0.map.second.0))
+    }
+}
+
+impl Bound for Disk {}
+impl Instance<BoundClass> for Disk {
+    fn source(cache: &mut HashSet<u64>) -> String {
+        UnitDisk::source(cache)
+    }
+    fn inst_name() -> String {
+        "disk".to_string()
+    }
+}
+
+impl Target for Disk {}
+impl Instance<TargetClass> for Disk {
+    fn source(cache: &mut HashSet<u64>) -> String {
+        UnitDisk::source(cache)
+    }
+    fn inst_name() -> String {
+        "disk_target".to_string()
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{map::*, prelude::*, shape::*};
+use crate::{map::*, prelude::*, shape::*, Result};
 use nalgebra::{linalg::SVD, Matrix3, Vector3};
 use std::collections::HashSet;
 
@@ -10,6 +10,12 @@ impl Ellipsoid {
     pub fn new(ori: Matrix3<f64>, pos: Vector3<f64>) -> Self {
         Self::from(UnitSphere::new().map(Linear::from(ori).chain(Shift::from(pos))))
     }
+    /// Creates ellipsoid failing if `ori` is singular.
+    pub fn try_new(ori: Matrix3<f64>, pos: Vector3<f64>) -> Result<Self> {
+        Ok(Self::from(
+            UnitSphere::new().map(Linear::try_new(ori)?.chain(Shift::from(pos))),
+        ))
+    }
 }
 impl From<EllipsoidBase> for Ellipsoid {
     fn from(base: EllipsoidBase) -> Self {
@@ -50,3 +56,43 @@ impl Bounded<Sphere> for Ellipsoid {
         Some(Sphere::new(rad, self.0.map.second.0))
     }
 }
+
+impl Bounded<Aabb> for Ellipsoid {
+    fn bound(&self) -> Option<Aabb> {
+        let ori = self.0.map.first.0;
+        let half_size = Vector3::from_iterator(ori.row_iter().map(|r| r.norm()));
+        Some(Aabb::from_center(self.0.map.second.0, half_size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aabb_bound() {
+        let ori = Matrix3::new(0.5, -1.0, 0.2, 2.0, 0.1, 1.0, 0.3, -3.0, 0.25);
+        let pos = Vector3::new(1.0, -2.0, 3.0);
+        let bound = Bounded::<Aabb>::bound(&Ellipsoid::new(ori, pos)).unwrap();
+
+        // The extent along each axis is reached at the point `ori * r / |r|`
+        // where `r` is the corresponding row of `ori`.
+        for (i, row) in ori.row_iter().enumerate() {
+            let p = ori * row.transpose().normalize() + pos;
+            assert!((p[i] - bound.max[i]).abs() < 1e-12);
+            assert!((2.0 * pos[i] - p[i] - bound.min[i]).abs() < 1e-12);
+        }
+        for i in 0..64 {
+            let (theta, phi) = (0.1 * i as f64, 0.37 * i as f64);
+            let u = Vector3::new(
+                theta.sin() * phi.cos(),
+                theta.sin() * phi.sin(),
+                theta.cos(),
+            );
+            let p = ori * u + pos;
+            for j in 0..3 {
+                assert!(bound.min[j] - 1e-12 <= p[j] && p[j] <= bound.max[j] + 1e-12);
+            }
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// Unique name of the generic instance built from `names`.
+pub(crate) fn generic_inst_name(prefix: &str, names: &[String]) -> String {
+    let mut hasher = DefaultHasher::new();
+    names.hash(&mut hasher);
+    format!("__{}_{:x}", prefix, hasher.finish())
+}
+
+/// Hash of the instance name to be stored in the source cache.
+pub(crate) fn inst_hash(name: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,298 @@
+use crate::{prelude::*, shape::*, Error, Result};
+use nalgebra::Vector3;
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+/// Maximal number of grid nodes along each side of the default `Heightfield`.
+pub const HEIGHTFIELD_MAX_SIZE: usize = 128;
+
+/// Heightfield - the solid between the square `[-1, 1] x [-1, 1]` in the plane `z = 0`
+/// and the surface bilinearly interpolated over the grid of heights.
+///
+/// Heights are stored by rows along `x` axis starting from `y = -1`.
+/// It could be moved and scaled to the terrain size
+/// by combining with the affine transform (*see `Shape::map()`*).
+///
+/// The grid has at most `N` nodes along each side. The size of packed shape is fixed,
+/// so the heights are stored in a buffer of `N^2` floats regardless of the grid size,
+/// and the same size is taken by every object of a select that contains the heightfield.
+#[derive(Clone, Debug)]
+pub struct SizedHeightfield<const N: usize> {
+    dims: (usize, usize),
+    heights: Vec<f64>,
+}
+
+/// Heightfield of the default size of `HEIGHTFIELD_MAX_SIZE` nodes along each side
+/// (it takes 64 KiB).
+pub type Heightfield = SizedHeightfield<HEIGHTFIELD_MAX_SIZE>;
+
+fn image_error(msg: &str) -> Error {
+    Error::from(format!("heightfield image error: {}", msg))
+}
+
+/// Reads the next whitespace-separated token of the PNM header skipping comments.
+fn pnm_token<'a>(data: &'a [u8], pos: &mut usize) -> Result<&'a str> {
+    loop {
+        match data.get(*pos) {
+            Some(b'#') => {
+                while *pos < data.len() && data[*pos] != b'\n' {
+                    *pos += 1;
+                }
+            }
+            Some(c) if c.is_ascii_whitespace() => *pos += 1,
+            Some(_) => break,
+            None => return Err(image_error("unexpected end of file")),
+        }
+    }
+    let start = *pos;
+    while *pos < data.len() && !data[*pos].is_ascii_whitespace() {
+        *pos += 1;
+    }
+    std::str::from_utf8(&data[start..*pos]).map_err(|_| image_error("bad header"))
+}
+
+fn pnm_number(data: &[u8], pos: &mut usize) -> Result<usize> {
+    pnm_token(data, pos)?
+        .parse::<usize>()
+        .map_err(|_| image_error("bad number"))
+}
+
+impl<const N: usize> SizedHeightfield<N> {
+    /// Creates heightfield from the grid of `dims.0` by `dims.1` non-negative heights.
+    pub fn new(dims: (usize, usize), heights: Vec<f64>) -> Result<Self> {
+        if dims.0 < 2 || dims.1 < 2 {
+            return Err(Error::from(
+                "heightfield grid must be at least 2x2".to_string(),
+            ));
+        }
+        if dims.0 > N || dims.1 > N {
+            return Err(Error::from(format!("heightfield grid exceeds {0}x{0}", N)));
+        }
+        if heights.len() != dims.0 * dims.1 {
+            return Err(Error::from(format!(
+                "heightfield grid {}x{} does not match {} heights",
+                dims.0,
+                dims.1,
+                heights.len(),
+            )));
+        }
+        if heights.iter().any(|h| !h.is_finite() || *h < 0.0) {
+            return Err(Error::from(
+                "heightfield heights must be finite and non-negative".to_string(),
+            ));
+        }
+        Ok(Self { dims, heights })
+    }
+
+    /// Creates heightfield by sampling the function `f(x, y)` over the grid nodes.
+    pub fn from_fn<F: Fn(f64, f64) -> f64>(dims: (usize, usize), f: F) -> Result<Self> {
+        let coord = |i: usize, n: usize| 2.0 * i as f64 / (n.max(2) - 1) as f64 - 1.0;
+        let heights = (0..dims.1)
+            .flat_map(|j| (0..dims.0).map(move |i| (i, j)))
+            .map(|(i, j)| f(coord(i, dims.0), coord(j, dims.1)))
+            .collect();
+        Self::new(dims, heights)
+    }
+
+    /// Loads heightfield from the grayscale PGM or PNG image
+    /// (the format is determined by the file extension).
+    ///
+    /// Brightness is mapped to the heights in `[0, 1]`, the top row of the image
+    /// becomes the `y = 1` edge. Images larger than `N` are resampled.
+    pub fn load_image<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let ext = path
+            .as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+        let reader = BufReader::new(File::open(&path)?);
+        match ext.as_deref() {
+            Some("pgm") => Self::read_pgm(reader),
+            Some("png") => Self::read_png(reader),
+            _ => Err(image_error("unknown format")),
+        }
+    }
+
+    /// Reads heightfield from the PGM image, both plain (`P2`) and binary (`P5`).
+    pub fn read_pgm<R: Read>(mut reader: R) -> Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let mut pos = 0;
+        let magic = pnm_token(&data, &mut pos)?;
+        if magic != "P2" && magic != "P5" {
+            return Err(image_error("not a PGM image"));
+        }
+        let binary = magic == "P5";
+        let (w, h) = (pnm_number(&data, &mut pos)?, pnm_number(&data, &mut pos)?);
+        let maxval = pnm_number(&data, &mut pos)?;
+        if maxval == 0 || maxval > 0xffff {
+            return Err(image_error("bad maximal value"));
+        }
+        let too_large = || image_error("image is too large");
+        let count = w.checked_mul(h).ok_or_else(too_large)?;
+
+        let values = if binary {
+            // Single whitespace character separates the header from the data
+            pos += 1;
+            let bytes = if maxval > 0xff { 2 } else { 1 };
+            let end = count
+                .checked_mul(bytes)
+                .and_then(|n| n.checked_add(pos))
+                .ok_or_else(too_large)?;
+            let raw = data
+                .get(pos..end)
+                .ok_or_else(|| image_error("unexpected end of file"))?;
+            raw.chunks(bytes)
+                .map(|c| c.iter().fold(0, |v, &b| (v << 8) | b as usize))
+                .collect::<Vec<_>>()
+        } else {
+            (0..count)
+                .map(|_| pnm_number(&data, &mut pos))
+                .collect::<Result<Vec<_>>>()?
+        };
+        Self::from_image(
+            (w, h),
+            values
+                .into_iter()
+                .map(|v| v as f64 / maxval as f64)
+                .collect(),
+        )
+    }
+
+    /// Reads heightfield from the PNG image.
+    ///
+    /// Color images are converted to grayscale by averaging the channels.
+    pub fn read_png<R: Read>(reader: R) -> Result<Self> {
+        let decode_error = |e: png::DecodingError| image_error(&e.to_string());
+        let (info, mut reader) = png::Decoder::new(reader)
+            .read_info()
+            .map_err(decode_error)?;
+        let mut data = vec![0; info.buffer_size()];
+        reader.next_frame(&mut data).map_err(decode_error)?;
+
+        let (w, h) = (info.width as usize, info.height as usize);
+        if w == 0 || h == 0 {
+            return Err(image_error("empty image"));
+        }
+        let channels = data.len() / (w * h);
+        // Alpha channel is ignored
+        let colors = if channels >= 3 { 3 } else { 1 };
+        Self::from_image(
+            (w, h),
+            data.chunks(channels)
+                .take(w * h)
+                .map(|px| {
+                    px[0..colors].iter().map(|&c| c as f64).sum::<f64>() / (255 * colors) as f64
+                })
+                .collect(),
+        )
+    }
+
+    /// Creates heightfield from image brightness stored from the top row to the bottom one.
+    fn from_image(dims: (usize, usize), values: Vec<f64>) -> Result<Self> {
+        if values.len() != dims.0 * dims.1 || dims.0 < 2 || dims.1 < 2 {
+            return Err(image_error("image must be at least 2x2"));
+        }
+        let mut heights = Vec::with_capacity(values.len());
+        for row in values.chunks(dims.0).rev() {
+            heights.extend_from_slice(row);
+        }
+        let field = Self { dims, heights };
+        let fit = |n: usize| n.min(N);
+        Self::new(
+            (fit(dims.0), fit(dims.1)),
+            field.resample((fit(dims.0), fit(dims.1))).heights,
+        )
+    }
+
+    /// Grid dimensions.
+    pub fn dims(&self) -> (usize, usize) {
+        self.dims
+    }
+    /// Heights stored by rows.
+    pub fn heights(&self) -> &[f64] {
+        &self.heights
+    }
+    /// Height at the grid node.
+    pub fn height(&self, i: usize, j: usize) -> f64 {
+        self.heights[j * self.dims.0 + i]
+    }
+    /// Maximal height.
+    pub fn max_height(&self) -> f64 {
+        self.heights.iter().fold(0.0, |a, &b| f64::max(a, b))
+    }
+
+    /// Height at the point of the grid coordinates `x` and `y` by bilinear interpolation.
+    fn interpolate(&self, x: f64, y: f64) -> f64 {
+        let (w, h) = self.dims;
+        let i = (x.floor().max(0.0) as usize).min(w - 2);
+        let j = (y.floor().max(0.0) as usize).min(h - 2);
+        let (s, r) = (x - i as f64, y - j as f64);
+        let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+        lerp(
+            lerp(self.height(i, j), self.height(i + 1, j), s),
+            lerp(self.height(i, j + 1), self.height(i + 1, j + 1), s),
+            r,
+        )
+    }
+
+    /// Creates heightfield over the grid of other dimensions by bilinear interpolation.
+    pub fn resample(&self, dims: (usize, usize)) -> Self {
+        let scale = |n: usize, m: usize| (n - 1) as f64 / (m.max(2) - 1) as f64;
+        let (sx, sy) = (scale(self.dims.0, dims.0), scale(self.dims.1, dims.1));
+        let heights = (0..dims.1)
+            .flat_map(|j| (0..dims.0).map(move |i| (i, j)))
+            .map(|(i, j)| self.interpolate(i as f64 * sx, j as f64 * sy))
+            .collect();
+        Self { dims, heights }
+    }
+}
+
+impl<const N: usize> Shape for SizedHeightfield<N> {}
+
+// The grid size is stored in the buffers, so heightfields of any size share the device code.
+impl<const N: usize> Instance<ShapeClass> for SizedHeightfield<N> {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/shape/heightfield.h>".to_string()
+    }
+    fn inst_name() -> String {
+        "heightfield".to_string()
+    }
+}
+
+impl<const N: usize> Pack for SizedHeightfield<N> {
+    fn size_int() -> usize {
+        2
+    }
+    fn size_float() -> usize {
+        1 + N * N
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        Packer::new(buffer_int, buffer_float)
+            .pack(&(self.dims.0 as i32))
+            .pack(&(self.dims.1 as i32))
+            .pack(&self.max_height());
+        for (dst, src) in buffer_float[1..].iter_mut().zip(self.heights.iter()) {
+            *dst = *src as f32;
+        }
+    }
+}
+
+impl<const N: usize> Bounded<Aabb> for SizedHeightfield<N> {
+    fn bound(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            Vector3::new(-1.0, -1.0, 0.0),
+            Vector3::new(1.0, 1.0, self.max_height()),
+        ))
+    }
+}
+
+impl<const N: usize> Bounded<Sphere> for SizedHeightfield<N> {
+    fn bound(&self) -> Option<Sphere> {
+        Bounded::<Aabb>::bound(self).map(|b| b.bounding_sphere())
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{prelude::*, shape::*, Error, Result};
+use nalgebra::{Matrix3, Vector3};
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+/// Triangular face of a mesh referencing its vertices and normals by index.
+#[derive(Clone, Debug)]
+pub struct Face {
+    pub vertices: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+}
+
+/// Maximal number of vertices (and of normals) of the default `TriangleMesh`.
+pub const MESH_MAX_VERTICES: usize = 256;
+/// Maximal number of triangular faces of the default `TriangleMesh`.
+pub const MESH_MAX_FACES: usize = 256;
+
+/// Vertices, normals and faces of a triangle mesh on the host.
+///
+/// The data could be of any size. It either produces separate `Triangle` shapes
+/// to be added to a scene (`BvhScene` is preferable for large meshes)
+/// or is packed into a single `SizedTriangleMesh` shape if it is small enough.
+#[derive(Clone, Debug, Default)]
+pub struct MeshData {
+    pub vertices: Vec<Vector3<f64>>,
+    pub normals: Vec<Vector3<f64>>,
+    pub faces: Vec<Face>,
+}
+
+fn obj_error(line: usize, msg: &str) -> Error {
+    Error::from(format!("OBJ parse error at line {}: {}", line, msg))
+}
+
+fn obj_vector<'a, I: Iterator<Item = &'a str>>(line: usize, words: I) -> Result<Vector3<f64>> {
+    let coords = words
+        .take(3)
+        .map(|w| w.parse::<f64>().map_err(|_| obj_error(line, "bad number")))
+        .collect::<Result<Vec<_>>>()?;
+    if coords.len() < 3 {
+        return Err(obj_error(line, "expected three coordinates"));
+    }
+    Ok(Vector3::from_column_slice(&coords))
+}
+
+/// Converts one-based (or negative relative) OBJ index to zero-based one.
+fn obj_index(line: usize, word: &str, count: usize) -> Result<usize> {
+    let index = word
+        .parse::<isize>()
+        .map_err(|_| obj_error(line, "bad index"))?;
+    let abs = if index < 0 {
+        count as isize + index
+    } else {
+        index - 1
+    };
+    if abs < 0 || abs as usize >= count {
+        return Err(obj_error(line, "index out of range"));
+    }
+    Ok(abs as usize)
+}
+
+impl MeshData {
+    /// Creates empty mesh.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads mesh from the Wavefront OBJ file.
+    pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::read_obj(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads mesh in the Wavefront OBJ format.
+    ///
+    /// Only vertices, normals and faces are taken into account,
+    /// polygonal faces are split into triangles. Vertex normals are used
+    /// for the face only if they are specified for all its vertices.
+    pub fn read_obj<R: BufRead>(reader: R) -> Result<Self> {
+        let mut mesh = Self::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let n = i + 1;
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("v") => mesh.vertices.push(obj_vector(n, words)?),
+                Some("vn") => mesh.normals.push(obj_vector(n, words)?.normalize()),
+                Some("f") => {
+                    let mut vertices = Vec::new();
+                    let mut normals = Vec::new();
+                    for word in words {
+                        let mut parts = word.split('/');
+                        let vi = parts.next().unwrap_or("");
+                        vertices.push(obj_index(n, vi, mesh.vertices.len())?);
+                        match parts.nth(1) {
+                            Some(ni) if !ni.is_empty() => {
+                                normals.push(obj_index(n, ni, mesh.normals.len())?)
+                            }
+                            _ => (),
+                        }
+                    }
+                    if vertices.len() < 3 {
+                        return Err(obj_error(n, "face has less than three vertices"));
+                    }
+                    let smooth = normals.len() == vertices.len();
+                    for j in 1..(vertices.len() - 1) {
+                        mesh.faces.push(Face {
+                            vertices: [vertices[0], vertices[j], vertices[j + 1]],
+                            normals: if smooth {
+                                Some([normals[0], normals[j], normals[j + 1]])
+                            } else {
+                                None
+                            },
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(mesh)
+    }
+
+    /// Applies affine transform to the mesh.
+    pub fn transform(&mut self, ori: &Matrix3<f64>, pos: &Vector3<f64>) {
+        for v in self.vertices.iter_mut() {
+            *v = ori * *v + pos;
+        }
+        // Cofactor matrix transforms normals and exists even for singular matrices
+        let (a, b, c) = (ori.column(0), ori.column(1), ori.column(2));
+        let cof = Matrix3::from_columns(&[b.cross(&c), c.cross(&a), a.cross(&b)]);
+        for n in self.normals.iter_mut() {
+            *n = (cof * *n).normalize();
+        }
+    }
+
+    /// Bounding box of the whole mesh.
+    pub fn bound(&self) -> Aabb {
+        self.vertices
+            .iter()
+            .fold(Aabb::empty(), |b, v| b.join_point(v))
+    }
+
+    /// Iterates over triangles of the mesh.
+    pub fn triangles(&self) -> impl Iterator<Item = Triangle> + '_ {
+        self.faces.iter().map(move |f| {
+            let vs = [
+                self.vertices[f.vertices[0]],
+                self.vertices[f.vertices[1]],
+                self.vertices[f.vertices[2]],
+            ];
+            match f.normals {
+                Some(ns) => Triangle::new_smooth(
+                    vs,
+                    [
+                        self.normals[ns[0]],
+                        self.normals[ns[1]],
+                        self.normals[ns[2]],
+                    ],
+                ),
+                None => Triangle::new(vs),
+            }
+        })
+    }
+}
+
+/// Triangle mesh shape that stores its vertices, normals and faces in the pack buffers.
+///
+/// The mesh holds up to `V` vertices (and as many normals) and up to `F` faces.
+///
+/// **Note:** the size of packed shape is fixed, so each mesh occupies the space
+/// for its full capacity regardless of its actual size, and the same size is taken by
+/// every object of a select that contains the mesh. Choose the capacity that fits
+/// the meshes of the scene. The faces are checked one by one,
+/// so for large meshes use `MeshData::triangles()` with `BvhScene` instead.
+#[derive(Clone, Debug)]
+pub struct SizedTriangleMesh<const V: usize, const F: usize> {
+    data: MeshData,
+}
+
+/// Triangle mesh of the default capacity of `MESH_MAX_VERTICES` vertices
+/// and `MESH_MAX_FACES` faces (it takes about 12 KiB).
+pub type TriangleMesh = SizedTriangleMesh<MESH_MAX_VERTICES, MESH_MAX_FACES>;
+
+impl<const V: usize, const F: usize> SizedTriangleMesh<V, F> {
+    /// Creates mesh shape, fails if the data exceeds the mesh capacity
+    /// or the faces reference missing vertices or normals.
+    pub fn new(data: MeshData) -> Result<Self> {
+        if data.vertices.len() > V || data.normals.len() > V || data.faces.len() > F {
+            return Err(Error::from(format!(
+                "mesh with {} vertices, {} normals and {} faces exceeds \
+                 {} vertices or normals and {} faces",
+                data.vertices.len(),
+                data.normals.len(),
+                data.faces.len(),
+                V,
+                F,
+            )));
+        }
+        let valid = data.faces.iter().all(|f| {
+            f.vertices.iter().all(|&i| i < data.vertices.len())
+                && f.normals.iter().flatten().all(|&i| i < data.normals.len())
+        });
+        if !valid {
+            return Err(Error::from(
+                "mesh face references missing vertex or normal".to_string(),
+            ));
+        }
+        Ok(Self { data })
+    }
+
+    /// Loads mesh shape from the Wavefront OBJ file.
+    pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(MeshData::load_obj(path)?)
+    }
+
+    pub fn data(&self) -> &MeshData {
+        &self.data
+    }
+    pub fn into_data(self) -> MeshData {
+        self.data
+    }
+}
+
+impl<const V: usize, const F: usize> Shape for SizedTriangleMesh<V, F> {}
+
+// The layout is stored in the buffers, so meshes of any capacity share the device code.
+impl<const V: usize, const F: usize> Instance<ShapeClass> for SizedTriangleMesh<V, F> {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/shape/mesh.h>".to_string()
+    }
+    fn inst_name() -> String {
+        "mesh".to_string()
+    }
+}
+
+impl<const V: usize, const F: usize> Pack for SizedTriangleMesh<V, F> {
+    fn size_int() -> usize {
+        2 + 6 * F
+    }
+    fn size_float() -> usize {
+        2 * 3 * V
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        let normals_offset = 3 * V;
+        buffer_int[0] = self.data.faces.len() as i32;
+        buffer_int[1] = normals_offset as i32;
+        for (face, dst) in self.data.faces.iter().zip(buffer_int[2..].chunks_mut(6)) {
+            let vertices = face.vertices.iter().map(|&i| i as i32);
+            let normals = match face.normals {
+                Some(ns) => [ns[0] as i32, ns[1] as i32, ns[2] as i32],
+                None => [-1; 3],
+            };
+            for (d, s) in dst.iter_mut().zip(vertices.chain(normals.iter().cloned())) {
+                *d = s;
+            }
+        }
+        for (v, dst) in self.data.vertices.iter().zip(buffer_float.chunks_mut(3)) {
+            dst.pack(v);
+        }
+        for (n, dst) in self
+            .data
+            .normals
+            .iter()
+            .zip(buffer_float[normals_offset..].chunks_mut(3))
+        {
+            dst.pack(n);
+        }
+    }
+}
+
+impl<const V: usize, const F: usize> Bounded<Aabb> for SizedTriangleMesh<V, F> {
+    fn bound(&self) -> Option<Aabb> {
+        Some(self.data.bound())
+    }
+}
+
+impl<const V: usize, const F: usize> Bounded<Sphere> for SizedTriangleMesh<V, F> {
+    fn bound(&self) -> Option<Sphere> {
+        Some(self.data.bound())
+            .filter(|b| !b.is_empty())
+            .map(|b| b.bounding_sphere())
+    }
+}
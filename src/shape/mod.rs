@@ -1,5 +1,11 @@
 pub use crate::core::shape::*;
 
+mod generic;
+pub(crate) use generic::*;
+
+mod aabb;
+pub use aabb::*;
+
 mod unit_sphere;
 pub use unit_sphere::*;
 mod sphere;
@@ -11,3 +17,44 @@ mod unit_cube;
 pub use unit_cube::*;
 mod parallelepiped;
 pub use parallelepiped::*;
+
+mod unit_plane;
+pub use unit_plane::*;
+mod plane;
+pub use plane::*;
+mod unit_disk;
+pub use unit_disk::*;
+mod disk;
+pub use disk::*;
+
+mod unit_cylinder;
+pub use unit_cylinder::*;
+mod cylinder;
+pub use cylinder::*;
+mod unit_cone;
+pub use unit_cone::*;
+mod cone;
+pub use cone::*;
+mod unit_capsule;
+pub use unit_capsule::*;
+mod capsule;
+pub use capsule::*;
+
+mod unit_torus;
+pub use unit_torus::*;
+mod torus;
+pub use torus::*;
+
+mod heightfield;
+pub use heightfield::*;
+
+mod triangle;
+pub use triangle::*;
+mod mesh;
+pub use mesh::*;
+
+mod csg;
+pub use csg::*;
+
+mod sdf;
+pub use sdf::*;
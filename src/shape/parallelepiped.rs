@@ -1,4 +1,4 @@
-use crate::{map::*, prelude::*, shape::*};
+use crate::{map::*, prelude::*, shape::*, Result};
 use nalgebra::{Matrix3, Vector3};
 use std::collections::HashSet;
 
@@ -10,6 +10,12 @@ impl Parallelepiped {
     pub fn new(ori: Matrix3<f64>, pos: Vector3<f64>) -> Self {
         Self::from(UnitCube::new().map(Linear::from(ori).chain(Shift::from(pos))))
     }
+    /// Creates parallelepiped failing if `ori` is singular.
+    pub fn try_new(ori: Matrix3<f64>, pos: Vector3<f64>) -> Result<Self> {
+        Ok(Self::from(
+            UnitCube::new().map(Linear::try_new(ori)?.chain(Shift::from(pos))),
+        ))
+    }
 }
 impl From<ParallelepipedBase> for Parallelepiped {
     fn from(base: ParallelepipedBase) -> Self {
@@ -42,14 +48,13 @@ impl Bounded<Sphere> for Parallelepiped {
     fn bound(&self) -> Option<Sphere> {
         let pos = self.0.map.second.0;
         let ori = self.0.map.first.0;
-        let basis = ori.transpose();
         let mut rad = 0.0;
         for i in 0..8 {
             let mut data = [0.0; 3];
             for j in 0..3 {
-                data[j] = 1.0 - 2.0 * (((i << j) & 1) as f64);
+                data[j] = 1.0 - 2.0 * (((i >> j) & 1) as f64);
             }
-            let len = (basis * Vector3::from_column_slice(&data)).norm();
+            let len = (ori * Vector3::from_column_slice(&data)).norm();
             if len > rad {
                 rad = len;
             }
@@ -57,3 +62,9 @@ impl Bounded<Sphere> for Parallelepiped {
         Some(Sphere::new(rad, pos))
     }
 }
+
+impl Bounded<Aabb> for Parallelepiped {
+    fn bound(&self) -> Option<Aabb> {
+        self.0.bound()
+    }
+}
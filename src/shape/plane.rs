@@ -0,0 +1,86 @@
+use crate::{map::*, prelude::*, shape::*, Error, Result};
+use nalgebra::{Matrix3, Vector3};
+use std::collections::HashSet;
+
+/// Matrix that maps `z` axis to the `axis` direction scaled by `len`
+/// and `x` and `y` axes to the orthogonal vectors of length `rad`.
+pub(crate) fn axis_ori(axis: &Vector3<f64>, rad: f64, len: f64) -> Matrix3<f64> {
+    let n = axis.normalize();
+    let a = if n.x.abs() < 0.5 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    let u = a.cross(&n).normalize();
+    let v = n.cross(&u);
+    Matrix3::from_columns(&[rad * u, rad * v, len * n])
+}
+
+/// Same as `axis_ori()` but fails if the `axis` is zero or not finite.
+pub(crate) fn try_axis_ori(axis: &Vector3<f64>, rad: f64, len: f64) -> Result<Matrix3<f64>> {
+    let norm = axis.norm();
+    if norm > 0.0 && norm.is_finite() {
+        Ok(axis_ori(axis, rad, len))
+    } else {
+        Err(Error::from(format!("invalid axis vector: {}", axis)))
+    }
+}
+
+type PlaneBase = ShapeMapper<UnitPlane, Affine>;
+/// Infinite plane defined by affine transform on unit plane.
+pub struct Plane(pub PlaneBase);
+
+impl Plane {
+    /// Creates plane passing through the `pos` point and orthogonal to the `norm` vector.
+    pub fn new(pos: Vector3<f64>, norm: Vector3<f64>) -> Self {
+        let ori = axis_ori(&norm, 1.0, 1.0);
+        Self::from(UnitPlane::new().map(Linear::from(ori).chain(Shift::from(pos))))
+    }
+    /// Creates plane failing if `norm` is zero.
+    pub fn try_new(pos: Vector3<f64>, norm: Vector3<f64>) -> Result<Self> {
+        let ori = try_axis_ori(&norm, 1.0, 1.0)?;
+        Ok(Self::from(
+            UnitPlane::new().map(Linear::try_new(ori)?.chain(Shift::from(pos))),
+        ))
+    }
+}
+impl From<PlaneBase> for Plane {
+    fn from(base: PlaneBase) -> Self {
+        Self(base)
+    }
+}
+
+impl Shape for Plane {}
+
+impl Instance<ShapeClass> for Plane {
+    fn source(cache: &mut HashSet<u64>) -> String {
+        PlaneBase::source(cache)
+    }
+    fn inst_name() -> String {
+        PlaneBase::inst_name()
+    }
+}
+
+impl Pack for Plane {
+    fn size_int() -> usize {
+        PlaneBase::size_int()
+    }
+    fn size_float() -> usize {
+        PlaneBase::size_float()
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        self.0.pack_to(buffer_int, buffer_float);
+    }
+}
+
+impl Bounded<Aabb> for Plane {
+    fn bound(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+impl Bounded<Sphere> for Plane {
+    fn bound(&self) -> Option<Sphere> {
+        None
+    }
+}
@@ -0,0 +1,20 @@
+use crate::{prelude::*, shape::*};
+
+/// Class of signed distance functions.
+///
+/// The only method is `dist` that returns the signed distance from the point
+/// to the surface (or its lower bound), see `SDF_DIST_ARGS_DEF` in `sdf.h`.
+pub enum SdfClass {}
+impl Class for SdfClass {
+    fn name() -> String {
+        "sdf".to_string()
+    }
+    fn methods() -> Vec<String> {
+        vec!["dist".to_string()]
+    }
+}
+
+/// Signed distance function that defines a shape.
+///
+/// The bounds are required to limit the sphere tracing.
+pub trait Sdf: Pack + Instance<SdfClass> + Bounded<Aabb> {}
@@ -0,0 +1,10 @@
+mod class;
+pub use class::*;
+
+mod primitives;
+pub use primitives::*;
+mod ops;
+pub use ops::*;
+
+mod shape;
+pub use shape::*;
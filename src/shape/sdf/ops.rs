@@ -0,0 +1,111 @@
+use crate::{prelude::*, shape::*};
+use nalgebra::Vector3;
+use std::collections::HashSet;
+
+fn ops_source<A: Sdf, B: Sdf>(cache: &mut HashSet<u64>, name: &str, def: &str) -> String {
+    if !cache.insert(inst_hash(name)) {
+        return String::new();
+    }
+    [
+        A::source(cache),
+        B::source(cache),
+        "#include <clay/shape/sdf/ops.h>".to_string(),
+        format!(
+            "{}({}, {}, {}, {}, {})",
+            def,
+            name,
+            A::inst_name(),
+            B::inst_name(),
+            A::size_int(),
+            A::size_float(),
+        ),
+    ]
+    .join("\n")
+}
+
+macro_rules! sdf_op {
+    ($Self:ident, $param:ident, $def:expr, $prefix:expr) => {
+        impl<A: Sdf, B: Sdf> $Self<A, B> {
+            pub fn new(first: A, second: B, $param: f64) -> Self {
+                Self {
+                    first,
+                    second,
+                    $param,
+                }
+            }
+        }
+
+        impl<A: Sdf, B: Sdf> Sdf for $Self<A, B> {}
+
+        impl<A: Sdf, B: Sdf> Instance<SdfClass> for $Self<A, B> {
+            fn source(cache: &mut HashSet<u64>) -> String {
+                ops_source::<A, B>(cache, &Self::inst_name(), $def)
+            }
+            fn inst_name() -> String {
+                generic_inst_name($prefix, &[A::inst_name(), B::inst_name()])
+            }
+        }
+
+        impl<A: Sdf, B: Sdf> Pack for $Self<A, B> {
+            fn size_int() -> usize {
+                A::size_int() + B::size_int()
+            }
+            fn size_float() -> usize {
+                1 + A::size_float() + B::size_float()
+            }
+            fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+                Packer::new(buffer_int, buffer_float)
+                    .pack(&self.$param)
+                    .pack(&self.first)
+                    .pack(&self.second);
+            }
+        }
+    };
+}
+
+fn join_bounds<A: Sdf, B: Sdf>(first: &A, second: &B) -> Option<Aabb> {
+    match (first.bound(), second.bound()) {
+        (Some(a), Some(b)) => Some(a.join(&b)),
+        _ => None,
+    }
+}
+
+/// Smooth union of two distance functions.
+///
+/// The surfaces are blended together within the distance of about `k`,
+/// zero `k` gives the ordinary union.
+pub struct SmoothUnion<A: Sdf, B: Sdf> {
+    pub first: A,
+    pub second: B,
+    pub k: f64,
+}
+sdf_op!(
+    SmoothUnion,
+    k,
+    "SDF_SMOOTH_UNION_FN_DEF",
+    "sdf_smooth_union"
+);
+
+impl<A: Sdf, B: Sdf> Bounded<Aabb> for SmoothUnion<A, B> {
+    fn bound(&self) -> Option<Aabb> {
+        join_bounds(&self.first, &self.second).map(|b| {
+            let margin = Vector3::repeat(0.25 * self.k);
+            Aabb::new(b.min - margin, b.max + margin)
+        })
+    }
+}
+
+/// Linear blend of two distance functions that morphs the first shape into the second one
+/// as `t` goes from zero to one.
+pub struct Blend<A: Sdf, B: Sdf> {
+    pub first: A,
+    pub second: B,
+    pub t: f64,
+}
+sdf_op!(Blend, t, "SDF_BLEND_FN_DEF", "sdf_blend");
+
+impl<A: Sdf, B: Sdf> Bounded<Aabb> for Blend<A, B> {
+    fn bound(&self) -> Option<Aabb> {
+        join_bounds(&self.first, &self.second)
+    }
+}
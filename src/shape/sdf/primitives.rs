@@ -0,0 +1,192 @@
+use crate::{prelude::*, shape::*};
+use nalgebra::Vector3;
+use std::collections::HashSet;
+
+const PRIMITIVES_SOURCE: &str = "#include <clay/shape/sdf/primitives.h>";
+
+/// Sphere distance function.
+#[derive(Clone, Debug)]
+pub struct SdfSphere {
+    pub pos: Vector3<f64>,
+    pub rad: f64,
+}
+
+impl SdfSphere {
+    pub fn new(rad: f64, pos: Vector3<f64>) -> Self {
+        Self { pos, rad }
+    }
+}
+
+impl Sdf for SdfSphere {}
+
+impl Instance<SdfClass> for SdfSphere {
+    fn source(_: &mut HashSet<u64>) -> String {
+        PRIMITIVES_SOURCE.to_string()
+    }
+    fn inst_name() -> String {
+        "sdf_sphere".to_string()
+    }
+}
+
+impl Pack for SdfSphere {
+    fn size_int() -> usize {
+        0
+    }
+    fn size_float() -> usize {
+        4
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        Packer::new(buffer_int, buffer_float)
+            .pack(&self.pos)
+            .pack(&self.rad);
+    }
+}
+
+impl Bounded<Aabb> for SdfSphere {
+    fn bound(&self) -> Option<Aabb> {
+        Some(Aabb::from_center(self.pos, Vector3::repeat(self.rad)))
+    }
+}
+
+/// Axis-aligned box distance function.
+#[derive(Clone, Debug)]
+pub struct SdfBox {
+    pub pos: Vector3<f64>,
+    pub half_size: Vector3<f64>,
+}
+
+impl SdfBox {
+    pub fn new(half_size: Vector3<f64>, pos: Vector3<f64>) -> Self {
+        Self { pos, half_size }
+    }
+}
+
+impl Sdf for SdfBox {}
+
+impl Instance<SdfClass> for SdfBox {
+    fn source(_: &mut HashSet<u64>) -> String {
+        PRIMITIVES_SOURCE.to_string()
+    }
+    fn inst_name() -> String {
+        "sdf_box".to_string()
+    }
+}
+
+impl Pack for SdfBox {
+    fn size_int() -> usize {
+        0
+    }
+    fn size_float() -> usize {
+        6
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        Packer::new(buffer_int, buffer_float)
+            .pack(&self.pos)
+            .pack(&self.half_size);
+    }
+}
+
+impl Bounded<Aabb> for SdfBox {
+    fn bound(&self) -> Option<Aabb> {
+        Some(Aabb::from_center(self.pos, self.half_size))
+    }
+}
+
+/// Torus distance function, the axis of the torus is `z`.
+#[derive(Clone, Debug)]
+pub struct SdfTorus {
+    pub pos: Vector3<f64>,
+    pub major: f64,
+    pub minor: f64,
+}
+
+impl SdfTorus {
+    pub fn new(major: f64, minor: f64, pos: Vector3<f64>) -> Self {
+        Self { pos, major, minor }
+    }
+}
+
+impl Sdf for SdfTorus {}
+
+impl Instance<SdfClass> for SdfTorus {
+    fn source(_: &mut HashSet<u64>) -> String {
+        PRIMITIVES_SOURCE.to_string()
+    }
+    fn inst_name() -> String {
+        "sdf_torus".to_string()
+    }
+}
+
+impl Pack for SdfTorus {
+    fn size_int() -> usize {
+        0
+    }
+    fn size_float() -> usize {
+        5
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        Packer::new(buffer_int, buffer_float)
+            .pack(&self.pos)
+            .pack(&self.major)
+            .pack(&self.minor);
+    }
+}
+
+impl Bounded<Aabb> for SdfTorus {
+    fn bound(&self) -> Option<Aabb> {
+        let r = self.major + self.minor;
+        Some(Aabb::from_center(self.pos, Vector3::new(r, r, self.minor)))
+    }
+}
+
+/// Distance estimator of the Mandelbulb fractal centered at the origin.
+///
+/// The fractal lies inside of the sphere of radius two.
+#[derive(Clone, Debug)]
+pub struct Mandelbulb {
+    pub power: f64,
+    pub iterations: usize,
+}
+
+impl Mandelbulb {
+    pub fn new(power: f64, iterations: usize) -> Self {
+        Self { power, iterations }
+    }
+}
+
+impl Default for Mandelbulb {
+    fn default() -> Self {
+        Self::new(8.0, 8)
+    }
+}
+
+impl Sdf for Mandelbulb {}
+
+impl Instance<SdfClass> for Mandelbulb {
+    fn source(_: &mut HashSet<u64>) -> String {
+        PRIMITIVES_SOURCE.to_string()
+    }
+    fn inst_name() -> String {
+        "sdf_mandelbulb".to_string()
+    }
+}
+
+impl Pack for Mandelbulb {
+    fn size_int() -> usize {
+        1
+    }
+    fn size_float() -> usize {
+        1
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        Packer::new(buffer_int, buffer_float)
+            .pack(&(self.iterations as i32))
+            .pack(&self.power);
+    }
+}
+
+impl Bounded<Aabb> for Mandelbulb {
+    fn bound(&self) -> Option<Aabb> {
+        Some(Aabb::from_center(Vector3::zeros(), Vector3::repeat(2.0)))
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{prelude::*, shape::*};
+use nalgebra::Vector3;
+use std::collections::HashSet;
+
+/// Shape defined by the signed distance function and intersected by sphere tracing.
+///
+/// The ray is marched at most `max_steps` times and the surface is considered hit
+/// when the distance to it is less than `eps`.
+pub struct SdfShape<D: Sdf> {
+    pub sdf: D,
+    pub max_steps: usize,
+    pub eps: f64,
+}
+
+impl<D: Sdf> SdfShape<D> {
+    pub fn new(sdf: D) -> Self {
+        Self {
+            sdf,
+            max_steps: 128,
+            eps: 1e-4,
+        }
+    }
+
+    /// Bounding sphere of the distance function used to limit the tracing.
+    fn bounding_sphere(&self) -> Option<(Vector3<f64>, f64)> {
+        self.sdf
+            .bound()
+            .filter(|b| !b.is_empty())
+            .map(|b| (b.center(), b.half_size().norm()))
+    }
+}
+
+impl<D: Sdf> Shape for SdfShape<D> {}
+
+impl<D: Sdf> Instance<ShapeClass> for SdfShape<D> {
+    fn source(cache: &mut HashSet<u64>) -> String {
+        let name = Self::inst_name();
+        if !cache.insert(inst_hash(&name)) {
+            return String::new();
+        }
+        [
+            D::source(cache),
+            "#include <clay/shape/sdf/sdf.h>".to_string(),
+            format!("SDF_SHAPE_FN_DEF({}, {})", name, D::inst_name()),
+        ]
+        .join("\n")
+    }
+    fn inst_name() -> String {
+        generic_inst_name("sdf_shape", &[D::inst_name()])
+    }
+}
+
+impl<D: Sdf> Pack for SdfShape<D> {
+    fn size_int() -> usize {
+        1 + D::size_int()
+    }
+    fn size_float() -> usize {
+        5 + D::size_float()
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        // Unbounded distance function is not traced at all
+        let (center, rad) = self
+            .bounding_sphere()
+            .unwrap_or_else(|| (Vector3::zeros(), 0.0));
+        Packer::new(buffer_int, buffer_float)
+            .pack(&(self.max_steps as i32))
+            .pack(&self.eps)
+            .pack(&center)
+            .pack(&rad)
+            .pack(&self.sdf);
+    }
+}
+
+impl<D: Sdf> Bounded<Aabb> for SdfShape<D> {
+    fn bound(&self) -> Option<Aabb> {
+        self.sdf.bound()
+    }
+}
+
+impl<D: Sdf> Bounded<Sphere> for SdfShape<D> {
+    fn bound(&self) -> Option<Sphere> {
+        self.bounding_sphere()
+            .map(|(center, rad)| Sphere::new(rad, center))
+    }
+}
@@ -40,6 +40,19 @@ impl Pack for Sphere {
     }
 }
 
+impl Bounded<Sphere> for Sphere {
+    fn bound(&self) -> Option<Sphere> {
+        Some(Sphere::new(self.0.map.first.0, self.0.map.second.0))
+    }
+}
+
+impl Bounded<Aabb> for Sphere {
+    fn bound(&self) -> Option<Aabb> {
+        let (rad, pos) = (self.0.map.first.0, self.0.map.second.0);
+        Some(Aabb::from_center(pos, Vector3::repeat(rad)))
+    }
+}
+
 impl Bound for Sphere {}
 impl Instance<BoundClass> for Sphere {
     fn source(cache: &mut HashSet<u64>) -> String {
@@ -0,0 +1,82 @@
+use crate::{map::*, prelude::*, shape::*, Error, Result};
+use nalgebra::{linalg::SVD, Matrix3, Vector3};
+use std::collections::HashSet;
+
+type TorusBase = ShapeMapper<UnitTorus, Affine>;
+/// Torus defined by affine transform on unit torus.
+pub struct Torus(pub TorusBase);
+
+impl Torus {
+    /// Creates torus from unit torus with the tube radius `minor` transformed by `ori` and `pos`.
+    pub fn new(minor: f64, ori: Matrix3<f64>, pos: Vector3<f64>) -> Self {
+        Self::from(UnitTorus::new(minor).map(Linear::from(ori).chain(Shift::from(pos))))
+    }
+    /// Creates torus failing if `ori` is singular or `minor` is not positive.
+    pub fn try_new(minor: f64, ori: Matrix3<f64>, pos: Vector3<f64>) -> Result<Self> {
+        if !(minor > 0.0 && minor.is_finite()) {
+            return Err(Error::from(format!(
+                "invalid tube radius of torus: {}",
+                minor
+            )));
+        }
+        Ok(Self::from(
+            UnitTorus::new(minor).map(Linear::try_new(ori)?.chain(Shift::from(pos))),
+        ))
+    }
+    /// Creates round torus centered at `pos` around the `axis` with given radii.
+    pub fn with_radii(major: f64, minor: f64, pos: Vector3<f64>, axis: Vector3<f64>) -> Self {
+        Self::new(minor / major, axis_ori(&axis, major, major), pos)
+    }
+}
+impl From<TorusBase> for Torus {
+    fn from(base: TorusBase) -> Self {
+        Self(base)
+    }
+}
+
+impl Shape for Torus {}
+
+impl Instance<ShapeClass> for Torus {
+    fn source(cache: &mut HashSet<u64>) -> String {
+        TorusBase::source(cache)
+    }
+    fn inst_name() -> String {
+        TorusBase::inst_name()
+    }
+}
+
+impl Pack for Torus {
+    fn size_int() -> usize {
+        TorusBase::size_int()
+    }
+    fn size_float() -> usize {
+        TorusBase::size_float()
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        self.0.pack_to(buffer_int, buffer_float);
+    }
+}
+
+impl Bounded<Sphere> for Torus {
+    fn bound(&self) -> Option<Sphere> {
+        let rad = SVD::new(self.0.map.first.0, false, false)
+            .singular_values
+            .as_slice()
+            .iter()
+            .fold(std::f64::NAN, |a, b| f64::max(a, *b));
+        Some(Sphere::new(
+            rad * (1.0 + self.0.shape.minor),
+            self.0.map.second.0,
+        ))
+    }
+}
+
+impl Bounded<Aabb> for Torus {
+    fn bound(&self) -> Option<Aabb> {
+        let ori = self.0.map.first.0;
+        let minor = self.0.shape.minor;
+        let half_size =
+            Vector3::from_iterator(ori.row_iter().map(|r| r[0].hypot(r[1]) + minor * r.norm()));
+        Some(Aabb::from_center(self.0.map.second.0, half_size))
+    }
+}
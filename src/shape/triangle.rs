@@ -0,0 +1,83 @@
+use crate::{prelude::*, shape::*};
+use nalgebra::Vector3;
+use std::collections::HashSet;
+
+/// Single two-sided triangle.
+///
+/// If vertex normals are provided then the normal is smoothly interpolated
+/// over the triangle, otherwise the flat normal of the triangle is used.
+#[derive(Clone, Debug)]
+pub struct Triangle {
+    pub vertices: [Vector3<f64>; 3],
+    pub normals: Option<[Vector3<f64>; 3]>,
+}
+
+impl Triangle {
+    /// Creates flat triangle.
+    pub fn new(vertices: [Vector3<f64>; 3]) -> Self {
+        Self {
+            vertices,
+            normals: None,
+        }
+    }
+    /// Creates triangle with smooth normals.
+    pub fn new_smooth(vertices: [Vector3<f64>; 3], normals: [Vector3<f64>; 3]) -> Self {
+        Self {
+            vertices,
+            normals: Some(normals),
+        }
+    }
+}
+
+impl Shape for Triangle {}
+
+impl Instance<ShapeClass> for Triangle {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/shape/triangle.h>".to_string()
+    }
+    fn inst_name() -> String {
+        "triangle".to_string()
+    }
+}
+
+impl Pack for Triangle {
+    fn size_int() -> usize {
+        1
+    }
+    fn size_float() -> usize {
+        6 * Vector3::<f64>::size_float()
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        let normals = self.normals.unwrap_or([Vector3::zeros(); 3]);
+        let vs = &self.vertices;
+        Packer::new(buffer_int, buffer_float)
+            .pack(&(self.normals.is_some() as i32))
+            .pack(&vs[0])
+            .pack(&vs[1])
+            .pack(&vs[2])
+            .pack(&normals[0])
+            .pack(&normals[1])
+            .pack(&normals[2]);
+    }
+}
+
+impl Bounded<Aabb> for Triangle {
+    fn bound(&self) -> Option<Aabb> {
+        Some(
+            self.vertices
+                .iter()
+                .fold(Aabb::empty(), |b, v| b.join_point(v)),
+        )
+    }
+}
+
+impl Bounded<Sphere> for Triangle {
+    fn bound(&self) -> Option<Sphere> {
+        let center = (self.vertices[0] + self.vertices[1] + self.vertices[2]) / 3.0;
+        let rad = self
+            .vertices
+            .iter()
+            .fold(0.0, |r, v| f64::max(r, (v - center).norm()));
+        Some(Sphere::new(rad, center))
+    }
+}
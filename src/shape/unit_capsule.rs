@@ -0,0 +1,52 @@
+use crate::{prelude::*, shape::*};
+use nalgebra::Vector3;
+use std::collections::HashSet;
+
+/// Unit capsule - cylinder of radius one along `z` axis with hemispherical ends,
+/// the centers of the hemispheres are `(0, 0, -half_len)` and `(0, 0, half_len)`.
+///
+/// It could be moved and deformed by combining with the affine transform
+/// (*see `Shape::map()`* and `Capsule`).
+#[derive(Clone, Debug, Default)]
+pub struct UnitCapsule {
+    pub half_len: f64,
+}
+
+impl UnitCapsule {
+    /// Creates new unit capsule with cylindrical part of length `2*half_len`
+    pub fn new(half_len: f64) -> Self {
+        Self { half_len }
+    }
+}
+
+impl Shape for UnitCapsule {}
+
+impl Instance<ShapeClass> for UnitCapsule {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/shape/capsule.h>".to_string()
+    }
+    fn inst_name() -> String {
+        "unit_capsule".to_string()
+    }
+}
+
+impl Pack for UnitCapsule {
+    fn size_int() -> usize {
+        0
+    }
+    fn size_float() -> usize {
+        1
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        Packer::new(buffer_int, buffer_float).pack(&self.half_len);
+    }
+}
+
+impl Bounded<Aabb> for UnitCapsule {
+    fn bound(&self) -> Option<Aabb> {
+        Some(Aabb::from_center(
+            Vector3::zeros(),
+            Vector3::new(1.0, 1.0, 1.0 + self.half_len),
+        ))
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{prelude::*, shape::*};
+use std::collections::HashSet;
+
+/// Unit cone - with the apex at `(0, 0, 1)` and the base of radius one at `z = -1`.
+///
+/// The cone is a solid with capped base.
+/// It could be transformed to an arbitrary elliptic cone
+/// by combining with the affine transform (*see `Shape::map()`* and `Cone`).
+#[derive(Clone, Debug, Default)]
+pub struct UnitCone {}
+
+impl UnitCone {
+    /// Creates new unit cone
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Shape for UnitCone {}
+
+impl Instance<ShapeClass> for UnitCone {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/shape/cone.h>".to_string()
+    }
+    fn inst_name() -> String {
+        "unit_cone".to_string()
+    }
+}
+
+impl Pack for UnitCone {
+    fn size_int() -> usize {
+        0
+    }
+    fn size_float() -> usize {
+        0
+    }
+    fn pack_to(&self, _buffer_int: &mut [i32], _buffer_float: &mut [f32]) {}
+}
+
+impl Bounded<Aabb> for UnitCone {
+    fn bound(&self) -> Option<Aabb> {
+        Some(Aabb::unit())
+    }
+}
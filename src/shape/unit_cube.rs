@@ -35,3 +35,9 @@ impl Pack for UnitCube {
     }
     fn pack_to(&self, _buffer_int: &mut [i32], _buffer_float: &mut [f32]) {}
 }
+
+impl Bounded<Aabb> for UnitCube {
+    fn bound(&self) -> Option<Aabb> {
+        Some(Aabb::unit())
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{prelude::*, shape::*};
+use std::collections::HashSet;
+
+/// Unit cylinder - of radius one and height two, centered at the origin along `z` axis.
+///
+/// The capped cylinder is a solid, while the open one is a two-sided tube surface.
+/// It could be transformed to an arbitrary elliptic cylinder
+/// by combining with the affine transform (*see `Shape::map()`* and `Cylinder`).
+#[derive(Clone, Debug)]
+pub struct UnitCylinder {
+    pub capped: bool,
+}
+
+impl UnitCylinder {
+    /// Creates new capped unit cylinder
+    pub fn new() -> Self {
+        Self { capped: true }
+    }
+    /// Creates new open unit cylinder
+    pub fn open() -> Self {
+        Self { capped: false }
+    }
+}
+
+impl Default for UnitCylinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shape for UnitCylinder {}
+
+impl Instance<ShapeClass> for UnitCylinder {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/shape/cylinder.h>".to_string()
+    }
+    fn inst_name() -> String {
+        "unit_cylinder".to_string()
+    }
+}
+
+impl Pack for UnitCylinder {
+    fn size_int() -> usize {
+        1
+    }
+    fn size_float() -> usize {
+        0
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        Packer::new(buffer_int, buffer_float).pack(&(self.capped as i32));
+    }
+}
+
+impl Bounded<Aabb> for UnitCylinder {
+    fn bound(&self) -> Option<Aabb> {
+        Some(Aabb::unit())
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{prelude::*, shape::*};
+use nalgebra::Vector3;
+use std::collections::HashSet;
+
+/// Unit disk - of radius one, centered at the origin and lying in the plane `z = 0`.
+///
+/// The disk is two-sided. It could be transformed to an arbitrary ellipse
+/// by combining with the affine transform (*see `Shape::map()`* and `Disk`).
+#[derive(Clone, Debug, Default)]
+pub struct UnitDisk {}
+
+impl UnitDisk {
+    /// Creates new unit disk
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Shape for UnitDisk {}
+
+impl Instance<ShapeClass> for UnitDisk {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/shape/disk.h>".to_string()
+    }
+    fn inst_name() -> String {
+        "unit_disk".to_string()
+    }
+}
+
+impl Pack for UnitDisk {
+    fn size_int() -> usize {
+        0
+    }
+    fn size_float() -> usize {
+        0
+    }
+    fn pack_to(&self, _buffer_int: &mut [i32], _buffer_float: &mut [f32]) {}
+}
+
+impl Bounded<Aabb> for UnitDisk {
+    fn bound(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            Vector3::new(-1.0, -1.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+        ))
+    }
+}
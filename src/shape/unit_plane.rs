@@ -0,0 +1,50 @@
+use crate::{prelude::*, shape::*};
+use std::collections::HashSet;
+
+/// Unit plane - the plane `z = 0` with normal along `z` axis.
+///
+/// The plane is two-sided and infinite, so it has no bounds.
+/// It could be moved and rotated by combining with the affine transform
+/// (*see `Shape::map()`* and `Plane`).
+#[derive(Clone, Debug, Default)]
+pub struct UnitPlane {}
+
+impl UnitPlane {
+    /// Creates new unit plane
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Shape for UnitPlane {}
+
+impl Instance<ShapeClass> for UnitPlane {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/shape/plane.h>".to_string()
+    }
+    fn inst_name() -> String {
+        "unit_plane".to_string()
+    }
+}
+
+impl Pack for UnitPlane {
+    fn size_int() -> usize {
+        0
+    }
+    fn size_float() -> usize {
+        0
+    }
+    fn pack_to(&self, _buffer_int: &mut [i32], _buffer_float: &mut [f32]) {}
+}
+
+impl Bounded<Aabb> for UnitPlane {
+    fn bound(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+impl Bounded<Sphere> for UnitPlane {
+    fn bound(&self) -> Option<Sphere> {
+        None
+    }
+}
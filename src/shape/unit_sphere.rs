@@ -38,3 +38,9 @@ impl Pack for UnitSphere {
     }
     fn pack_to(&self, _buffer_int: &mut [i32], _buffer_float: &mut [f32]) {}
 }
+
+impl Bounded<Aabb> for UnitSphere {
+    fn bound(&self) -> Option<Aabb> {
+        Some(Aabb::unit())
+    }
+}
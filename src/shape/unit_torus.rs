@@ -0,0 +1,53 @@
+use crate::{prelude::*, shape::*};
+use nalgebra::Vector3;
+use std::collections::HashSet;
+
+/// Unit torus - centered at the origin with `z` axis, major radius one
+/// and the given `minor` radius of the tube.
+///
+/// It could be moved and deformed by combining with the affine transform
+/// (*see `Shape::map()`* and `Torus`).
+#[derive(Clone, Debug)]
+pub struct UnitTorus {
+    pub minor: f64,
+}
+
+impl UnitTorus {
+    /// Creates new unit torus with the tube radius `minor`
+    pub fn new(minor: f64) -> Self {
+        Self { minor }
+    }
+}
+
+impl Shape for UnitTorus {}
+
+impl Instance<ShapeClass> for UnitTorus {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/shape/torus.h>".to_string()
+    }
+    fn inst_name() -> String {
+        "unit_torus".to_string()
+    }
+}
+
+impl Pack for UnitTorus {
+    fn size_int() -> usize {
+        0
+    }
+    fn size_float() -> usize {
+        1
+    }
+    fn pack_to(&self, buffer_int: &mut [i32], buffer_float: &mut [f32]) {
+        Packer::new(buffer_int, buffer_float).pack(&self.minor);
+    }
+}
+
+impl Bounded<Aabb> for UnitTorus {
+    fn bound(&self) -> Option<Aabb> {
+        let r = self.minor;
+        Some(Aabb::from_center(
+            Vector3::zeros(),
+            Vector3::new(1.0 + r, 1.0 + r, r),
+        ))
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{
+    prelude::*,
+    view::{frame_args, shutter_args_def, shutter_args_set, Shutter, View, SHUTTER_ARGS_COUNT},
+    Context,
+};
+use nalgebra::{Rotation3, Vector3};
+use ocl::{self, builders::KernelBuilder, prm};
+use std::collections::HashSet;
+
+/// Mapping of the angle from the view direction to the distance from the image center.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FisheyeMapping {
+    /// Distance is proportional to the angle.
+    #[default]
+    Equidistant = 0,
+    /// Equal areas of the image cover equal solid angles.
+    Equisolid = 1,
+}
+
+/// Fisheye view.
+///
+/// The circle inscribed into the image height covers the `field` angle,
+/// the image outside of the circle is black.
+#[derive(Debug, Clone)]
+pub struct FisheyeView {
+    /// Position of the point of view.
+    pub pos: Vector3<f64>,
+    /// Orientation.
+    pub ori: Rotation3<f64>,
+    /// Full field angle of the view in radians.
+    pub field: f64,
+    pub mapping: FisheyeMapping,
+    /// Motion of the view during the frame, the view is static if `None`.
+    pub shutter: Option<Shutter>,
+}
+
+impl FisheyeView {
+    /// Creates equidistant fisheye view with the field angle of 180 degrees.
+    pub fn new(pos: Vector3<f64>, ori: Rotation3<f64>) -> Self {
+        Self {
+            pos,
+            ori,
+            field: std::f64::consts::PI,
+            mapping: FisheyeMapping::default(),
+            shutter: None,
+        }
+    }
+
+    pub fn update(&mut self, pos: Vector3<f64>, ori: Rotation3<f64>) {
+        self.pos = pos;
+        self.ori = ori;
+    }
+}
+
+impl View for FisheyeView {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/view/fisheye_view.h>\n".to_string()
+    }
+}
+
+impl Store for FisheyeView {
+    type Data = Self;
+    fn create_data(&self, _context: &Context) -> clay_core::Result<Self::Data> {
+        Ok(self.clone())
+    }
+    fn update_data(&self, _context: &Context, data: &mut Self::Data) -> clay_core::Result<()> {
+        *data = self.clone();
+        Ok(())
+    }
+}
+
+impl Push for FisheyeView {
+    fn args_count() -> usize {
+        4 + SHUTTER_ARGS_COUNT
+    }
+    fn args_def(kb: &mut KernelBuilder) {
+        kb.arg(prm::Float3::zero())
+            .arg(prm::Float16::zero())
+            .arg(0.0f32)
+            .arg(0i32);
+        shutter_args_def(kb);
+    }
+    fn args_set(&mut self, i: usize, k: &mut ocl::Kernel) -> crate::Result<()> {
+        let (pos, map) = frame_args(&self.pos, &self.ori);
+        k.set_arg(i, &pos)?;
+        k.set_arg(i + 1, &map)?;
+        k.set_arg(i + 2, &(self.field as f32))?;
+        k.set_arg(i + 3, &(self.mapping as i32))?;
+        shutter_args_set(self.shutter.as_ref(), &self.pos, &self.ori, i + 4, k)?;
+
+        Ok(())
+    }
+}
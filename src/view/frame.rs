@@ -0,0 +1,18 @@
+use nalgebra::{Rotation3, Vector3};
+use ocl::prm;
+
+/// Converts view position and orientation to kernel arguments,
+/// the axes of the view are stored as `s012`, `s456` and `s89a` components of the map.
+pub(crate) fn frame_args(pos: &Vector3<f64>, ori: &Rotation3<f64>) -> (prm::Float3, prm::Float16) {
+    let mapf = ori.matrix().map(|x| x as f32);
+    let mut map16 = [0f32; 16];
+    map16[0..3].copy_from_slice(&mapf.as_slice()[0..3]);
+    map16[4..7].copy_from_slice(&mapf.as_slice()[3..6]);
+    map16[8..11].copy_from_slice(&mapf.as_slice()[6..9]);
+
+    let posf = pos.map(|x| x as f32);
+    let mut pos3 = [0f32; 3];
+    pos3.copy_from_slice(posf.as_slice());
+
+    (prm::Float3::from(pos3), prm::Float16::from(map16))
+}
@@ -1,4 +1,17 @@
 pub use crate::core::view::*;
 
+mod frame;
+pub(crate) use frame::*;
+mod shutter;
+pub use shutter::*;
+
 mod projection;
 pub use projection::*;
+mod orthographic;
+pub use orthographic::*;
+mod panoramic;
+pub use panoramic::*;
+mod fisheye;
+pub use fisheye::*;
+mod stereo;
+pub use stereo::*;
@@ -0,0 +1,76 @@
+use crate::{
+    prelude::*,
+    view::{frame_args, shutter_args_def, shutter_args_set, Shutter, View, SHUTTER_ARGS_COUNT},
+    Context,
+};
+use nalgebra::{Rotation3, Vector3};
+use ocl::{self, builders::KernelBuilder, prm};
+use std::collections::HashSet;
+
+/// Orthographic (parallel) projection view.
+#[derive(Debug, Clone)]
+pub struct OrthographicView {
+    /// Position of the center of the view.
+    pub pos: Vector3<f64>,
+    /// Orientation.
+    pub ori: Rotation3<f64>,
+    /// Height of the visible area in world space,
+    /// its width is determined by the aspect ratio of the image.
+    pub size: f64,
+    /// Motion of the view during the frame, the view is static if `None`.
+    pub shutter: Option<Shutter>,
+}
+
+impl OrthographicView {
+    pub fn new(pos: Vector3<f64>, ori: Rotation3<f64>, size: f64) -> Self {
+        Self {
+            pos,
+            ori,
+            size,
+            shutter: None,
+        }
+    }
+
+    pub fn update(&mut self, pos: Vector3<f64>, ori: Rotation3<f64>) {
+        self.pos = pos;
+        self.ori = ori;
+    }
+}
+
+impl View for OrthographicView {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/view/ortho_view.h>\n".to_string()
+    }
+}
+
+impl Store for OrthographicView {
+    type Data = Self;
+    fn create_data(&self, _context: &Context) -> clay_core::Result<Self::Data> {
+        Ok(self.clone())
+    }
+    fn update_data(&self, _context: &Context, data: &mut Self::Data) -> clay_core::Result<()> {
+        *data = self.clone();
+        Ok(())
+    }
+}
+
+impl Push for OrthographicView {
+    fn args_count() -> usize {
+        3 + SHUTTER_ARGS_COUNT
+    }
+    fn args_def(kb: &mut KernelBuilder) {
+        kb.arg(prm::Float3::zero())
+            .arg(prm::Float16::zero())
+            .arg(0.0f32);
+        shutter_args_def(kb);
+    }
+    fn args_set(&mut self, i: usize, k: &mut ocl::Kernel) -> crate::Result<()> {
+        let (pos, map) = frame_args(&self.pos, &self.ori);
+        k.set_arg(i, &pos)?;
+        k.set_arg(i + 1, &map)?;
+        k.set_arg(i + 2, &(self.size as f32))?;
+        shutter_args_set(self.shutter.as_ref(), &self.pos, &self.ori, i + 3, k)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{
+    prelude::*,
+    view::{frame_args, shutter_args_def, shutter_args_set, Shutter, View, SHUTTER_ARGS_COUNT},
+    Context,
+};
+use nalgebra::{Rotation3, Vector3};
+use ocl::{self, builders::KernelBuilder, prm};
+use std::collections::HashSet;
+
+/// Panoramic view covering the full sphere in the equirectangular layout.
+///
+/// The image width spans the full turn around the view `y` axis with the view direction
+/// in the center, so the image with 2:1 aspect ratio has the same angular resolution
+/// in both directions.
+#[derive(Debug, Clone)]
+pub struct PanoramicView {
+    /// Position of the point of view.
+    pub pos: Vector3<f64>,
+    /// Orientation.
+    pub ori: Rotation3<f64>,
+    /// Motion of the view during the frame, the view is static if `None`.
+    pub shutter: Option<Shutter>,
+}
+
+impl PanoramicView {
+    pub fn new(pos: Vector3<f64>, ori: Rotation3<f64>) -> Self {
+        Self {
+            pos,
+            ori,
+            shutter: None,
+        }
+    }
+
+    pub fn update(&mut self, pos: Vector3<f64>, ori: Rotation3<f64>) {
+        self.pos = pos;
+        self.ori = ori;
+    }
+}
+
+impl View for PanoramicView {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/view/pano_view.h>\n".to_string()
+    }
+}
+
+impl Store for PanoramicView {
+    type Data = Self;
+    fn create_data(&self, _context: &Context) -> clay_core::Result<Self::Data> {
+        Ok(self.clone())
+    }
+    fn update_data(&self, _context: &Context, data: &mut Self::Data) -> clay_core::Result<()> {
+        *data = self.clone();
+        Ok(())
+    }
+}
+
+impl Push for PanoramicView {
+    fn args_count() -> usize {
+        2 + SHUTTER_ARGS_COUNT
+    }
+    fn args_def(kb: &mut KernelBuilder) {
+        kb.arg(prm::Float3::zero()).arg(prm::Float16::zero());
+        shutter_args_def(kb);
+    }
+    fn args_set(&mut self, i: usize, k: &mut ocl::Kernel) -> crate::Result<()> {
+        let (pos, map) = frame_args(&self.pos, &self.ori);
+        k.set_arg(i, &pos)?;
+        k.set_arg(i + 1, &map)?;
+        shutter_args_set(self.shutter.as_ref(), &self.pos, &self.ori, i + 2, k)?;
+
+        Ok(())
+    }
+}
@@ -1,9 +1,16 @@
-use crate::{prelude::*, view::View, Context};
+use crate::{
+    prelude::*,
+    view::{frame_args, shutter_args_def, shutter_args_set, Shutter, View, SHUTTER_ARGS_COUNT},
+    Context,
+};
 use nalgebra::{Rotation3, Vector3};
 use ocl::{self, builders::KernelBuilder, prm};
 use std::collections::HashSet;
 
 /// Perspective projection view.
+///
+/// It is a pinhole camera by default, non-zero `aperture` turns it into
+/// a thin lens camera with depth of field.
 #[derive(Debug, Clone)]
 pub struct ProjectionView {
     /// Position of the point of view.
@@ -12,11 +19,36 @@ pub struct ProjectionView {
     pub ori: Rotation3<f64>,
     /// Field of view width.
     pub fov: f64,
+    /// Radius of the lens aperture.
+    pub aperture: f64,
+    /// Distance to the plane in focus.
+    pub focus: f64,
+    /// Number of aperture blades, the aperture is round if it is less than three.
+    pub blades: usize,
+    /// Rotation angle of the aperture polygon.
+    pub blade_rotation: f64,
+    /// Motion of the view during the frame, the view is static if `None`.
+    pub shutter: Option<Shutter>,
 }
 
 impl ProjectionView {
     pub fn new(pos: Vector3<f64>, ori: Rotation3<f64>) -> Self {
-        Self { pos, ori, fov: 1.0 }
+        Self {
+            pos,
+            ori,
+            fov: 1.0,
+            aperture: 0.0,
+            focus: 1.0,
+            blades: 0,
+            blade_rotation: 0.0,
+            shutter: None,
+        }
+    }
+
+    /// Sets the lens aperture radius and the focus distance.
+    pub fn set_lens(&mut self, aperture: f64, focus: f64) {
+        self.aperture = aperture;
+        self.focus = focus;
     }
 
     pub fn update(&mut self, pos: Vector3<f64>, ori: Rotation3<f64>) {
@@ -44,27 +76,28 @@ impl Store for ProjectionView {
 
 impl Push for ProjectionView {
     fn args_count() -> usize {
-        3
+        7 + SHUTTER_ARGS_COUNT
     }
     fn args_def(kb: &mut KernelBuilder) {
         kb.arg(prm::Float3::zero())
             .arg(prm::Float16::zero())
+            .arg(0.0f32)
+            .arg(0.0f32)
+            .arg(0.0f32)
+            .arg(0i32)
             .arg(0.0f32);
+        shutter_args_def(kb);
     }
     fn args_set(&mut self, i: usize, k: &mut ocl::Kernel) -> crate::Result<()> {
-        let mapf = self.ori.matrix().map(|x| x as f32);
-        let mut map16 = [0f32; 16];
-        map16[0..3].copy_from_slice(&mapf.as_slice()[0..3]);
-        map16[4..7].copy_from_slice(&mapf.as_slice()[3..6]);
-        map16[8..11].copy_from_slice(&mapf.as_slice()[6..9]);
-
-        let posf = self.pos.map(|x| x as f32);
-        let mut pos3 = [0f32; 3];
-        pos3.copy_from_slice(posf.as_slice());
-
-        k.set_arg(i + 0, &prm::Float3::from(pos3))?;
-        k.set_arg(i + 1, &prm::Float16::from(map16))?;
+        let (pos, map) = frame_args(&self.pos, &self.ori);
+        k.set_arg(i, &pos)?;
+        k.set_arg(i + 1, &map)?;
         k.set_arg(i + 2, &(self.fov as f32))?;
+        k.set_arg(i + 3, &(self.aperture as f32))?;
+        k.set_arg(i + 4, &(self.focus as f32))?;
+        k.set_arg(i + 5, &(self.blades as i32))?;
+        k.set_arg(i + 6, &(self.blade_rotation as f32))?;
+        shutter_args_set(self.shutter.as_ref(), &self.pos, &self.ori, i + 7, k)?;
 
         Ok(())
     }
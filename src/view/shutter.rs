@@ -0,0 +1,76 @@
+use nalgebra::{Rotation3, Vector3};
+use ocl::{builders::KernelBuilder, prm};
+
+/// Motion of the view during the exposure of the frame.
+///
+/// The view moves from its own position and orientation at the start of the frame
+/// to the ones of the shutter at the end of the frame. Each ray is emitted
+/// at a random moment between `open` and `close`, so the motion gets blurred.
+#[derive(Debug, Clone)]
+pub struct Shutter {
+    /// Position of the view at the end of the frame.
+    pub pos: Vector3<f64>,
+    /// Orientation of the view at the end of the frame.
+    pub ori: Rotation3<f64>,
+    /// Moment the shutter opens, `0` is the start of the frame and `1` is the end.
+    pub open: f64,
+    /// Moment the shutter closes.
+    pub close: f64,
+}
+
+impl Shutter {
+    /// Creates the shutter open during the whole frame.
+    pub fn new(pos: Vector3<f64>, ori: Rotation3<f64>) -> Self {
+        Self {
+            pos,
+            ori,
+            open: 0.0,
+            close: 1.0,
+        }
+    }
+
+    /// Position and orientation of the view at the moment `t` of the frame.
+    pub fn frame_at(
+        &self,
+        pos: &Vector3<f64>,
+        ori: &Rotation3<f64>,
+        t: f64,
+    ) -> (Vector3<f64>, Rotation3<f64>) {
+        let rot = Rotation3::new(t * (ori.inverse() * self.ori).scaled_axis());
+        (pos + t * (self.pos - pos), ori * rot)
+    }
+}
+
+pub(crate) const SHUTTER_ARGS_COUNT: usize = 4;
+
+pub(crate) fn shutter_args_def(kb: &mut KernelBuilder) {
+    kb.arg(prm::Float3::zero())
+        .arg(prm::Float3::zero())
+        .arg(0.0f32)
+        .arg(0.0f32);
+}
+
+/// Sets the motion of the view, see `shutter_sample` in `shutter.h`.
+pub(crate) fn shutter_args_set(
+    shutter: Option<&Shutter>,
+    pos: &Vector3<f64>,
+    ori: &Rotation3<f64>,
+    i: usize,
+    k: &mut ocl::Kernel,
+) -> crate::Result<()> {
+    let float3 = |v: Vector3<f64>| prm::Float3::new(v.x as f32, v.y as f32, v.z as f32);
+    let (shift, rot, open, close) = match shutter {
+        Some(s) => (
+            s.pos - pos,
+            (ori.inverse() * s.ori).scaled_axis(),
+            s.open,
+            s.close,
+        ),
+        None => (Vector3::zeros(), Vector3::zeros(), 0.0, 0.0),
+    };
+    k.set_arg(i, &float3(shift))?;
+    k.set_arg(i + 1, &float3(rot))?;
+    k.set_arg(i + 2, &(open as f32))?;
+    k.set_arg(i + 3, &(close as f32))?;
+    Ok(())
+}
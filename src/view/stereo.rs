@@ -0,0 +1,84 @@
+use crate::{
+    prelude::*,
+    view::{ProjectionView, View},
+    Context,
+};
+use nalgebra::{Rotation3, Vector3};
+use ocl::{self, builders::KernelBuilder};
+use std::collections::HashSet;
+
+/// Placement of the eye images in the render buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StereoLayout {
+    /// Left eye in the left half, right eye in the right half.
+    #[default]
+    SideBySide = 0,
+    /// Left eye in the top half, right eye in the bottom half.
+    TopBottom = 1,
+}
+
+/// Stereo view rendering both eyes into halves of the image.
+///
+/// Eyes are placed at the distance `ipd` from each other around the position
+/// of the underlying projection view, their images coincide at the `convergence` distance.
+#[derive(Debug, Clone)]
+pub struct StereoView {
+    /// View of the point between the eyes.
+    pub view: ProjectionView,
+    /// Interpupillary distance.
+    pub ipd: f64,
+    /// Distance to the plane of zero parallax.
+    pub convergence: f64,
+    pub layout: StereoLayout,
+}
+
+impl StereoView {
+    pub fn new(view: ProjectionView, ipd: f64, convergence: f64) -> Self {
+        Self {
+            view,
+            ipd,
+            convergence,
+            layout: StereoLayout::default(),
+        }
+    }
+
+    pub fn update(&mut self, pos: Vector3<f64>, ori: Rotation3<f64>) {
+        self.view.update(pos, ori);
+    }
+}
+
+impl View for StereoView {
+    fn source(_: &mut HashSet<u64>) -> String {
+        "#include <clay/view/stereo_view.h>\n".to_string()
+    }
+}
+
+impl Store for StereoView {
+    type Data = Self;
+    fn create_data(&self, _context: &Context) -> clay_core::Result<Self::Data> {
+        Ok(self.clone())
+    }
+    fn update_data(&self, _context: &Context, data: &mut Self::Data) -> clay_core::Result<()> {
+        *data = self.clone();
+        Ok(())
+    }
+}
+
+impl Push for StereoView {
+    fn args_count() -> usize {
+        ProjectionView::args_count() + 3
+    }
+    fn args_def(kb: &mut KernelBuilder) {
+        ProjectionView::args_def(kb);
+        kb.arg(0.0f32).arg(0.0f32).arg(0i32);
+    }
+    fn args_set(&mut self, i: usize, k: &mut ocl::Kernel) -> crate::Result<()> {
+        self.view.args_set(i, k)?;
+        let j = i + ProjectionView::args_count();
+        k.set_arg(j, &(self.ipd as f32))?;
+        k.set_arg(j + 1, &(self.convergence as f32))?;
+        k.set_arg(j + 2, &(self.layout as i32))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+//! Common utilities for the rendering tests.
+//!
+//! The tests are run on a CPU OpenCL device (POCL is preferred if present)
+//! and are skipped if there is no such device in the system.
+//!
+//! Rendered images are compared with the stored statistics in `tests/reference`.
+//! The device random generator is seeded with a fixed seed, so the renders are
+//! reproducible on the same device, but the float math differs between
+//! OpenCL implementations, so the statistics are compared within tolerances
+//! that are stored along with them.
+//!
+//! A missing reference is an error. The references are (re)written from the current
+//! renders only when `CLAY_BLESS` environment variable is set.
+
+#![allow(dead_code)]
+
+use clay::{
+    process::{create_renderer, read_hdr, run_budget, Budget, RenderWorker},
+    scene::Scene,
+    view::View,
+    Context,
+};
+use ocl::{flags::DeviceType, Device, Platform};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{env, fs, path::PathBuf};
+
+/// Name of the POCL platform.
+const POCL_NAME: &str = "Portable Computing Language";
+
+/// Seed of the device random generator.
+const SEED: u64 = 0x5eed;
+
+/// Default tolerances of the new reference.
+const MEAN_TOLERANCE: f64 = 0.05;
+const HISTOGRAM_TOLERANCE: f64 = 0.1;
+
+/// Lower bound of the luminance histogram, the first bin also contains all darker pixels.
+const HIST_MIN_LOG2: i32 = -6;
+/// Number of histogram bins, each bin has the width of one stop (power of two),
+/// the last bin also contains all brighter pixels.
+const HIST_BINS: usize = 12;
+
+/// Creates context on the first available CPU device.
+pub fn cpu_context() -> Option<Context> {
+    let mut platforms = Platform::list();
+    platforms.sort_by_key(|p| !p.name().map(|n| n.contains(POCL_NAME)).unwrap_or(false));
+    for platform in platforms {
+        let device = match Device::list(platform, Some(DeviceType::CPU)) {
+            Ok(devices) => devices.into_iter().next(),
+            Err(_) => None,
+        };
+        if let Some(device) = device {
+            match Context::new(platform, device) {
+                Ok(context) => return Some(context),
+                Err(e) => eprintln!("cannot create context: {}", e),
+            }
+        }
+    }
+    None
+}
+
+/// Gets the CPU context or skips the test.
+macro_rules! cpu_context_or_skip {
+    () => {
+        match common::cpu_context() {
+            Some(context) => context,
+            None => {
+                eprintln!("no CPU OpenCL device found, skipping the test");
+                return;
+            }
+        }
+    };
+}
+
+/// Renders the scene and returns linear RGB color of pixels.
+pub fn render<S: Scene, V: View>(
+    context: &Context,
+    scene: S,
+    view: V,
+    dims: (usize, usize),
+    passes: usize,
+) -> clay::Result<Vec<f32>> {
+    let renderer = create_renderer::<S, V>().build(dims, scene, view)?;
+    let (mut worker, _) = renderer.create_worker(context)?;
+    seed(&worker)?;
+    run_budget(&mut worker, Budget::Passes(passes))?;
+    read_hdr(&worker, dims)
+}
+
+/// Replaces the state of the device random generator with the one derived from `SEED`.
+pub fn seed<S: Scene, V: View>(worker: &RenderWorker<S, V>) -> clay::Result<()> {
+    let random = worker.data().buffer().random();
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let state = (0..random.len())
+        .map(|_| rng.next_u32())
+        .collect::<Vec<_>>();
+    random.write(&state).enq()?;
+    Ok(())
+}
+
+pub fn luminance(rgb: &[f32]) -> f64 {
+    0.2126 * rgb[0] as f64 + 0.7152 * rgb[1] as f64 + 0.0722 * rgb[2] as f64
+}
+
+/// Statistics of the rendered image.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Stats {
+    /// Mean color of the image.
+    pub mean: [f64; 3],
+    /// Fraction of pixels in each luminance bin.
+    pub histogram: Vec<f64>,
+}
+
+impl Stats {
+    pub fn new(rgb: &[f32]) -> Self {
+        let n = rgb.len() / 3;
+        let mut mean = [0.0; 3];
+        let mut histogram = vec![0.0; HIST_BINS];
+        for px in rgb.chunks(3) {
+            for (m, &c) in mean.iter_mut().zip(px.iter()) {
+                *m += c as f64 / n as f64;
+            }
+            let l = luminance(px);
+            let bin = if l > 0.0 {
+                (l.log2().floor() as i32 - HIST_MIN_LOG2).max(0) as usize
+            } else {
+                0
+            };
+            histogram[bin.min(HIST_BINS - 1)] += 1.0 / n as f64;
+        }
+        Self { mean, histogram }
+    }
+
+    pub fn mean_luminance(&self) -> f64 {
+        let m = self.mean;
+        luminance(&[m[0] as f32, m[1] as f32, m[2] as f32])
+    }
+}
+
+/// Stored statistics of the image and the allowed deviation from them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Reference {
+    pub stats: Stats,
+    /// Relative tolerance of each mean color component.
+    pub mean_tolerance: f64,
+    /// Tolerance of the L1 distance between histograms.
+    pub histogram_tolerance: f64,
+}
+
+impl Reference {
+    fn path(name: &str) -> PathBuf {
+        [env!("CARGO_MANIFEST_DIR"), "tests", "reference"]
+            .iter()
+            .collect::<PathBuf>()
+            .join(format!("{}.json", name))
+    }
+
+    pub fn load(name: &str) -> Option<Self> {
+        let text = fs::read_to_string(Self::path(name)).ok()?;
+        Some(serde_json::from_str(&text).expect("bad reference file"))
+    }
+
+    pub fn store(&self, name: &str) {
+        let path = Self::path(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, serde_json::to_string_pretty(self).unwrap()).unwrap();
+        eprintln!("reference written to {}", path.display());
+    }
+
+    /// Checks the statistics against the reference, panics if they don't match.
+    pub fn check(&self, stats: &Stats) {
+        for (i, (&a, &b)) in stats.mean.iter().zip(self.stats.mean.iter()).enumerate() {
+            assert!(
+                (a - b).abs() <= self.mean_tolerance * b.abs().max(1e-3),
+                "mean color component {} is {}, expected {}",
+                i,
+                a,
+                b,
+            );
+        }
+        let dist = stats
+            .histogram
+            .iter()
+            .zip(self.stats.histogram.iter())
+            .map(|(a, b)| (a - b).abs())
+            .sum::<f64>();
+        assert!(
+            dist <= self.histogram_tolerance,
+            "histogram distance is {}, histogram: {:?}, expected: {:?}",
+            dist,
+            stats.histogram,
+            self.stats.histogram,
+        );
+    }
+}
+
+/// Relative tolerance of mean colors of the device and the host images.
+#[cfg(feature = "cpu")]
+const CPU_MEAN_TOLERANCE: f64 = 0.01;
+/// Tolerance of the L1 distance between histograms of the device and the host images.
+#[cfg(feature = "cpu")]
+const CPU_HISTOGRAM_TOLERANCE: f64 = 0.02;
+
+/// Renders the scene on the device and on the host (*see `clay::cpu`*)
+/// and checks that the statistics of the images match.
+#[cfg(feature = "cpu")]
+pub fn compare_with_cpu<S, V>(
+    context: &Context,
+    scene: S,
+    view: V,
+    dims: (usize, usize),
+    passes: usize,
+) where
+    S: Scene + clay::cpu::CpuScene,
+    V: View + clay::cpu::CpuView,
+{
+    let host = clay::cpu::render(&scene, &view, dims, passes, 0);
+    let device = render(context, scene, view, dims, passes).unwrap();
+    Reference {
+        stats: Stats::new(&host),
+        mean_tolerance: CPU_MEAN_TOLERANCE,
+        histogram_tolerance: CPU_HISTOGRAM_TOLERANCE,
+    }
+    .check(&Stats::new(&device));
+}
+
+/// Compares the image statistics with the named reference,
+/// or rewrites the reference keeping its tolerances if `CLAY_BLESS` is set.
+pub fn check_reference(name: &str, rgb: &[f32]) {
+    let stats = Stats::new(rgb);
+    let reference = Reference::load(name);
+    if env::var_os("CLAY_BLESS").is_some() {
+        let (mean_tolerance, histogram_tolerance) = reference
+            .map(|r| (r.mean_tolerance, r.histogram_tolerance))
+            .unwrap_or((MEAN_TOLERANCE, HISTOGRAM_TOLERANCE));
+        Reference {
+            stats,
+            mean_tolerance,
+            histogram_tolerance,
+        }
+        .store(name);
+        return;
+    }
+    match reference {
+        Some(reference) => reference.check(&stats),
+        None => panic!(
+            "reference `{}` not found, run the tests with `CLAY_BLESS=1` to create it",
+            name,
+        ),
+    }
+}
@@ -0,0 +1,884 @@
+#![cfg(feature = "cpu")]
+
+use clay::{cpu::*, map::*, material::*, object::*, prelude::*, scene::*, shape::*, view::*};
+use nalgebra::{Matrix3, Rotation3, UnitQuaternion, Vector3};
+use rand::{rngs::StdRng, SeedableRng};
+
+const EPS: f64 = 1e-9;
+
+fn assert_close(a: &Vector3<f64>, b: &Vector3<f64>) {
+    assert!((a - b).norm() < EPS, "{:?} != {:?}", a, b);
+}
+
+fn ray(start: [f64; 3], dir: [f64; 3]) -> Ray {
+    Ray::new(Vector3::from(start), Vector3::from(dir).normalize())
+}
+
+#[test]
+fn unit_sphere() {
+    let hit = UnitSphere::new()
+        .hit(&ray([0.0, 0.0, -3.0], [0.0, 0.0, 1.0]))
+        .unwrap();
+    assert!((hit.enter - 2.0).abs() < EPS);
+    assert!((hit.exit - 4.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, 0.0, -1.0));
+
+    assert!(UnitSphere::new()
+        .hit(&ray([0.0, 2.0, -3.0], [0.0, 0.0, 1.0]))
+        .is_none());
+    assert!(UnitSphere::new()
+        .hit(&ray([0.0, 0.0, 3.0], [0.0, 0.0, 1.0]))
+        .is_none());
+}
+
+#[test]
+fn unit_cube() {
+    let hit = UnitCube::new()
+        .hit(&ray([3.0, 0.5, 0.5], [-1.0, 0.0, 0.0]))
+        .unwrap();
+    assert!((hit.enter - 2.0).abs() < EPS);
+    assert!((hit.exit - 4.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(1.0, 0.0, 0.0));
+
+    assert!(UnitCube::new()
+        .hit(&ray([3.0, 1.5, 0.0], [-1.0, 0.0, 0.0]))
+        .is_none());
+}
+
+#[test]
+fn sphere() {
+    let sphere = Sphere::new(2.0, Vector3::new(1.0, 0.0, 0.0));
+    let hit = sphere.hit(&ray([1.0, 0.0, -5.0], [0.0, 0.0, 1.0])).unwrap();
+    assert!((hit.enter - 3.0).abs() < EPS);
+    assert!((hit.exit - 7.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, 0.0, -1.0));
+}
+
+#[test]
+fn ellipsoid() {
+    let ori = Matrix3::from_diagonal(&Vector3::new(1.0, 2.0, 3.0));
+    let ellipsoid = Ellipsoid::new(ori, Vector3::new(0.0, 0.0, 1.0));
+    let hit = ellipsoid
+        .hit(&ray([0.0, -10.0, 1.0], [0.0, 1.0, 0.0]))
+        .unwrap();
+    assert!((hit.enter - 8.0).abs() < EPS);
+    assert!((hit.exit - 12.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, -1.0, 0.0));
+
+    // Normal of the ellipse x^2 + (z/3)^2 = 1 at (sqrt(1/2), 3*sqrt(1/2))
+    let s = 0.5f64.sqrt();
+    let hit = ellipsoid
+        .hit(&ray([s + 1.0, 0.0, 1.0 + 3.0 * s], [-1.0, 0.0, 0.0]))
+        .unwrap();
+    assert!((hit.enter - 1.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(3.0, 0.0, 1.0).normalize());
+}
+
+#[test]
+fn parallelepiped() {
+    let ori = *Rotation3::from_axis_angle(&Vector3::z_axis(), 0.25 * std::f64::consts::PI).matrix();
+    let shape = Parallelepiped::new(ori, Vector3::zeros());
+    let hit = shape.hit(&ray([5.0, 0.0, 0.0], [-1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - (5.0 - 2.0f64.sqrt())).abs() < EPS);
+    assert!(hit.norm.dot(&Vector3::x()) > 0.0);
+
+    // The bounding sphere contains the farthest corner of the sheared box
+    let shear = Matrix3::new(1.0, 1.0, 1.0, 0.0, 0.1, 0.0, 0.0, 0.0, 0.1);
+    let sphere: Sphere = Parallelepiped::new(shear, Vector3::zeros())
+        .bound()
+        .unwrap();
+    assert!((sphere.0.map.first.0 - 9.02f64.sqrt()).abs() < EPS);
+}
+
+#[test]
+fn triangle() {
+    let vs = [
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(1.0, 0.0, 0.0),
+        Vector3::new(0.0, 1.0, 0.0),
+    ];
+    let flat = Triangle::new(vs);
+    let hit = flat.hit(&ray([0.25, 0.25, 2.0], [0.0, 0.0, -1.0])).unwrap();
+    assert!((hit.enter - 2.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, 0.0, 1.0));
+    let hit = flat.hit(&ray([0.25, 0.25, -2.0], [0.0, 0.0, 1.0])).unwrap();
+    assert_close(&hit.norm, &Vector3::new(0.0, 0.0, -1.0));
+    assert!(flat
+        .hit(&ray([0.75, 0.75, 2.0], [0.0, 0.0, -1.0]))
+        .is_none());
+
+    let n = Vector3::new(0.0, 0.0, 1.0);
+    let smooth = Triangle::new_smooth(vs, [Vector3::new(-1.0, -1.0, 1.0).normalize(), n, n]);
+    let hit = smooth.hit(&ray([0.0, 0.0, 2.0], [0.0, 0.0, -1.0])).unwrap();
+    assert_close(&hit.norm, &Vector3::new(-1.0, -1.0, 1.0).normalize());
+}
+
+#[test]
+fn triangle_mesh() {
+    let obj = "\
+        v -1 -1 -1\nv 1 -1 -1\nv 1 1 -1\nv -1 1 -1\n\
+        v -1 -1 1\nv 1 -1 1\nv 1 1 1\nv -1 1 1\n\
+        f 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\nf 2 3 7 6\nf 3 4 8 7\nf 4 1 5 8\n";
+    let data = MeshData::read_obj(obj.as_bytes()).unwrap();
+    assert_eq!(data.faces.len(), 12);
+    let cube = TriangleMesh::new(data.clone()).unwrap();
+
+    let hit = cube.hit(&ray([0.2, 0.3, 5.0], [0.0, 0.0, -1.0])).unwrap();
+    assert!((hit.enter - 4.0).abs() < EPS);
+    assert!((hit.exit - 6.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, 0.0, 1.0));
+    let hit = cube.hit(&ray([0.0, 0.0, 0.0], [1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 1.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(-1.0, 0.0, 0.0));
+    assert!(cube.hit(&ray([2.0, 0.0, 5.0], [0.0, 0.0, -1.0])).is_none());
+
+    let mut big = data.clone();
+    big.faces = (0..(MESH_MAX_FACES + 1))
+        .map(|i| data.faces[i % 12].clone())
+        .collect();
+    assert!(TriangleMesh::new(big).is_err());
+
+    // The cube exactly fits into the mesh of the smaller capacity
+    type CubeMesh = SizedTriangleMesh<8, 12>;
+    assert_eq!(CubeMesh::size_int(), 2 + 6 * 12);
+    assert_eq!(CubeMesh::size_float(), 2 * 3 * 8);
+    let small = CubeMesh::new(data.clone()).unwrap();
+    let hit = small.hit(&ray([0.2, 0.3, 5.0], [0.0, 0.0, -1.0])).unwrap();
+    assert!((hit.enter - 4.0).abs() < EPS);
+    assert!(SizedTriangleMesh::<7, 12>::new(data.clone()).is_err());
+    assert!(SizedTriangleMesh::<8, 11>::new(data.clone()).is_err());
+
+    let mut bad = data;
+    bad.faces[0].vertices[0] = 8;
+    assert!(TriangleMesh::new(bad).is_err());
+}
+
+#[test]
+fn plane() {
+    let plane = Plane::new(Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, 1.0, 1.0));
+    let hit = plane.hit(&ray([0.0, 0.0, 5.0], [0.0, 0.0, -1.0])).unwrap();
+    assert!((hit.enter - 4.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, 1.0, 1.0).normalize());
+    let hit = plane.hit(&ray([0.0, 0.0, -5.0], [0.0, 0.0, 1.0])).unwrap();
+    assert!((hit.enter - 6.0).abs() < EPS);
+    assert_close(&hit.norm, &-Vector3::new(0.0, 1.0, 1.0).normalize());
+    assert!(plane.hit(&ray([0.0, 0.0, 5.0], [0.0, 0.0, 1.0])).is_none());
+    assert!(Bounded::<Aabb>::bound(&plane).is_none());
+}
+
+#[test]
+fn disk() {
+    let disk = Disk::new(
+        2.0,
+        Vector3::new(1.0, 0.0, 0.0),
+        Vector3::new(1.0, 0.0, 0.0),
+    );
+    let hit = disk.hit(&ray([-3.0, 1.5, 0.0], [1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 4.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(-1.0, 0.0, 0.0));
+    assert!(disk.hit(&ray([-3.0, 1.5, 1.5], [1.0, 0.0, 0.0])).is_none());
+
+    let sphere: Sphere = disk.bound().unwrap();
+    let aabb: Aabb = disk.bound().unwrap();
+    assert!((sphere.0.map.first.0 - 2.0).abs() < EPS);
+    assert_close(&sphere.0.map.second.0, &Vector3::new(1.0, 0.0, 0.0));
+    assert_close(&aabb.min, &Vector3::new(1.0, -2.0, -2.0));
+    assert_close(&aabb.max, &Vector3::new(1.0, 2.0, 2.0));
+}
+
+#[test]
+fn cylinder() {
+    let capped = UnitCylinder::new();
+    let hit = capped.hit(&ray([3.0, 0.0, 0.5], [-1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 2.0).abs() < EPS);
+    assert!((hit.exit - 4.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(1.0, 0.0, 0.0));
+    let hit = capped.hit(&ray([0.5, 0.0, 3.0], [0.0, 0.0, -1.0])).unwrap();
+    assert!((hit.enter - 2.0).abs() < EPS);
+    assert!((hit.exit - 4.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, 0.0, 1.0));
+    assert!(capped
+        .hit(&ray([3.0, 0.0, 1.5], [-1.0, 0.0, 0.0]))
+        .is_none());
+
+    let open = UnitCylinder::open();
+    assert!(open.hit(&ray([0.5, 0.0, 3.0], [0.0, 0.0, -1.0])).is_none());
+    let hit = open.hit(&ray([0.0, 0.0, 0.0], [1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 1.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(-1.0, 0.0, 0.0));
+    // Looking through the open end hits the inner side
+    let hit = open.hit(&ray([0.0, 0.0, 1.5], [0.6, 0.0, -0.8])).unwrap();
+    assert!((hit.enter - 5.0 / 3.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(-1.0, 0.0, 0.0));
+
+    let cylinder = Cylinder::between(
+        0.5,
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(4.0, 0.0, 0.0),
+    );
+    let hit = cylinder
+        .hit(&ray([2.0, 0.0, 3.0], [0.0, 0.0, -1.0]))
+        .unwrap();
+    assert!((hit.enter - 2.5).abs() < EPS);
+    assert!((hit.exit - 3.5).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, 0.0, 1.0));
+    let hit = cylinder
+        .hit(&ray([-1.0, 0.0, 0.0], [1.0, 0.0, 0.0]))
+        .unwrap();
+    assert!((hit.enter - 1.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(-1.0, 0.0, 0.0));
+    let aabb: Aabb = cylinder.bound().unwrap();
+    assert_close(&aabb.min, &Vector3::new(0.0, -0.5, -0.5));
+    assert_close(&aabb.max, &Vector3::new(4.0, 0.5, 0.5));
+}
+
+#[test]
+fn cone() {
+    let cone = UnitCone::new();
+    let hit = cone.hit(&ray([3.0, 0.0, 0.0], [-1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 2.5).abs() < EPS);
+    assert!((hit.exit - 3.5).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(2.0, 0.0, 1.0).normalize());
+    let hit = cone.hit(&ray([0.0, 0.0, -3.0], [0.0, 0.0, 1.0])).unwrap();
+    assert!((hit.enter - 2.0).abs() < EPS);
+    assert!((hit.exit - 4.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, 0.0, -1.0));
+    let hit = cone.hit(&ray([0.0, 0.0, 5.0], [0.0, 0.0, -1.0])).unwrap();
+    assert!((hit.enter - 4.0).abs() < EPS);
+    assert!((hit.exit - 6.0).abs() < EPS);
+    // The upper nappe of the double cone must not be hit
+    assert!(cone.hit(&ray([3.0, 0.0, 3.0], [-1.0, 0.0, 0.0])).is_none());
+    let hit = cone.hit(&ray([0.6, 0.0, 3.0], [0.0, 0.0, -1.0])).unwrap();
+    assert!((hit.enter - 3.2).abs() < EPS);
+
+    let cone = Cone::between(
+        1.0,
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 4.0, 0.0),
+    );
+    let hit = cone.hit(&ray([0.0, -2.0, 0.0], [0.0, 1.0, 0.0])).unwrap();
+    assert!((hit.enter - 2.0).abs() < EPS);
+    assert!((hit.exit - 6.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, -1.0, 0.0));
+}
+
+#[test]
+fn capsule() {
+    let capsule = Capsule::new(
+        0.5,
+        Vector3::new(0.0, 0.0, -1.0),
+        Vector3::new(0.0, 0.0, 1.0),
+    );
+    let hit = capsule
+        .hit(&ray([0.0, 0.0, 5.0], [0.0, 0.0, -1.0]))
+        .unwrap();
+    assert!((hit.enter - 3.5).abs() < EPS);
+    assert!((hit.exit - 6.5).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, 0.0, 1.0));
+    let hit = capsule
+        .hit(&ray([3.0, 0.0, 0.5], [-1.0, 0.0, 0.0]))
+        .unwrap();
+    assert!((hit.enter - 2.5).abs() < EPS);
+    assert!((hit.exit - 3.5).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(1.0, 0.0, 0.0));
+    let s = 0.5f64.sqrt();
+    let hit = capsule
+        .hit(&ray([3.0, 0.0, 1.0 + 0.5 * s], [-1.0, 0.0, 0.0]))
+        .unwrap();
+    assert!((hit.enter - (3.0 - 0.5 * s)).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(s, 0.0, s));
+    assert!(capsule
+        .hit(&ray([3.0, 0.0, 1.6], [-1.0, 0.0, 0.0]))
+        .is_none());
+
+    let sphere: Sphere = capsule.bound().unwrap();
+    assert!((sphere.0.map.first.0 - 1.5).abs() < EPS);
+    let aabb: Aabb = capsule.bound().unwrap();
+    assert_close(&aabb.min, &Vector3::new(-0.5, -0.5, -1.5));
+    assert_close(&aabb.max, &Vector3::new(0.5, 0.5, 1.5));
+}
+
+#[test]
+fn torus() {
+    let torus = UnitTorus::new(0.25);
+    let hit = torus.hit(&ray([-3.0, 0.0, 0.0], [1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 1.75).abs() < EPS);
+    assert!((hit.exit - 2.25).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(-1.0, 0.0, 0.0));
+    let hit = torus.hit(&ray([0.0, 0.0, 0.0], [0.0, 1.0, 0.0])).unwrap();
+    assert!((hit.enter - 0.75).abs() < EPS);
+    assert!((hit.exit - 1.25).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, -1.0, 0.0));
+    let hit = torus.hit(&ray([1.0, 0.0, 3.0], [0.0, 0.0, -1.0])).unwrap();
+    assert!((hit.enter - 2.75).abs() < EPS);
+    assert!((hit.exit - 3.25).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, 0.0, 1.0));
+    // Through the hole and past the tube
+    assert!(torus.hit(&ray([0.0, 0.0, 3.0], [0.0, 0.0, -1.0])).is_none());
+    assert!(torus.hit(&ray([-3.0, 0.0, 0.3], [1.0, 0.0, 0.0])).is_none());
+
+    // Off-center hit of the outer side of the tube
+    let s = 0.5f64.sqrt();
+    let hit = torus
+        .hit(&ray([-3.0, 0.0, 0.25 * s], [1.0, 0.0, 0.0]))
+        .unwrap();
+    assert!((hit.enter - (2.0 - 0.25 * s)).abs() < 1e-6);
+    assert_close(&hit.norm, &Vector3::new(-s, 0.0, s));
+
+    let torus = Torus::with_radii(
+        2.0,
+        0.5,
+        Vector3::new(0.0, 0.0, 1.0),
+        Vector3::new(1.0, 0.0, 0.0),
+    );
+    let hit = torus.hit(&ray([0.0, 0.0, 5.0], [0.0, 0.0, -1.0])).unwrap();
+    assert!((hit.enter - 1.5).abs() < EPS);
+    assert!((hit.exit - 2.5).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, 0.0, 1.0));
+    let aabb: Aabb = torus.bound().unwrap();
+    assert_close(&aabb.min, &Vector3::new(-0.5, -2.5, -1.5));
+    assert_close(&aabb.max, &Vector3::new(0.5, 2.5, 3.5));
+}
+
+#[test]
+fn heightfield() {
+    let flat = Heightfield::from_fn((3, 3), |_, _| 0.5).unwrap();
+    let hit = flat.hit(&ray([0.0, 0.0, 3.0], [0.0, 0.0, -1.0])).unwrap();
+    assert!((hit.enter - 2.5).abs() < EPS);
+    assert!((hit.exit - 3.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, 0.0, 1.0));
+    // Enters through the side of the bounding box
+    let hit = flat.hit(&ray([-3.0, 0.0, 0.25], [1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 2.0).abs() < EPS);
+    assert!((hit.exit - 4.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(-1.0, 0.0, 0.0));
+    assert!(flat.hit(&ray([-3.0, 0.0, 0.75], [1.0, 0.0, 0.0])).is_none());
+    assert!(flat.hit(&ray([0.0, 0.0, 0.25], [0.0, 0.0, -1.0])).is_none());
+
+    // Slope crossed by the grid traversal
+    let slope = Heightfield::from_fn((5, 5), |x, _| 0.5 * (x + 1.0)).unwrap();
+    let norm = Vector3::new(-0.5, 0.0, 1.0).normalize();
+    let hit = slope.hit(&ray([0.0, 0.0, 5.0], [0.0, 0.0, -1.0])).unwrap();
+    assert!((hit.enter - 4.5).abs() < EPS);
+    assert!((hit.exit - 5.0).abs() < EPS);
+    assert_close(&hit.norm, &norm);
+    let hit = slope.hit(&ray([-3.0, 0.3, 0.75], [1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 3.5).abs() < EPS);
+    assert!((hit.exit - 4.0).abs() < EPS);
+    assert_close(&hit.norm, &norm);
+    let s = 0.5f64.sqrt();
+    let hit = slope.hit(&ray([-0.8, -0.9, 1.5], [0.0, s, -s])).unwrap();
+    assert!((hit.enter - 1.4 / s).abs() < EPS);
+    assert_close(&hit.norm, &norm);
+
+    let aabb: Aabb = slope.bound().unwrap();
+    assert_close(&aabb.max, &Vector3::new(1.0, 1.0, 1.0));
+    assert!(Heightfield::new((1, 1), vec![0.0]).is_err());
+    assert!(Heightfield::new((2, 2), vec![0.0, 1.0, -1.0, 0.0]).is_err());
+}
+
+#[test]
+fn csg() {
+    let sphere = |x: f64| Sphere::new(1.0, Vector3::new(x, 0.0, 0.0));
+
+    let union = Union::new(sphere(0.0), sphere(1.0));
+    let hit = union.hit(&ray([-3.0, 0.0, 0.0], [1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 2.0).abs() < EPS);
+    assert!((hit.exit - 5.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(-1.0, 0.0, 0.0));
+    let union = Union::new(sphere(3.0), sphere(0.0));
+    let hit = union.hit(&ray([-3.0, 0.0, 0.0], [1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 2.0).abs() < EPS);
+    assert!((hit.exit - 4.0).abs() < EPS);
+    // From inside of one operand the other one is hit
+    let hit = union.hit(&ray([0.0, 0.0, 0.0], [1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 2.0).abs() < EPS);
+
+    let lens = Intersection::new(sphere(0.0), sphere(1.0));
+    let hit = lens.hit(&ray([-3.0, 0.0, 0.0], [1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 3.0).abs() < EPS);
+    assert!((hit.exit - 4.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(-1.0, 0.0, 0.0));
+    assert!(lens.hit(&ray([-3.0, 0.0, 0.9], [1.0, 0.0, 0.0])).is_none());
+    let aabb: Aabb = lens.bound().unwrap();
+    assert_close(&aabb.min, &Vector3::new(0.0, -1.0, -1.0));
+    assert_close(&aabb.max, &Vector3::new(1.0, 1.0, 1.0));
+
+    let hollow = Difference::new(UnitCube::new(), Sphere::new(0.5, Vector3::zeros()));
+    let hit = hollow.hit(&ray([-3.0, 0.0, 0.0], [1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 2.0).abs() < EPS);
+    assert!((hit.exit - 2.5).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(-1.0, 0.0, 0.0));
+    // The cavity is seen from inside of it
+    let hit = hollow.hit(&ray([0.0, 0.0, 0.0], [1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 0.5).abs() < EPS);
+    assert!((hit.exit - 1.0).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(-1.0, 0.0, 0.0));
+
+    let wall = Difference::new(
+        Parallelepiped::new(
+            Matrix3::from_diagonal(&Vector3::new(0.1, 2.0, 2.0)),
+            Vector3::zeros(),
+        ),
+        Parallelepiped::new(
+            Matrix3::from_diagonal(&Vector3::new(1.0, 0.5, 0.5)),
+            Vector3::zeros(),
+        ),
+    );
+    assert!(wall.hit(&ray([-3.0, 0.0, 0.0], [1.0, 0.0, 0.0])).is_none());
+    let hit = wall.hit(&ray([-3.0, 1.0, 0.0], [1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 2.9).abs() < EPS);
+    let hit = wall.hit(&ray([0.0, 0.0, 0.0], [0.0, 1.0, 0.0])).unwrap();
+    assert!((hit.enter - 0.5).abs() < EPS);
+    assert_close(&hit.norm, &Vector3::new(0.0, -1.0, 0.0));
+}
+
+#[test]
+fn sdf() {
+    let tol = 1e-3;
+    let mut shape = SdfShape::new(SdfSphere::new(1.0, Vector3::new(0.0, 0.0, 1.0)));
+    shape.eps = 1e-6;
+    let hit = shape.hit(&ray([0.0, -3.0, 1.0], [0.0, 1.0, 0.0])).unwrap();
+    assert!((hit.enter - 2.0).abs() < tol);
+    assert!((hit.exit - 4.0).abs() < tol);
+    assert!((hit.norm - Vector3::new(0.0, -1.0, 0.0)).norm() < tol);
+    assert!(shape.hit(&ray([0.0, -3.0, 2.5], [0.0, 1.0, 0.0])).is_none());
+
+    let cube = SdfShape::new(SdfBox::new(Vector3::repeat(1.0), Vector3::zeros()));
+    let hit = cube.hit(&ray([3.0, 0.5, 0.5], [-1.0, 0.0, 0.0])).unwrap();
+    assert!((hit.enter - 2.0).abs() < tol);
+    assert!((hit.norm - Vector3::new(1.0, 0.0, 0.0)).norm() < tol);
+
+    let torus = SdfShape::new(SdfTorus::new(1.0, 0.25, Vector3::zeros()));
+    let hit = torus.hit(&ray([0.0, 0.0, 0.0], [0.0, 1.0, 0.0])).unwrap();
+    assert!((hit.enter - 0.75).abs() < tol);
+    assert!((hit.exit - 1.25).abs() < tol);
+
+    // Separate spheres are connected by the smooth union
+    let spheres = |k| {
+        SdfShape::new(SmoothUnion::new(
+            SdfSphere::new(1.0, Vector3::new(-1.5, 0.0, 0.0)),
+            SdfSphere::new(1.0, Vector3::new(1.5, 0.0, 0.0)),
+            k,
+        ))
+    };
+    assert!(spheres(0.0)
+        .hit(&ray([0.0, 0.0, 3.0], [0.0, 0.0, -1.0]))
+        .is_none());
+    let hit = spheres(3.0)
+        .hit(&ray([0.0, 0.0, 3.0], [0.0, 0.0, -1.0]))
+        .unwrap();
+    // The neck is where `sqrt(1.5^2 + z^2) - 1 = k/4`
+    assert!((hit.enter - (3.0 - 0.8125f64.sqrt())).abs() < tol);
+    assert!((hit.norm - Vector3::new(0.0, 0.0, 1.0)).norm() < tol);
+
+    let morph = SdfShape::new(Blend::new(
+        SdfSphere::new(1.0, Vector3::zeros()),
+        SdfSphere::new(2.0, Vector3::zeros()),
+        0.5,
+    ));
+    let hit = morph.hit(&ray([0.0, 0.0, 3.0], [0.0, 0.0, -1.0])).unwrap();
+    assert!((hit.enter - 1.5).abs() < tol);
+
+    let bulb = SdfShape::new(Mandelbulb::default());
+    let hit = bulb.hit(&ray([0.0, 0.0, 3.0], [0.0, 0.0, -1.0])).unwrap();
+    assert!(hit.enter > 1.0 && hit.enter < 3.0);
+    assert!((hit.norm.norm() - 1.0).abs() < EPS);
+    assert!(bulb.hit(&ray([0.0, 0.0, 3.0], [1.0, 0.0, 0.0])).is_none());
+}
+
+#[test]
+fn maps() {
+    let map = Linear::from(Matrix3::new(1.0, 2.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 2.0))
+        .chain(Scale::from(2.0))
+        .chain(Shift::from(Vector3::new(1.0, 2.0, 3.0)));
+    let v = Vector3::new(0.5, -1.0, 2.0);
+    assert_close(&map.abs(&v), &Vector3::new(-2.0, 0.0, 11.0));
+    assert_close(&map.rel(&v), &Vector3::new(-3.0, -2.0, 8.0));
+    assert_close(&map.abs_inv(&map.abs(&v)), &v);
+    assert_close(&map.rel_inv(&map.rel(&v)), &v);
+
+    // Mapped normal stays orthogonal to mapped tangents
+    let (n, t) = (Vector3::new(1.0, 1.0, 0.0), Vector3::new(1.0, -1.0, 3.0));
+    assert!(map.norm(&n).dot(&map.rel(&t)).abs() < EPS);
+
+    // Rotation keeps normals orthogonal without the inverse
+    let rot = Rotation3::from_axis_angle(&Vector3::z_axis(), 0.5 * std::f64::consts::PI);
+    let quat = UnitQuaternion::from_rotation_matrix(&rot);
+    for map in [Rotation::from(rot), Rotation::from(quat)].iter() {
+        assert_close(&map.abs(&Vector3::x()), &Vector3::y());
+        assert_close(&map.rel_inv(&Vector3::y()), &Vector3::x());
+        assert_close(&map.norm(&Vector3::x()), &Vector3::y());
+    }
+
+    let map = Scale3::from(Vector3::new(2.0, 1.0, 0.5)).chain(Rotation::from(rot));
+    assert_close(&map.abs(&v), &Vector3::new(1.0, 1.0, 1.0));
+    assert_close(&map.abs_inv(&map.abs(&v)), &v);
+    assert!(map.norm(&n).dot(&map.rel(&t)).abs() < EPS);
+
+    // Singular matrices are rejected instead of panicking on packing
+    let flat = Matrix3::from_diagonal(&Vector3::new(1.0, 1.0, 0.0));
+    assert!(Linear::try_new(flat).is_err());
+    assert!(Linear::try_new(Matrix3::identity()).is_ok());
+    assert!(Linear::try_new(Matrix3::repeat(std::f64::NAN)).is_err());
+    assert!(Linear::try_new(1e-40 * Matrix3::identity()).is_err());
+    assert!(Scale3::try_new(Vector3::new(2.0, 1.0, 0.5)).is_ok());
+    assert!(Scale3::try_new(Vector3::new(2.0, 0.0, 0.5)).is_err());
+    assert!(Scale3::try_new(Vector3::new(2.0, std::f64::INFINITY, 0.5)).is_err());
+    assert!(Ellipsoid::try_new(flat, Vector3::zeros()).is_err());
+    assert!(Parallelepiped::try_new(flat, Vector3::zeros()).is_err());
+    assert!(Parallelepiped::try_new(Matrix3::identity(), Vector3::zeros()).is_ok());
+    assert!(Cylinder::try_new(flat, Vector3::zeros()).is_err());
+    assert!(Cylinder::try_new_open(flat, Vector3::zeros()).is_err());
+    assert!(Cone::try_new(flat, Vector3::zeros()).is_err());
+    assert!(Torus::try_new(0.5, flat, Vector3::zeros()).is_err());
+    assert!(Torus::try_new(0.0, Matrix3::identity(), Vector3::zeros()).is_err());
+    assert!(Torus::try_new(0.5, Matrix3::identity(), Vector3::zeros()).is_ok());
+    assert!(Plane::try_new(Vector3::zeros(), Vector3::zeros()).is_err());
+    assert!(Plane::try_new(Vector3::zeros(), Vector3::z()).is_ok());
+    assert!(Disk::try_new(1.0, Vector3::zeros(), Vector3::zeros()).is_err());
+    assert!(Disk::try_new(0.0, Vector3::zeros(), Vector3::z()).is_err());
+    assert!(Disk::try_new(1.0, Vector3::zeros(), Vector3::z()).is_ok());
+    assert!(Capsule::try_new(0.0, Vector3::zeros(), Vector3::z()).is_err());
+    let mut buffer_float = vec![0.0; Linear::size_float()];
+    Linear::from(flat).pack_to(&mut [], &mut buffer_float);
+    assert!(buffer_float[9..].iter().all(|x| x.is_nan()));
+
+    // Matrices are packed by rows as `matrix3_load` reads them
+    let shear = Matrix3::new(1.0, 2.0, 3.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0);
+    let mut buffer_float = vec![0.0; Linear::size_float()];
+    Linear::from(shear).pack_to(&mut [], &mut buffer_float);
+    assert_eq!(&buffer_float[0..3], &[1.0, 2.0, 3.0]);
+    assert_eq!(&buffer_float[9..12], &[1.0, -2.0, -3.0]);
+    let mut buffer_float = vec![0.0; Rotation::size_float()];
+    Rotation::from(rot).pack_to(&mut [], &mut buffer_float);
+    assert!((buffer_float[1] + 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn materials() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut color = Vector3::zeros();
+    let norm = Vector3::new(0.0, 0.0, 1.0);
+    let pos = Vector3::zeros();
+    let r = ray([0.0, -1.0, 1.0], [0.0, 1.0, -1.0]);
+
+    let new_ray = Reflective {}
+        .bounce(&mut rng, &r, &pos, &norm, &mut color)
+        .unwrap();
+    assert_close(&new_ray.dir, &Vector3::new(0.0, 1.0, 1.0).normalize());
+
+    for _ in 0..64 {
+        let new_ray = Diffuse {}
+            .bounce(&mut rng, &r, &pos, &norm, &mut color)
+            .unwrap();
+        assert!((new_ray.dir.norm() - 1.0).abs() < EPS);
+        assert!(new_ray.dir.dot(&norm) >= 0.0);
+    }
+
+    // Beyond the critical angle the light is totally reflected
+    let glass = Refractive::new(1.5);
+    let inner = ray([0.0, -1.0, -1.0], [0.0, 1.0, 1.0]);
+    for _ in 0..64 {
+        let new_ray = glass
+            .bounce(&mut rng, &inner, &pos, &norm, &mut color)
+            .unwrap();
+        assert_close(&new_ray.dir, &Vector3::new(0.0, 1.0, -1.0).normalize());
+    }
+    // Normal incidence passes straight through
+    let straight = ray([0.0, 0.0, 1.0], [0.0, 0.0, -1.0]);
+    let reflectance = fresnel_reflectance(Fresnel::Exact, 1.0 / 1.5, 1.0, 1.0);
+    assert!((reflectance - 0.04).abs() < EPS);
+
+    let mut passed = 0;
+    for _ in 0..1000 {
+        let new_ray = glass
+            .bounce(&mut rng, &straight, &pos, &norm, &mut color)
+            .unwrap();
+        if new_ray.dir.z < 0.0 {
+            assert_close(&new_ray.dir, &straight.dir);
+            passed += 1;
+        }
+    }
+    assert!(passed > 900);
+    assert_close(&color, &Vector3::zeros());
+
+    let light = Luminous {}.color_with(Vector3::new(1.0, 0.5, 0.25));
+    assert!(light
+        .bounce(&mut rng, &r, &pos, &norm, &mut color)
+        .is_none());
+    assert_close(&color, &Vector3::new(1.0, 0.5, 0.25));
+}
+
+#[test]
+fn scene_trace() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let bg = Vector3::new(0.1, 0.2, 0.3);
+    let mut scene = ListScene::new(ConstantBackground::new(bg));
+    scene.add(
+        Sphere::new(1.0, Vector3::new(0.0, 5.0, 0.0))
+            .cover(Luminous {}.color_with(Vector3::new(1.0, 1.0, 0.0))),
+    );
+
+    let color = scene.trace(&mut rng, ray([0.0, 0.0, 0.0], [0.0, 1.0, 0.0]));
+    assert_close(&color, &Vector3::new(1.0, 1.0, 0.0));
+    let color = scene.trace(&mut rng, ray([0.0, 0.0, 0.0], [0.0, -1.0, 0.0]));
+    assert_close(&color, &bg);
+}
+
+#[test]
+fn list_scene_edit() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let bg = Vector3::new(0.1, 0.2, 0.3);
+    let mut scene = ListScene::new(ConstantBackground::new(bg));
+    for &(x, y) in [(0.0, 5.0), (0.0, -5.0), (5.0, 0.0)].iter() {
+        scene.add(
+            Sphere::new(1.0, Vector3::new(x, y, 0.0))
+                .cover(Luminous {}.color_with(Vector3::new(1.0, 1.0, 0.0))),
+        );
+    }
+    assert_eq!(scene.iter().count(), 3);
+
+    // Moved object is hit at its new position
+    scene.get_mut(0).unwrap().shape = Sphere::new(1.0, Vector3::new(0.0, 0.0, 5.0));
+    let color = scene.trace(&mut rng, ray([0.0, 0.0, 0.0], [0.0, 0.0, 1.0]));
+    assert_close(&color, &Vector3::new(1.0, 1.0, 0.0));
+    let color = scene.trace(&mut rng, ray([0.0, 0.0, 0.0], [0.0, 1.0, 0.0]));
+    assert_close(&color, &bg);
+    assert!(scene.get_mut(3).is_none());
+
+    scene.remove(1);
+    assert_eq!(scene.objects().len(), 2);
+    let color = scene.trace(&mut rng, ray([0.0, 0.0, 0.0], [0.0, -1.0, 0.0]));
+    assert_close(&color, &bg);
+    let color = scene.trace(&mut rng, ray([0.0, 0.0, 0.0], [1.0, 0.0, 0.0]));
+    assert_close(&color, &Vector3::new(1.0, 1.0, 0.0));
+
+    scene.clear();
+    assert_eq!(scene.iter().count(), 0);
+    let color = scene.trace(&mut rng, ray([0.0, 0.0, 0.0], [1.0, 0.0, 0.0]));
+    assert_close(&color, &bg);
+}
+
+#[test]
+fn glass_sphere() {
+    // Light passing through the sphere centrally keeps its direction
+    // and is only attenuated by the reflection on the surfaces
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut scene = ListScene::new(GradientBackground::new(
+        Vector3::new(1.0, 1.0, 1.0),
+        Vector3::zeros(),
+        Vector3::new(0.0, 1.0, 0.0),
+    ));
+    scene.set_max_depth(8);
+    scene.add(Sphere::new(1.0, Vector3::zeros()).cover(Refractive::new(1.5)));
+
+    let n = 4000;
+    let mut sum = Vector3::zeros();
+    for _ in 0..n {
+        sum += scene.trace(&mut rng, ray([0.0, -5.0, 0.0], [0.0, 1.0, 0.0]));
+    }
+    let mean = sum / n as f64;
+    // Both reflections are 4%, multiple internal reflections are neglected
+    let expected = 0.96 * 0.96;
+    assert!((mean.x - expected).abs() < 0.02, "{}", mean.x);
+}
+
+#[test]
+fn thin_lens() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut view = ProjectionView::new(Vector3::new(1.0, 2.0, 3.0), Rotation3::identity());
+    view.set_lens(0.1, 4.0);
+    let (mut min, mut max) = (Vector3::repeat(1.0), Vector3::repeat(-1.0));
+    for _ in 0..256 {
+        let r = view.emit(&mut rng, (3, 0), (4, 4));
+        let offset = r.start - view.pos;
+        assert!(offset.z.abs() < EPS && offset.norm() <= 0.1 + EPS);
+        min = min.zip_map(&offset, f64::min);
+        max = max.zip_map(&offset, f64::max);
+        // Rays through the pixel converge at its footprint on the focal plane
+        let p = (r.start + r.dir * (4.0 / -r.dir.z) - view.pos) / 4.0;
+        assert!(p.x > 0.125 - EPS && p.x < 0.375 + EPS, "{}", p.x);
+        assert!(p.y > 0.375 - EPS && p.y < 0.625 + EPS, "{}", p.y);
+    }
+    assert!(min.x < -0.05 && max.x > 0.05 && min.y < -0.05 && max.y > 0.05);
+
+    // Hexagonal aperture
+    let step = std::f64::consts::PI / 3.0;
+    for _ in 0..256 {
+        let (x, y) = lens_sample(&mut rng, 6, 0.0);
+        for k in 0..6 {
+            let phi = (k as f64 + 0.5) * step;
+            assert!(x * phi.cos() + y * phi.sin() <= (0.5 * step).cos() + EPS);
+        }
+    }
+}
+
+#[test]
+fn orthographic() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let ori = Rotation3::face_towards(&-Vector3::y(), &Vector3::z());
+    let mut view = OrthographicView::new(Vector3::zeros(), Rotation3::identity(), 2.0);
+    view.update(Vector3::new(0.0, -5.0, 0.0), ori);
+    for _ in 0..64 {
+        // Parallel rays start at the pixel footprint in the view plane
+        let r = view.emit(&mut rng, (0, 0), (8, 4));
+        assert_close(&r.dir, &Vector3::new(0.0, 1.0, 0.0));
+        assert!((r.start.y + 5.0).abs() < EPS);
+        assert!(r.start.x > -2.25 - EPS && r.start.x < -1.75 + EPS);
+        assert!(r.start.z > 0.75 - EPS && r.start.z < 1.25 + EPS);
+    }
+}
+
+#[test]
+fn panoramic() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let view = PanoramicView::new(Vector3::zeros(), Rotation3::identity());
+    for _ in 0..64 {
+        let r = view.emit(&mut rng, (4, 2), (8, 4));
+        assert!((r.dir.norm() - 1.0).abs() < EPS);
+        assert!(r.dir.z < 0.0 && r.dir.x >= 0.0 && r.dir.y <= 0.0);
+        assert!(r.dir.x <= -r.dir.z + EPS && -r.dir.y <= r.dir.x.hypot(r.dir.z) + EPS);
+        // The leftmost column looks backwards and the top row looks up
+        assert!(view.emit(&mut rng, (0, 1), (8, 4)).dir.z > 0.0);
+        assert!(view.emit(&mut rng, (3, 0), (8, 4)).dir.y > 0.7);
+    }
+}
+
+#[test]
+fn fisheye() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut view = FisheyeView::new(Vector3::zeros(), Rotation3::identity());
+    for &mapping in [FisheyeMapping::Equidistant, FisheyeMapping::Equisolid].iter() {
+        view.mapping = mapping;
+        for _ in 0..64 {
+            let r = view.emit(&mut rng, (5, 2), (8, 4));
+            assert!((r.dir.norm() - 1.0).abs() < EPS);
+            assert_close(&r.color, &Vector3::new(1.0, 1.0, 1.0));
+            // Restore the image point from the angle to the view direction
+            let theta = (-r.dir.z).acos();
+            let dist = match mapping {
+                FisheyeMapping::Equidistant => theta / (0.5 * view.field),
+                FisheyeMapping::Equisolid => (0.5 * theta).sin() / (0.25 * view.field).sin(),
+            };
+            let x = dist * r.dir.x / r.dir.x.hypot(r.dir.y);
+            assert!(x > 0.25 - EPS && x < 0.75 + EPS, "{}", x);
+        }
+        // Corners of the image are outside of the circle
+        assert_close(
+            &view.emit(&mut rng, (0, 0), (8, 4)).color,
+            &Vector3::zeros(),
+        );
+    }
+}
+
+#[test]
+fn stereo() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let center = ProjectionView::new(Vector3::zeros(), Rotation3::identity());
+    let mut view = StereoView::new(center, 0.2, 4.0);
+    for &layout in [StereoLayout::SideBySide, StereoLayout::TopBottom].iter() {
+        view.layout = layout;
+        let (size, left, right) = match layout {
+            StereoLayout::SideBySide => ((8, 4), (1, 1), (5, 1)),
+            StereoLayout::TopBottom => ((4, 8), (1, 1), (1, 5)),
+        };
+        for _ in 0..64 {
+            let (l, r) = (
+                view.emit(&mut rng, left, size),
+                view.emit(&mut rng, right, size),
+            );
+            assert_close(&l.start, &Vector3::new(-0.1, 0.0, 0.0));
+            assert_close(&r.start, &Vector3::new(0.1, 0.0, 0.0));
+            // The same pixels of both eyes cover the same area of the convergence plane
+            for p in [
+                l.start + l.dir * (4.0 / -l.dir.z),
+                r.start + r.dir * (4.0 / -r.dir.z),
+            ]
+            .iter()
+            {
+                assert!(p.x > -1.5 - EPS && p.x < -0.5 + EPS, "{}", p.x);
+                assert!(p.y > 0.5 - EPS && p.y < 1.5 + EPS, "{}", p.y);
+            }
+        }
+    }
+}
+
+#[test]
+fn shutter() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let ori = Rotation3::from_axis_angle(&Vector3::x_axis(), 0.5);
+    let end_ori = Rotation3::from_axis_angle(&Vector3::y_axis(), 1.0) * ori;
+    let mut shutter = Shutter::new(Vector3::new(2.0, 0.0, 0.0), end_ori);
+    let (pos, rot) = shutter.frame_at(&Vector3::zeros(), &ori, 0.0);
+    assert_close(&pos, &Vector3::zeros());
+    assert!(rot.angle_to(&ori) < EPS);
+    let (pos, rot) = shutter.frame_at(&Vector3::zeros(), &ori, 1.0);
+    assert_close(&pos, &Vector3::new(2.0, 0.0, 0.0));
+    assert!(rot.angle_to(&end_ori) < 1e-6);
+    let (_, rot) = shutter.frame_at(&Vector3::zeros(), &ori, 0.5);
+    assert!((rot.angle_to(&ori) - 0.5).abs() < 1e-6);
+
+    // Rays are emitted from the positions along the path within the shutter interval
+    shutter.open = 0.25;
+    shutter.close = 0.75;
+    let mut view = ProjectionView::new(Vector3::zeros(), ori);
+    view.shutter = Some(shutter);
+    let (mut min, mut max) = (std::f64::INFINITY, -std::f64::INFINITY);
+    for _ in 0..256 {
+        let r = view.emit(&mut rng, (0, 0), (4, 4));
+        assert!(r.start.y.abs() < EPS && r.start.z.abs() < EPS);
+        min = min.min(r.start.x);
+        max = max.max(r.start.x);
+    }
+    assert!((0.5 - EPS..0.55).contains(&min));
+    assert!(max > 1.45 && max <= 1.5 + EPS);
+
+    let mut view = OrthographicView::new(Vector3::zeros(), ori, 1.0);
+    let mut shutter = Shutter::new(Vector3::zeros(), Rotation3::identity());
+    shutter.open = 0.5;
+    shutter.close = 0.5;
+    view.shutter = Some(shutter);
+    let r = view.emit(&mut rng, (2, 2), (4, 4));
+    assert!(r.start.norm() < 0.2);
+    let dir = Rotation3::from_axis_angle(&Vector3::x_axis(), 0.25) * -Vector3::z();
+    assert_close(&r.dir, &dir);
+}
+
+#[test]
+fn render_background() {
+    let bg = Vector3::new(0.5, 0.25, 1.0);
+    let scene: ListScene<Covered<Sphere, Diffuse>, _> = ListScene::new(ConstantBackground::new(bg));
+    let view = ProjectionView::new(Vector3::zeros(), Rotation3::identity());
+    let data = render(&scene, &view, (8, 4), 2, 0);
+    assert_eq!(data.len(), 3 * 8 * 4);
+    for px in data.chunks(3) {
+        assert!((px[0] - 0.5).abs() < 1e-6);
+        assert!((px[1] - 0.25).abs() < 1e-6);
+        assert!((px[2] - 1.0).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn russian_roulette() {
+    // Roulette terminates half of the paths but the mean color stays the same
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut scene = ListScene::new(ConstantBackground::new(Vector3::new(1.0, 1.0, 1.0)));
+    scene.set_russian_roulette(Some(RussianRoulette::new(1, 0.05)));
+    scene.add(
+        Sphere::new(1.0, Vector3::zeros())
+            .cover(Diffuse {}.color_with(Vector3::new(0.5, 0.5, 0.5))),
+    );
+
+    let n = 4000;
+    let (mut sum, mut terminated) = (0.0, 0);
+    for _ in 0..n {
+        let color = scene.trace(&mut rng, ray([0.0, -5.0, 0.0], [0.0, 1.0, 0.0]));
+        sum += color.x;
+        if color.x == 0.0 {
+            terminated += 1;
+        }
+    }
+    assert!((sum / n as f64 - 0.5).abs() < 0.05, "{}", sum / n as f64);
+    assert!((terminated as f64 / n as f64 - 0.5).abs() < 0.05);
+}
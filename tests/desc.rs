@@ -0,0 +1,134 @@
+use clay::desc::*;
+use std::{env, fs, path::PathBuf, process};
+
+/// Scene that contains every shape of the description.
+const SCENE_RON: &str = r#"(
+    background: Constant(color: (1.0, 1.0, 1.0)),
+    view: (pos: (0.0, -5.0, 1.0), dir: (0.0, 1.0, 0.0)),
+    objects: [
+        (shape: Sphere(radius: 0.5, pos: (0.0, 0.0, 0.5)), material: Diffuse(color: (0.9, 0.3, 0.3))),
+        (shape: Ellipsoid(transform: (scale: (0.4, 0.3, 0.2))), material: Refractive(ior: 1.5)),
+        (shape: Parallelepiped(transform: (pos: (0.0, 0.0, -0.5), scale: (5.0, 5.0, 0.5))), material: Glossy(reflect: 0.1, color: (0.9, 0.9, 0.9))),
+        (shape: Triangle(vertices: ((0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0))), material: Reflective(color: (1.0, 1.0, 1.0))),
+        (shape: Mesh(path: "cube.obj"), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (shape: Plane(pos: (0.0, 0.0, -1.0), norm: (0.0, 0.0, 1.0)), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (shape: Disk(radius: 0.5, pos: (0.0, 0.0, 3.0), norm: (0.0, 0.0, -1.0)), material: Luminous(color: (10.0, 10.0, 10.0)), target: true),
+        (shape: Cylinder(transform: (scale: (0.2, 0.2, 1.0))), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (shape: Cylinder(transform: (scale: (0.3, 0.3, 1.0)), open: true), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (shape: Cone(transform: (pos: (1.0, 0.0, 1.0))), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (shape: Capsule(radius: 0.1, a: (0.0, 0.0, 0.0), b: (1.0, 1.0, 1.0)), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (shape: Torus(minor: 0.25, transform: (rotation: Some((axis: (1.0, 0.0, 0.0), angle: 0.5)))), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (shape: TriangleMesh(path: "cube.obj", transform: (pos: (2.0, 0.0, 0.0))), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (shape: Heightfield(path: "field.pgm", transform: (scale: (4.0, 4.0, 0.5))), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (
+            shape: Csg(
+                op: Difference,
+                first: Parallelepiped(transform: (scale: (0.5, 0.5, 0.5))),
+                second: Sphere(radius: 0.6, pos: (0.0, 0.0, 0.0)),
+            ),
+            material: Diffuse(color: (0.5, 0.5, 0.5)),
+        ),
+        (shape: Csg(op: Union, first: Cone(transform: ()), second: Capsule(radius: 0.1, a: (0.0, 0.0, 0.0), b: (0.0, 0.0, 2.0))), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (shape: Csg(op: Intersection, first: Cylinder(transform: ()), second: Ellipsoid(transform: ())), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (shape: Sdf(Sphere(radius: 0.5, pos: (0.0, 0.0, 2.0))), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (shape: Sdf(Box(half_size: (0.5, 0.2, 0.1), pos: (0.0, 0.0, 2.0))), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (shape: Sdf(Torus(major: 0.5, minor: 0.1, pos: (0.0, 0.0, 2.0))), material: Diffuse(color: (0.5, 0.5, 0.5))),
+        (shape: Sdf(Mandelbulb(power: 8.0, iterations: 6)), material: Diffuse(color: (0.5, 0.5, 0.5))),
+    ],
+)"#;
+
+/// Number of objects built from the scene, the mesh is split into its 12 triangles.
+const OBJECT_COUNT: usize = 21 - 1 + 12;
+
+const CUBE_OBJ: &str = "\
+    v -1 -1 -1\nv 1 -1 -1\nv 1 1 -1\nv -1 1 -1\n\
+    v -1 -1 1\nv 1 -1 1\nv 1 1 1\nv -1 1 1\n\
+    f 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\nf 2 3 7 6\nf 3 4 8 7\nf 4 1 5 8\n";
+
+const FIELD_PGM: &str = "P2 3 3 4\n0 1 2\n1 2 3\n2 3 4\n";
+
+/// Creates the directory with the files referenced by the scene.
+fn files_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("clay-desc-{}-{}", name, process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("cube.obj"), CUBE_OBJ).unwrap();
+    fs::write(dir.join("field.pgm"), FIELD_PGM).unwrap();
+    dir
+}
+
+fn check_build(mut desc: SceneDesc, name: &str) {
+    let dir = files_dir(name);
+    desc.base_dir = dir.clone();
+    let objects = desc.build_objects().unwrap();
+    assert_eq!(objects.len(), OBJECT_COUNT);
+    assert_eq!(objects.iter().filter(|(_, target)| *target).count(), 1);
+    desc.build_bvh_scene().unwrap();
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn ron() {
+    let desc = SceneDesc::from_ron(SCENE_RON).unwrap();
+    let text = ron::ser::to_string(&desc).unwrap();
+    let parsed = SceneDesc::from_ron(&text).unwrap();
+    assert_eq!(format!("{:?}", parsed), format!("{:?}", desc));
+    check_build(parsed, "ron");
+}
+
+#[test]
+fn json() {
+    let desc = SceneDesc::from_ron(SCENE_RON).unwrap();
+    let text = serde_json::to_string_pretty(&desc).unwrap();
+    let parsed = SceneDesc::from_json(&text).unwrap();
+    assert_eq!(format!("{:?}", parsed), format!("{:?}", desc));
+    check_build(parsed, "json");
+}
+
+#[test]
+fn csg_operands() {
+    let build = |text: &str| {
+        let shape: ShapeDesc = ron::de::from_str(text).unwrap();
+        shape.build(&PathBuf::new())
+    };
+    assert!(build(
+        "Csg(op: Union, first: Cone(transform: ()), second: Torus(minor: 0.5, transform: ()))"
+    )
+    .is_err());
+    assert!(build(
+        "Csg(op: Union, first: Cylinder(transform: (), open: true), second: Cone(transform: ()))"
+    )
+    .is_err());
+    assert!(build("Csg(op: Union, first: Plane(pos: (0.0, 0.0, 0.0), norm: (0.0, 0.0, 1.0)), second: Cone(transform: ()))").is_err());
+    assert_eq!(
+        build("Csg(op: Union, first: Cone(transform: ()), second: Cone(transform: ()))")
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn degenerate_shapes() {
+    // Degenerate shapes from the file are reported instead of rendering NaN
+    let build = |text: &str| {
+        let shape: ShapeDesc = ron::de::from_str(text).unwrap();
+        shape.build(&PathBuf::new())
+    };
+    for text in [
+        "Ellipsoid(transform: (scale: (1.0, 0.0, 1.0)))",
+        "Parallelepiped(transform: (scale: (1.0, 1.0, 0.0)))",
+        "Cylinder(transform: (scale: (0.0, 1.0, 1.0)))",
+        "Cylinder(transform: (scale: (0.0, 1.0, 1.0)), open: true)",
+        "Cone(transform: (scale: (1.0, 1.0, 0.0)))",
+        "Capsule(radius: 0.0, a: (0.0, 0.0, 0.0), b: (0.0, 0.0, 1.0))",
+        "Torus(minor: 0.25, transform: (scale: (1.0, 0.0, 1.0)))",
+        "Torus(minor: 0.0, transform: ())",
+        "Plane(pos: (0.0, 0.0, 0.0), norm: (0.0, 0.0, 0.0))",
+        "Disk(radius: 0.5, pos: (0.0, 0.0, 0.0), norm: (0.0, 0.0, 0.0))",
+        "Disk(radius: 0.0, pos: (0.0, 0.0, 0.0), norm: (0.0, 0.0, 1.0))",
+    ]
+    .iter()
+    {
+        assert!(build(text).is_err(), "{} is built", text);
+    }
+}
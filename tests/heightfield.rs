@@ -0,0 +1,79 @@
+use clay::{prelude::*, process::write_png, shape::*};
+use std::io::Cursor;
+
+const EPS: f64 = 1e-6;
+
+#[test]
+fn read_pgm() {
+    let text = "P2\n# comment\n3 2\n4\n0 1 2\n4 3 2\n";
+    let field = Heightfield::read_pgm(text.as_bytes()).unwrap();
+    assert_eq!(field.dims(), (3, 2));
+    // The top row of the image is the far edge of the field
+    let expected = [1.0, 0.75, 0.5, 0.0, 0.25, 0.5];
+    for (h, e) in field.heights().iter().zip(expected.iter()) {
+        assert!((h - e).abs() < EPS, "{} != {}", h, e);
+    }
+
+    let mut binary = b"P5 2 2 65535\n".to_vec();
+    binary.extend_from_slice(&[0x00, 0x00, 0xff, 0xff, 0x80, 0x00, 0x00, 0x00]);
+    let field = Heightfield::read_pgm(binary.as_slice()).unwrap();
+    assert!((field.height(0, 1) - 0.0).abs() < EPS);
+    assert!((field.height(1, 1) - 1.0).abs() < EPS);
+    assert!((field.height(0, 0) - 32768.0 / 65535.0).abs() < EPS);
+
+    assert!(Heightfield::read_pgm("P3\n2 2\n255\n".as_bytes()).is_err());
+    assert!(Heightfield::read_pgm("P2\n2 2\n255\n0 0 0".as_bytes()).is_err());
+    // The sizes whose product overflows are rejected before reading the data
+    assert!(Heightfield::read_pgm("P5 4294967296 4294967296 255\n".as_bytes()).is_err());
+    assert!(Heightfield::read_pgm("P5 4294967296 2147483648 65535\n".as_bytes()).is_err());
+    assert!(Heightfield::read_pgm("P2 4294967296 4294967296 255\n0".as_bytes()).is_err());
+}
+
+#[test]
+fn read_png() {
+    let mut data = Vec::new();
+    let rgb = [0, 0, 0, 255, 255, 255, 51, 51, 51, 0, 0, 255];
+    write_png(&mut data, (2, 2), &rgb).unwrap();
+    let field = Heightfield::read_png(Cursor::new(data)).unwrap();
+    assert_eq!(field.dims(), (2, 2));
+    let expected = [0.2, 1.0 / 3.0, 0.0, 1.0];
+    for (h, e) in field.heights().iter().zip(expected.iter()) {
+        assert!((h - e).abs() < EPS, "{} != {}", h, e);
+    }
+}
+
+#[test]
+fn resample() {
+    let n = HEIGHTFIELD_MAX_SIZE + 1;
+    let mut text = format!("P2 {} 2 {}\n", n, n - 1);
+    for _ in 0..2 {
+        for i in 0..n {
+            text += &format!("{} ", i);
+        }
+    }
+    let field = Heightfield::read_pgm(text.as_bytes()).unwrap();
+    assert_eq!(field.dims(), (HEIGHTFIELD_MAX_SIZE, 2));
+    let last = HEIGHTFIELD_MAX_SIZE - 1;
+    assert!((field.height(last, 0) - 1.0).abs() < EPS);
+    let mid = (last / 2) as f64 / last as f64;
+    assert!((field.height(last / 2, 1) - mid).abs() < EPS);
+}
+
+#[test]
+fn sized() {
+    type SmallField = SizedHeightfield<8>;
+    assert_eq!(SmallField::size_float(), 1 + 8 * 8);
+    assert!(SmallField::from_fn((8, 8), |x, _| x + 1.0).is_ok());
+    assert!(SmallField::from_fn((9, 8), |x, _| x + 1.0).is_err());
+
+    // Larger images are resampled to the grid size
+    let mut text = "P2 16 2 15\n".to_string();
+    for _ in 0..2 {
+        for i in 0..16 {
+            text += &format!("{} ", i);
+        }
+    }
+    let field = SmallField::read_pgm(text.as_bytes()).unwrap();
+    assert_eq!(field.dims(), (8, 2));
+    assert!((field.height(7, 0) - 1.0).abs() < EPS);
+}
@@ -0,0 +1,158 @@
+use clay::process::{write_exr, write_pfm, write_png, write_ppm, ImageFormat};
+use std::{convert::TryInto, io::Cursor};
+
+const DIMS: (usize, usize) = (3, 2);
+
+fn rgb_ldr() -> Vec<u8> {
+    (0..(3 * DIMS.0 * DIMS.1)).map(|i| (40 * i) as u8).collect()
+}
+
+fn rgb_hdr() -> Vec<f32> {
+    (0..(3 * DIMS.0 * DIMS.1))
+        .map(|i| 0.5 * i as f32 - 1.0)
+        .collect()
+}
+
+fn i32_at(data: &[u8], pos: usize) -> i32 {
+    i32::from_le_bytes(data[pos..(pos + 4)].try_into().unwrap())
+}
+
+fn f32_at(data: &[u8], pos: usize) -> f32 {
+    f32::from_le_bytes(data[pos..(pos + 4)].try_into().unwrap())
+}
+
+/// Reads null-terminated string and moves the position after it.
+fn read_str(data: &[u8], pos: &mut usize) -> String {
+    let end = *pos + data[*pos..].iter().position(|&b| b == 0).unwrap();
+    let s = String::from_utf8(data[*pos..end].to_vec()).unwrap();
+    *pos = end + 1;
+    s
+}
+
+#[test]
+fn image_format() {
+    assert_eq!(ImageFormat::from_path("a/b.PNG"), Some(ImageFormat::Png));
+    assert_eq!(ImageFormat::from_path("b.ppm"), Some(ImageFormat::Ppm));
+    assert_eq!(ImageFormat::from_path("b.pfm"), Some(ImageFormat::Pfm));
+    assert_eq!(ImageFormat::from_path("b.exr"), Some(ImageFormat::Exr));
+    assert_eq!(ImageFormat::from_path("b.jpg"), None);
+    assert_eq!(ImageFormat::from_path("b"), None);
+}
+
+#[test]
+fn png() {
+    let rgb = rgb_ldr();
+    let mut data = Vec::new();
+    write_png(&mut data, DIMS, &rgb).unwrap();
+
+    let (info, mut reader) = png::Decoder::new(Cursor::new(data)).read_info().unwrap();
+    assert_eq!((info.width, info.height), (DIMS.0 as u32, DIMS.1 as u32));
+    assert_eq!(info.color_type, png::ColorType::RGB);
+    let mut image = vec![0; info.buffer_size()];
+    reader.next_frame(&mut image).unwrap();
+    assert_eq!(image, rgb);
+}
+
+#[test]
+fn ppm() {
+    let rgb = rgb_ldr();
+    let mut data = Vec::new();
+    write_ppm(&mut data, DIMS, &rgb).unwrap();
+
+    let header = b"P6\n3 2\n255\n";
+    assert_eq!(&data[..header.len()], header);
+    assert_eq!(&data[header.len()..], rgb.as_slice());
+}
+
+#[test]
+fn pfm() {
+    let rgb = rgb_hdr();
+    let mut data = Vec::new();
+    write_pfm(&mut data, DIMS, &rgb).unwrap();
+
+    let header = b"PF\n3 2\n-1.0\n";
+    assert_eq!(&data[..header.len()], header);
+    let values = data[header.len()..]
+        .chunks(4)
+        .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+        .collect::<Vec<_>>();
+    // Rows are stored from bottom to top
+    let width = 3 * DIMS.0;
+    assert_eq!(&values[..width], &rgb[width..]);
+    assert_eq!(&values[width..], &rgb[..width]);
+}
+
+#[test]
+fn exr() {
+    let rgb = rgb_hdr();
+    let mut data = Vec::new();
+    write_exr(&mut data, DIMS, &rgb).unwrap();
+
+    assert_eq!(&data[..4], &[0x76, 0x2f, 0x31, 0x01]);
+    assert_eq!(i32_at(&data, 4), 2);
+
+    let mut pos = 8;
+    let mut names = Vec::new();
+    loop {
+        let name = read_str(&data, &mut pos);
+        if name.is_empty() {
+            break;
+        }
+        let kind = read_str(&data, &mut pos);
+        let size = i32_at(&data, pos) as usize;
+        let value = &data[(pos + 4)..(pos + 4 + size)];
+        match name.as_str() {
+            "channels" => {
+                assert_eq!(kind, "chlist");
+                let mut p = 0;
+                for expected in ["B", "G", "R"].iter() {
+                    assert_eq!(&read_str(value, &mut p), expected);
+                    // Float pixel type and no subsampling
+                    assert_eq!(i32_at(value, p), 2);
+                    assert_eq!((i32_at(value, p + 8), i32_at(value, p + 12)), (1, 1));
+                    p += 16;
+                }
+                assert_eq!(&value[p..], &[0]);
+            }
+            "compression" => assert_eq!(value, &[0]),
+            "dataWindow" | "displayWindow" => {
+                let window = (0..4).map(|i| i32_at(value, 4 * i)).collect::<Vec<_>>();
+                assert_eq!(window, vec![0, 0, 2, 1]);
+            }
+            "lineOrder" => assert_eq!(value, &[0]),
+            _ => (),
+        }
+        names.push(name);
+        pos += 4 + size;
+    }
+    for required in [
+        "channels",
+        "compression",
+        "dataWindow",
+        "displayWindow",
+        "lineOrder",
+        "pixelAspectRatio",
+        "screenWindowCenter",
+        "screenWindowWidth",
+    ]
+    .iter()
+    {
+        assert!(names.iter().any(|n| n == required), "no {}", required);
+    }
+
+    let line_size = 4 * 3 * DIMS.0;
+    for y in 0..DIMS.1 {
+        let offset = u64::from_le_bytes(data[(pos + 8 * y)..][..8].try_into().unwrap()) as usize;
+        assert_eq!(i32_at(&data, offset) as usize, y);
+        assert_eq!(i32_at(&data, offset + 4) as usize, line_size);
+        for x in 0..DIMS.0 {
+            let px = &rgb[(3 * (DIMS.0 * y + x))..][..3];
+            for (i, &c) in [2, 1, 0].iter().enumerate() {
+                let value = f32_at(&data, offset + 8 + 4 * (DIMS.0 * i + x));
+                assert_eq!(value, px[c]);
+            }
+        }
+    }
+    let last = pos + 8 * DIMS.1 + DIMS.1 * (8 + line_size);
+    assert_eq!(data.len(), last);
+}
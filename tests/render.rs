@@ -0,0 +1,816 @@
+//! Renders the example scenes on a CPU OpenCL device and compares them with the references.
+
+#[macro_use]
+mod common;
+
+use clay::{
+    material::*,
+    material_combine, material_pdf_combine, material_pdf_select, material_select,
+    object::*,
+    prelude::*,
+    process::{create_default_postproc, create_renderer, render_to_file, Budget},
+    scene::{
+        BvhScene, ConstantBackground as ConstBg, GradientBackground as GradBg, ListScene,
+        MisHeuristic, TargetListScene,
+    },
+    shape::*,
+    shape_select,
+    view::ProjectionView,
+};
+use nalgebra::{Matrix3, Rotation3, Vector3};
+use std::{env, fs, process};
+
+const DIMS: (usize, usize) = (64, 40);
+const PASSES: usize = 64;
+
+shape_select!(MyShape {
+    P(TP=Parallelepiped),
+    S(TS=Ellipsoid),
+});
+material_combine!(Glossy {
+    reflect: Reflective,
+    diffuse: Colored<Diffuse>,
+});
+material_pdf_combine!(Glossy { reflect, diffuse });
+material_combine!(Glowing {
+    diffuse: Reflective,
+    reflect: Colored<Diffuse>,
+    luminous: Colored<Luminous>,
+});
+material_pdf_combine!(Glowing {
+    diffuse,
+    reflect,
+    luminous,
+});
+material_select!(MyMaterial {
+    D(TD=Colored<Diffuse>),
+    R(TR=Glossy),
+    G(TG=Glowing),
+    T(TT=Refractive),
+    L(TL=Colored<Luminous>),
+});
+material_pdf_select!(MyMaterial { D, R, G, T, L });
+
+type MyObject = Covered<MyShape, MyMaterial>;
+
+fn sky() -> GradBg {
+    GradBg::new(
+        Vector3::new(1.0, 1.0, 1.0),
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 1.0),
+    )
+}
+
+fn diffuse(color: Vector3<f64>) -> MyMaterial {
+    MyMaterial::from(Diffuse {}.color_with(color))
+}
+
+fn glossy(reflect: f64, color: Vector3<f64>) -> MyMaterial {
+    MyMaterial::from(Glossy::new(
+        (reflect, Reflective {}),
+        (1.0 - reflect, Diffuse {}.color_with(color)),
+    ))
+}
+
+fn sphere(rad: f64, pos: Vector3<f64>) -> MyShape {
+    MyShape::from(Ellipsoid::new(rad * Matrix3::identity(), pos))
+}
+
+fn cuboid(size: Vector3<f64>, pos: Vector3<f64>) -> MyShape {
+    MyShape::from(Parallelepiped::new(Matrix3::from_diagonal(&size), pos))
+}
+
+fn look(pos: Vector3<f64>, dir: Vector3<f64>) -> ProjectionView {
+    ProjectionView::new(pos, Rotation3::face_towards(&-dir, &Vector3::z_axis()))
+}
+
+#[test]
+fn furnace() {
+    // White diffuse object lit uniformly from all sides is invisible
+    let context = cpu_context_or_skip!();
+    let mut scene = ListScene::new(ConstBg::new(Vector3::new(1.0, 1.0, 1.0)));
+    scene.add(sphere(1.0, Vector3::zeros()).cover(diffuse(Vector3::new(1.0, 1.0, 1.0))));
+    let view = look(Vector3::new(0.0, -3.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+
+    let data = common::render(&context, scene, view, DIMS, 4).unwrap();
+    for px in data.chunks(3) {
+        for &c in px {
+            assert!((c - 1.0).abs() < 1e-3, "pixel color {:?}", px);
+        }
+    }
+}
+
+#[test]
+fn background() {
+    // Rays that don't hit anything get exactly the background color,
+    // and the colors accumulated over the passes are averaged
+    let context = cpu_context_or_skip!();
+    let scene = ListScene::<MyObject, _>::new(ConstBg::new(Vector3::new(0.25, 0.5, 1.0)));
+    let view = look(Vector3::zeros(), Vector3::new(1.0, 0.0, 0.0));
+
+    let data = common::render(&context, scene, view, DIMS, 4).unwrap();
+    for px in data.chunks(3) {
+        assert_eq!(px, &[0.25, 0.5, 1.0]);
+    }
+}
+
+#[test]
+fn render_to_file() {
+    let context = cpu_context_or_skip!();
+    let scene = ListScene::<MyObject, _>::new(ConstBg::new(Vector3::new(0.25, 0.5, 1.0)));
+    let view = look(Vector3::zeros(), Vector3::new(1.0, 0.0, 0.0));
+    let renderer = create_renderer::<_, ProjectionView>()
+        .build(DIMS, scene, view)
+        .unwrap();
+    let (mut worker, _) = renderer.create_worker(&context).unwrap();
+    let (mut postproc, _) = create_default_postproc()
+        .collect()
+        .unwrap()
+        .build_default(&context, DIMS)
+        .unwrap();
+
+    let path = |ext: &str| env::temp_dir().join(format!("clay-{}.{}", process::id(), ext));
+    let mut render = |ext: &str| {
+        render_to_file(
+            path(ext),
+            Budget::Passes(2),
+            &mut worker,
+            &mut postproc,
+            DIMS,
+        )
+        .map(|n| (n, fs::read(path(ext)).unwrap()))
+    };
+
+    // The HDR image is the unfiltered color averaged over the passes
+    let (n, data) = render("pfm").unwrap();
+    assert_eq!(n, 2);
+    let header = format!("PF\n{} {}\n-1.0\n", DIMS.0, DIMS.1);
+    assert_eq!(&data[..header.len()], header.as_bytes());
+    for px in data[header.len()..].chunks(12) {
+        let rgb = px
+            .chunks(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect::<Vec<_>>();
+        assert_eq!(rgb, vec![0.25, 0.5, 1.0]);
+    }
+
+    let (_, data) = render("ppm").unwrap();
+    let header = format!("P6\n{} {}\n255\n", DIMS.0, DIMS.1);
+    assert_eq!(&data[..header.len()], header.as_bytes());
+    assert_eq!(data.len(), header.len() + 3 * DIMS.0 * DIMS.1);
+
+    let (_, data) = render("exr").unwrap();
+    assert_eq!(&data[..4], &[0x76, 0x2f, 0x31, 0x01]);
+
+    assert!(render("jpg").is_err());
+    for ext in ["pfm", "ppm", "exr"].iter() {
+        fs::remove_file(path(ext)).unwrap();
+    }
+}
+
+#[test]
+fn spheres() {
+    let context = cpu_context_or_skip!();
+    let mut scene = ListScene::new(sky());
+    scene.add(
+        sphere(0.75, Vector3::new(-0.75, 0.0, 0.0)).cover(diffuse(Vector3::new(0.4, 1.0, 0.4))),
+    );
+    scene.add(sphere(1.0, Vector3::new(1.0, 0.0, 0.0)).cover(diffuse(Vector3::new(0.4, 0.4, 1.0))));
+    let view = look(Vector3::new(0.25, -3.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+
+    let data = common::render(&context, scene, view, DIMS, PASSES).unwrap();
+    common::check_reference("spheres", &data);
+}
+
+#[test]
+fn shapes() {
+    let context = cpu_context_or_skip!();
+    let mut scene = ListScene::new(sky());
+    scene.add(
+        MyShape::from(Ellipsoid::new(
+            Matrix3::from_diagonal(&Vector3::new(0.8, 0.7, 0.4)),
+            Vector3::new(0.0, 0.0, 0.4),
+        ))
+        .cover(diffuse(Vector3::new(0.9, 0.3, 0.3))),
+    );
+    scene.add(
+        MyShape::from(Ellipsoid::new(
+            Matrix3::from_diagonal(&Vector3::new(0.4, 0.5, 0.2)),
+            Vector3::new(0.0, 0.0, 1.0),
+        ))
+        .cover(diffuse(Vector3::new(0.9, 0.9, 0.9))),
+    );
+    let ori = Rotation3::rotation_between(&Vector3::new(1.0, 1.0, 1.0), &Vector3::z())
+        .unwrap()
+        .matrix()
+        * (0.4 / 3.0f64.sqrt());
+    scene.add(
+        MyShape::from(Parallelepiped::new(ori, Vector3::new(0.0, 0.0, 1.6)))
+            .cover(diffuse(Vector3::new(0.9, 0.3, 0.3))),
+    );
+    scene.add(
+        cuboid(Vector3::new(10.0, 10.0, 0.5), Vector3::new(0.0, 0.0, -0.5))
+            .cover(diffuse(Vector3::new(0.9, 0.9, 0.9))),
+    );
+    let view = look(Vector3::new(2.0, 0.0, 1.0), Vector3::new(-1.0, 0.0, 0.0));
+
+    let data = common::render(&context, scene, view, DIMS, PASSES).unwrap();
+    common::check_reference("shapes", &data);
+}
+
+#[test]
+fn materials() {
+    let context = cpu_context_or_skip!();
+    let mut scene = ListScene::new(ConstBg::new(Vector3::zeros()));
+    scene.set_max_depth(6);
+    scene.add(
+        cuboid(Vector3::new(0.8, 1.6, 0.8), Vector3::new(-2.0, 0.0, 0.8))
+            .cover(glossy(0.95, Vector3::new(1.0, 1.0, 1.0))),
+    );
+    scene.add(
+        sphere(0.4, Vector3::new(0.0, 1.0, 0.4)).cover(glossy(0.2, Vector3::new(1.0, 0.01, 0.01))),
+    );
+    scene.add(
+        sphere(0.3, Vector3::new(0.0, -1.0, 0.3)).cover(diffuse(Vector3::new(0.1, 0.01, 0.9))),
+    );
+    scene.add(
+        sphere(0.35, Vector3::new(1.0, -1.2, 0.35)).cover(MyMaterial::from(Refractive::new(1.5))),
+    );
+    scene.add(
+        sphere(0.25, Vector3::new(0.0, 0.0, 0.25)).cover(MyMaterial::from(
+            Luminous {}.color_with(20.0 * Vector3::new(1.0, 1.0, 0.2)),
+        )),
+    );
+    scene.add(
+        sphere(0.25, Vector3::new(1.0, 0.0, 0.25)).cover(MyMaterial::from(Glowing::new(
+            (0.1, Reflective {}),
+            (0.6, Diffuse {}.color_with(Vector3::new(1.0, 1.0, 1.0))),
+            (
+                0.3,
+                Luminous {}.color_with(2.0 * Vector3::new(0.01, 1.0, 0.01)),
+            ),
+        ))),
+    );
+    scene.add(
+        cuboid(Vector3::new(5.0, 5.0, 0.5), Vector3::new(0.0, 0.0, -0.5))
+            .cover(glossy(0.1, Vector3::new(1.0, 1.0, 1.0))),
+    );
+    let view = look(Vector3::new(2.0, -2.0, 2.0), Vector3::new(-1.0, 0.8, -0.8));
+
+    let data = common::render(&context, scene, view, DIMS, PASSES).unwrap();
+    common::check_reference("materials", &data);
+}
+
+#[test]
+fn light_source() {
+    let context = cpu_context_or_skip!();
+    let mut scene = TargetListScene::<_, Sphere, _>::new(GradBg::new(
+        Vector3::new(0.1, 0.1, 0.2),
+        Vector3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 1.0),
+    ));
+    scene.set_max_depth(4);
+    for i in 0..4 {
+        let (x, y) = (2.0 * ((i % 2) as f64) - 1.0, 2.0 * ((i / 2) as f64) - 1.0);
+        let (fill, color) = (0.5, Vector3::new(0.3, 0.3, 0.9));
+        let center = Vector3::new(0.0, 0.0, 1.0);
+        scene.add(
+            cuboid(
+                Vector3::new(fill / 3.0, fill / 3.0, 1.0),
+                Vector3::new(x, y, 0.0) * (1.0 - fill / 3.0) + center,
+            )
+            .cover(diffuse(color)),
+        );
+        scene.add(
+            cuboid(
+                Vector3::new(fill, 3.0 - 2.0 * fill, fill) / 3.0,
+                Vector3::new(x, 0.0, y) * (1.0 - fill / 3.0) + center,
+            )
+            .cover(diffuse(color)),
+        );
+        scene.add(
+            cuboid(
+                Vector3::new(3.0 - 2.0 * fill, fill, fill) / 3.0,
+                Vector3::new(0.0, x, y) * (1.0 - fill / 3.0) + center,
+            )
+            .cover(diffuse(color)),
+        );
+    }
+    scene.add(
+        cuboid(Vector3::new(10.0, 10.0, 0.5), Vector3::new(0.0, 0.0, -0.5))
+            .cover(diffuse(Vector3::new(0.9, 0.9, 0.9))),
+    );
+    scene.add_targeted(
+        sphere(1.0, 10.0 * Vector3::new(4.0, 6.0, 8.0)).cover(MyMaterial::from(
+            Luminous {}.color_with(2e4 * Vector3::new(1.0, 1.0, 0.8)),
+        )),
+    );
+    let view = look(Vector3::new(2.0, 0.0, 1.0), Vector3::new(-1.0, 0.0, 0.0));
+
+    let data = common::render(&context, scene, view, DIMS, PASSES).unwrap();
+    common::check_reference("light_source", &data);
+}
+
+#[test]
+fn indirect_lighting() {
+    let context = cpu_context_or_skip!();
+    let mut scene = TargetListScene::<_, Sphere, _>::new(GradBg::new(
+        10.0 * Vector3::new(0.1, 0.1, 1.0),
+        10.0 * Vector3::new(0.5, 0.5, 1.0),
+        Vector3::new(0.0, 0.0, 1.0),
+    ));
+    scene.set_max_depth(8);
+    scene.set_target_prob(0.1);
+
+    // Room with a window in the wall facing the sun
+    let (size, thc) = (Vector3::new(3.0, 3.0, 1.5), 0.05);
+    let (wpos, wsize) = (0.5, (1.6, 1.0));
+    let white = Vector3::new(0.9, 0.9, 0.9);
+    let parts = vec![
+        (
+            cuboid(
+                Vector3::new(size.x + 2.0 * thc, size.y + 2.0 * thc, thc),
+                Vector3::new(0.0, 0.0, 2.0 * size.z + thc),
+            ),
+            white,
+        ),
+        (
+            cuboid(
+                Vector3::new(thc, size.y, size.z),
+                Vector3::new(-(size.x + thc), 0.0, size.z),
+            ),
+            Vector3::new(0.4, 0.4, 1.0),
+        ),
+        (
+            cuboid(
+                Vector3::new(size.x + 2.0 * thc, thc, size.z),
+                Vector3::new(0.0, -(size.y + thc), size.z),
+            ),
+            Vector3::new(0.4, 1.0, 0.4),
+        ),
+        (
+            cuboid(
+                Vector3::new(size.x + 2.0 * thc, thc, size.z),
+                Vector3::new(0.0, size.y + thc, size.z),
+            ),
+            Vector3::new(1.0, 1.0, 0.4),
+        ),
+        (
+            cuboid(
+                Vector3::new(thc, size.y, 0.5 * wpos),
+                Vector3::new(size.x + thc, 0.0, 0.5 * wpos),
+            ),
+            white,
+        ),
+        (
+            cuboid(
+                Vector3::new(thc, size.y, size.z - wsize.1 - 0.5 * wpos),
+                Vector3::new(size.x + thc, 0.0, size.z + wsize.1 + 0.5 * wpos),
+            ),
+            white,
+        ),
+        (
+            cuboid(
+                Vector3::new(thc, 0.5 * (size.y - wsize.0), wsize.1),
+                Vector3::new(size.x + thc, 0.5 * (size.y + wsize.0), wpos + wsize.1),
+            ),
+            white,
+        ),
+        (
+            cuboid(
+                Vector3::new(thc, 0.5 * (size.y - wsize.0), wsize.1),
+                Vector3::new(size.x + thc, -0.5 * (size.y + wsize.0), wpos + wsize.1),
+            ),
+            white,
+        ),
+    ];
+    for (shape, color) in parts {
+        scene.add(shape.cover(diffuse(color)));
+    }
+    scene.add(
+        cuboid(
+            Vector3::new(size.x + 2.0 * thc, size.y + 2.0 * thc, thc),
+            Vector3::new(0.0, 0.0, -thc),
+        )
+        .cover(glossy(0.1, white)),
+    );
+    scene.add(
+        sphere(
+            0.4,
+            Vector3::new(-(size.x - 0.45), -0.2 * (size.y - 0.4), 0.4),
+        )
+        .cover(glossy(1.0, white)),
+    );
+    scene.add(
+        cuboid(
+            Vector3::new(100.0, 100.0, 0.5),
+            Vector3::new(0.0, 0.0, -0.5 - 2.0 * thc),
+        )
+        .cover(diffuse(Vector3::new(0.5, 1.0, 0.3))),
+    );
+    let (dist, lrad) = (1e4, 2e2);
+    scene.add_targeted(
+        sphere(lrad, dist * Vector3::new(1.0, -0.1, 0.2)).cover(MyMaterial::from(
+            Luminous {}.color_with(4e4 * Vector3::new(1.0, 1.0, 0.6)),
+        )),
+    );
+    let view = look(Vector3::new(1.5, -2.0, 1.0), Vector3::new(-0.85, 1.0, -0.1));
+
+    let data = common::render(&context, scene, view, DIMS, PASSES).unwrap();
+    common::check_reference("indirect_lighting", &data);
+}
+
+#[test]
+fn target_brightness() {
+    // Targets of very different brightness are sampled proportionally,
+    // but the image converges to the same one as without target sampling
+    let context = cpu_context_or_skip!();
+    let objects = || {
+        vec![
+            cuboid(Vector3::new(5.0, 5.0, 0.5), Vector3::new(0.0, 0.0, -0.5))
+                .cover(diffuse(Vector3::new(0.8, 0.8, 0.8))),
+            sphere(0.3, Vector3::new(-1.0, 0.0, 1.5)).cover(MyMaterial::from(
+                Luminous {}.color_with(100.0 * Vector3::new(1.0, 0.8, 0.6)),
+            )),
+            sphere(1.0, Vector3::new(1.5, 1.0, 2.0)).cover(MyMaterial::from(
+                Luminous {}.color_with(0.1 * Vector3::new(0.6, 0.8, 1.0)),
+            )),
+        ]
+    };
+    let view = || look(Vector3::new(0.0, -4.0, 2.0), Vector3::new(0.0, 1.0, -0.5));
+
+    let mut list = ListScene::new(ConstBg::new(Vector3::zeros()));
+    list.set_max_depth(2);
+    objects().into_iter().for_each(|o| list.add(o));
+    let mut target = TargetListScene::<_, Sphere, _>::new(ConstBg::new(Vector3::zeros()));
+    target.set_max_depth(2);
+    let mut objects = objects().into_iter();
+    target.add(objects.next().unwrap());
+    objects.for_each(|o| target.add_targeted(o));
+
+    let expected = common::render(&context, list, view(), DIMS, 16 * PASSES).unwrap();
+    let data = common::render(&context, target, view(), DIMS, PASSES).unwrap();
+    common::Reference {
+        stats: common::Stats::new(&expected),
+        mean_tolerance: 0.05,
+        histogram_tolerance: 0.2,
+    }
+    .check(&common::Stats::new(&data));
+}
+
+#[test]
+fn mis() {
+    // All the heuristics converge to the same image, including combined materials
+    let context = cpu_context_or_skip!();
+    let scene = |mis| {
+        let mut scene = TargetListScene::<_, Sphere, _>::new(ConstBg::new(Vector3::zeros()));
+        scene.set_max_depth(3);
+        scene.set_mis(mis);
+        scene.add(
+            cuboid(Vector3::new(5.0, 5.0, 0.5), Vector3::new(0.0, 0.0, -0.5))
+                .cover(glossy(0.3, Vector3::new(0.8, 0.8, 0.8))),
+        );
+        scene.add(
+            sphere(0.5, Vector3::new(0.0, 0.0, 0.5)).cover(diffuse(Vector3::new(0.9, 0.5, 0.3))),
+        );
+        scene.add_targeted(
+            sphere(0.5, Vector3::new(-1.0, 1.0, 2.0)).cover(MyMaterial::from(
+                Luminous {}.color_with(10.0 * Vector3::new(1.0, 1.0, 1.0)),
+            )),
+        );
+        scene
+    };
+    let view = || look(Vector3::new(0.0, -4.0, 2.0), Vector3::new(0.0, 1.0, -0.5));
+
+    let expected = common::render(
+        &context,
+        scene(MisHeuristic::Disabled),
+        view(),
+        DIMS,
+        4 * PASSES,
+    )
+    .unwrap();
+    for &mis in [MisHeuristic::Balance, MisHeuristic::Power].iter() {
+        let data = common::render(&context, scene(mis), view(), DIMS, 4 * PASSES).unwrap();
+        common::Reference {
+            stats: common::Stats::new(&expected),
+            mean_tolerance: 0.03,
+            histogram_tolerance: 0.1,
+        }
+        .check(&common::Stats::new(&data));
+    }
+}
+
+#[test]
+fn target_miss() {
+    // The flat lamp is sampled by the cone of its bounding sphere, so many targeted rays
+    // miss it, but they must not gather the background that is already gathered
+    // by the material sampling
+    let context = cpu_context_or_skip!();
+    let background = || ConstBg::new(Vector3::new(0.5, 0.5, 0.5));
+    let objects = || {
+        vec![
+            cuboid(Vector3::new(5.0, 5.0, 0.5), Vector3::new(0.0, 0.0, -0.5))
+                .cover(diffuse(Vector3::new(0.8, 0.8, 0.8))),
+            MyShape::from(Ellipsoid::new(
+                Matrix3::from_diagonal(&Vector3::new(1.0, 1.0, 0.02)),
+                Vector3::new(0.0, 0.0, 2.0),
+            ))
+            .cover(MyMaterial::from(
+                Luminous {}.color_with(5.0 * Vector3::new(1.0, 1.0, 1.0)),
+            )),
+        ]
+    };
+    let view = || look(Vector3::new(0.0, -4.0, 2.0), Vector3::new(0.0, 1.0, -0.5));
+
+    let mut list = ListScene::new(background());
+    list.set_max_depth(2);
+    objects().into_iter().for_each(|o| list.add(o));
+    let mut target = TargetListScene::<_, Sphere, _>::new(background());
+    target.set_max_depth(2);
+    let mut objects = objects().into_iter();
+    target.add(objects.next().unwrap());
+    objects.for_each(|o| target.add_targeted(o));
+
+    let expected = common::render(&context, list, view(), DIMS, 4 * PASSES).unwrap();
+    let data = common::render(&context, target, view(), DIMS, 4 * PASSES).unwrap();
+    common::Reference {
+        stats: common::Stats::new(&expected),
+        mean_tolerance: 0.03,
+        histogram_tolerance: 0.1,
+    }
+    .check(&common::Stats::new(&data));
+}
+
+#[test]
+fn disk_light() {
+    // Disk lights are sampled over their area, both with and without MIS
+    let context = cpu_context_or_skip!();
+    let objects = || {
+        vec![
+            Disk::new(5.0, Vector3::zeros(), Vector3::z())
+                .cover(diffuse(Vector3::new(0.8, 0.8, 0.8))),
+            Disk::new(
+                0.5,
+                Vector3::new(0.0, 0.0, 1.5),
+                Vector3::new(1.0, 0.0, -0.2),
+            )
+            .cover(MyMaterial::from(
+                Luminous {}.color_with(10.0 * Vector3::new(1.0, 1.0, 1.0)),
+            )),
+        ]
+    };
+    let view = || look(Vector3::new(0.0, -4.0, 2.0), Vector3::new(0.0, 1.0, -0.5));
+
+    let mut list = ListScene::new(ConstBg::new(Vector3::zeros()));
+    objects().into_iter().for_each(|o| list.add(o));
+    let expected = common::render(&context, list, view(), DIMS, 16 * PASSES).unwrap();
+
+    for &mis in [MisHeuristic::Disabled, MisHeuristic::Power].iter() {
+        let mut target = TargetListScene::<_, Disk, _>::new(ConstBg::new(Vector3::zeros()));
+        target.set_mis(mis);
+        let mut objects = objects().into_iter();
+        target.add(objects.next().unwrap());
+        objects.for_each(|o| target.add_targeted(o));
+        let data = common::render(&context, target, view(), DIMS, PASSES).unwrap();
+        common::Reference {
+            stats: common::Stats::new(&expected),
+            mean_tolerance: 0.05,
+            histogram_tolerance: 0.2,
+        }
+        .check(&common::Stats::new(&data));
+    }
+}
+
+fn grid() -> Vec<MyObject> {
+    let mut objects = Vec::new();
+    for i in 0..64 {
+        let pos = Vector3::new((i % 8) as f64 - 3.5, (i / 8) as f64 - 3.5, 0.3);
+        let color = Vector3::new((i % 8) as f64 / 8.0, (i / 8) as f64 / 8.0, 0.5);
+        objects.push(match i % 3 {
+            0 => cuboid(Vector3::new(0.3, 0.3, 0.3), pos).cover(diffuse(color)),
+            _ => sphere(0.1 * (i % 3 + 2) as f64, pos).cover(glossy(0.3, color)),
+        });
+    }
+    objects.push(
+        cuboid(Vector3::new(10.0, 10.0, 0.5), Vector3::new(0.0, 0.0, -0.5))
+            .cover(diffuse(Vector3::new(0.9, 0.9, 0.9))),
+    );
+    objects
+}
+
+#[test]
+fn bvh() {
+    // Hierarchy traversal finds the same hits as the linear search
+    let context = cpu_context_or_skip!();
+    let view = || look(Vector3::new(5.0, -6.0, 4.0), Vector3::new(-5.0, 6.0, -4.0));
+
+    let mut list = ListScene::new(sky());
+    grid().into_iter().for_each(|o| list.add(o));
+    let mut bvh = BvhScene::new(sky());
+    grid().into_iter().for_each(|o| bvh.add(o));
+
+    let expected = common::render(&context, list, view(), DIMS, PASSES).unwrap();
+    let data = common::render(&context, bvh, view(), DIMS, PASSES).unwrap();
+    common::Reference {
+        stats: common::Stats::new(&expected),
+        mean_tolerance: 0.02,
+        histogram_tolerance: 0.05,
+    }
+    .check(&common::Stats::new(&data));
+}
+
+/// The device output of the shapes and views is compared with the host tracer.
+#[cfg(feature = "cpu")]
+mod cpu {
+    use super::*;
+    use clay::{
+        cpu::{CpuShape, CpuView},
+        map::{Rotation, Scale3},
+        view::*,
+    };
+
+    const PASSES: usize = 16;
+
+    fn paint(color: Vector3<f64>) -> Colored<Diffuse> {
+        Diffuse {}.color_with(color)
+    }
+
+    fn facing(dir: Vector3<f64>) -> Rotation3<f64> {
+        Rotation3::face_towards(&-dir, &Vector3::z_axis())
+    }
+
+    /// Renders the shape under the sky.
+    fn compare_shape<T: Shape + CpuShape>(shape: T) {
+        let context = cpu_context_or_skip!();
+        let mut scene = ListScene::new(sky());
+        scene.add(shape.cover(paint(Vector3::new(0.9, 0.6, 0.3))));
+        let view = look(Vector3::new(3.0, -4.0, 2.5), Vector3::new(-3.0, 4.0, -2.5));
+        common::compare_with_cpu(&context, scene, view, DIMS, PASSES);
+    }
+
+    /// Renders two spheres under the sky.
+    fn compare_view<V: View + CpuView>(view: V) {
+        let context = cpu_context_or_skip!();
+        let mut scene = ListScene::new(sky());
+        scene.add(
+            Sphere::new(0.75, Vector3::new(-0.75, 0.0, 0.0))
+                .cover(paint(Vector3::new(0.4, 1.0, 0.4))),
+        );
+        scene.add(
+            Sphere::new(1.0, Vector3::new(1.0, 0.5, 0.0)).cover(paint(Vector3::new(0.4, 0.4, 1.0))),
+        );
+        common::compare_with_cpu(&context, scene, view, DIMS, PASSES);
+    }
+
+    #[test]
+    fn triangle_mesh() {
+        let obj = "\
+            v -1 -1 -1\nv 1 -1 -1\nv 1 1 -1\nv -1 1 -1\nv 0 0 1\n\
+            f 1 4 3 2\nf 1 2 5\nf 2 3 5\nf 3 4 5\nf 4 1 5\n";
+        let data = MeshData::read_obj(obj.as_bytes()).unwrap();
+        compare_shape(TriangleMesh::new(data).unwrap());
+    }
+
+    #[test]
+    fn plane() {
+        compare_shape(Plane::new(
+            Vector3::new(0.0, 0.0, -0.5),
+            Vector3::new(0.0, 0.2, 1.0),
+        ));
+    }
+
+    #[test]
+    fn disk() {
+        compare_shape(Disk::new(
+            1.0,
+            Vector3::zeros(),
+            Vector3::new(1.0, -1.0, 1.0),
+        ));
+    }
+
+    #[test]
+    fn cylinder() {
+        compare_shape(Cylinder::new(
+            Matrix3::from_diagonal(&Vector3::new(0.5, 0.5, 1.0)),
+            Vector3::zeros(),
+        ));
+    }
+
+    #[test]
+    fn cone() {
+        compare_shape(Cone::new(
+            Matrix3::from_diagonal(&Vector3::new(0.8, 0.8, 1.2)),
+            Vector3::zeros(),
+        ));
+    }
+
+    #[test]
+    fn capsule() {
+        compare_shape(Capsule::new(
+            0.4,
+            Vector3::new(-0.5, 0.0, -0.5),
+            Vector3::new(0.5, 0.0, 0.5),
+        ));
+    }
+
+    #[test]
+    fn torus() {
+        let ori = Rotation3::from_axis_angle(&Vector3::x_axis(), 0.5);
+        compare_shape(Torus::new(0.3, *ori.matrix(), Vector3::zeros()));
+    }
+
+    #[test]
+    fn csg() {
+        compare_shape(Difference::new(
+            Sphere::new(1.0, Vector3::zeros()),
+            Sphere::new(0.7, Vector3::new(0.5, -0.5, 0.5)),
+        ));
+    }
+
+    #[test]
+    fn sdf() {
+        compare_shape(SdfShape::new(SmoothUnion::new(
+            SdfTorus::new(0.8, 0.25, Vector3::zeros()),
+            SdfSphere::new(0.5, Vector3::new(0.0, 0.0, 0.4)),
+            0.2,
+        )));
+    }
+
+    #[test]
+    fn heightfield() {
+        let field = Heightfield::from_fn((16, 16), |x, y| {
+            0.3 + 0.2 * (3.0 * x).sin() * (2.0 * y).cos()
+        })
+        .unwrap();
+        compare_shape(field);
+    }
+
+    #[test]
+    fn lens() {
+        let mut view = look(Vector3::new(0.25, -3.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        view.set_lens(0.2, 3.0);
+        view.blades = 5;
+        compare_view(view);
+    }
+
+    #[test]
+    fn orthographic() {
+        let dir = Vector3::new(0.0, 1.0, -0.3);
+        compare_view(OrthographicView::new(-3.0 * dir, facing(dir), 4.0));
+    }
+
+    #[test]
+    fn panoramic() {
+        let dir = Vector3::new(0.0, 1.0, 0.0);
+        compare_view(PanoramicView::new(-3.0 * dir, facing(dir)));
+    }
+
+    #[test]
+    fn fisheye() {
+        let dir = Vector3::new(0.0, 1.0, 0.0);
+        compare_view(FisheyeView::new(-3.0 * dir, facing(dir)));
+    }
+
+    #[test]
+    fn stereo() {
+        let view = look(Vector3::new(0.25, -3.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        compare_view(StereoView::new(view, 0.2, 3.0));
+    }
+
+    #[test]
+    fn shutter() {
+        let mut view = look(Vector3::new(0.25, -3.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        view.shutter = Some(Shutter::new(
+            Vector3::new(0.75, -3.0, 0.5),
+            facing(Vector3::new(0.2, 1.0, 0.0)),
+        ));
+        compare_view(view);
+    }
+
+    #[test]
+    fn sheared_parallelepiped() {
+        compare_shape(Parallelepiped::new(
+            Matrix3::new(1.0, 0.8, 0.0, 0.0, 0.6, 0.4, 0.0, 0.0, 0.5),
+            Vector3::zeros(),
+        ));
+    }
+
+    #[test]
+    fn linear_row_order() {
+        // The long axis of the ellipsoid is turned across the view, while the transposed
+        // matrix makes it axis-aligned and much shorter on the screen, so the images
+        // differ unless the device reads the matrix in the same order as the host
+        let rot = Rotation3::from_axis_angle(&Vector3::z_axis(), -0.93);
+        let ori = rot.matrix() * Matrix3::from_diagonal(&Vector3::new(0.4, 1.5, 0.4));
+        compare_shape(Ellipsoid::new(ori, Vector3::zeros()));
+    }
+
+    #[test]
+    fn rotated_cube() {
+        let rot = Rotation3::from_axis_angle(&Vector3::y_axis(), 0.4)
+            * Rotation3::from_axis_angle(&Vector3::x_axis(), 0.3);
+        let map = Scale3::from(Vector3::new(0.5, 0.8, 1.2)).chain(Rotation::from(rot));
+        compare_shape(UnitCube::new().map(map));
+    }
+}